@@ -1,6 +1,9 @@
+const crypto = require("crypto");
 const http = require("http");
 const https = require("https");
 const KHReportingURL = "KH_REPORTING_URL";
+const KHRunUUID = "KH_RUN_UUID";
+const KHRunKey = "KH_RUN_KEY";
 
 /**
  * ReportSuccess reports a success to kuberhealthy.
@@ -69,6 +72,29 @@ function sendReport(statusReport) {
         throw urlErr;
     }
 
+    // Fetch the kuberhealthy run UUID.
+    let runUUID;
+    try {
+        runUUID = getKuberhealthyRunUUID();
+    } catch (err) {
+        // Throw an error if there was a problem fetching the run UUID.
+        let uuidErr = new Error("failed to fetch the kuberhealthy run uuid: " + err.message);
+        throw uuidErr;
+    }
+
+    // Build the request headers.  The report is signed with the key of this run so kuberhealthy can verify it
+    // came from this pod.  Checks started by versions of kuberhealthy that do not give out run keys send their
+    // reports unsigned.
+    let headers = {
+        "Content-Type": "application/json",
+        "Content-Length": Buffer.byteLength(data),
+        "kh-run-uuid": runUUID,
+    };
+    let runKey = process.env[KHRunKey];
+    if (runKey) {
+        headers["kh-signature"] = signReport(runKey, data);
+    }
+
     // Check the protocol used for the reporting URL.
     let httpsOn = false;
     if (khURL.protocol.localeCompare("https") == 0) {
@@ -83,10 +109,7 @@ function sendReport(statusReport) {
             port: 443,
             path: khURL.pathname,
             method: "POST",
-            headers: {
-                "Content-Type": "application/json",
-                "Content-Length": data.length,
-            },
+            headers: headers,
         };
 
         // Send a POST via https.
@@ -119,10 +142,7 @@ function sendReport(statusReport) {
         port: 80,
         path: khURL.pathname,
         method: "POST",
-        headers: {
-            "Content-Type": "application/json",
-            "Content-Length": data.length,
-        },
+        headers: headers,
     };
 
     // Send a POST via http.
@@ -169,6 +189,32 @@ function getKuberhealthyURL() {
     return reportingURL;
 }
 
+/**
+ * getKuberhealthyRunUUID retrieves the UUID of this check run from the environment and returns it.
+ * @returns {string} Returns the run UUID.
+ * @throws Throws an error if the run UUID is blank.
+ */
+function getKuberhealthyRunUUID() {
+    let runUUID = process.env[KHRunUUID];
+
+    // Throw an error if the UUID is empty.
+    if (!runUUID) {
+        throw new Error("fetched " + KHRunUUID + " environment variable but it was blank");
+    }
+
+    return runUUID;
+}
+
+/**
+ * signReport signs a report body with an HMAC-SHA256 keyed with the run key.
+ * @param {string} runKey - The key of this check run.
+ * @param {string} data - The JSON body of the report.
+ * @returns {string} Returns the hex encoded signature.
+ */
+function signReport(runKey, data) {
+    return crypto.createHmac("sha256", runKey).update(data).digest("hex");
+}
+
 /**
  * newReport creates a new error report to be sent to the kuberhealthy server. If the
  * number of errors supplied is 0, then we assume the status report is OK. If any errors
@@ -1,4 +1,6 @@
 import dataclasses
+import hashlib
+import hmac
 import json
 import os
 from dataclasses import dataclass
@@ -38,6 +40,18 @@ def get_kuberhealthy_url():
     return reporting_url_env
 
 
+def get_kuberhealthy_run_uuid():
+    run_uuid = os.environ.get("KH_RUN_UUID", "")
+    if not run_uuid:
+        raise Exception("fetched KH_RUN_UUID environment variable but it was blank")
+    return run_uuid
+
+
+def sign_report(run_key: str, data: bytes) -> str:
+    # reports are signed with an HMAC-SHA256 of the request body, keyed with the run key
+    return hmac.new(run_key.encode("utf-8"), data, hashlib.sha256).hexdigest()
+
+
 def send_report(status_report: StatusReport):
     try:
         data = json.dumps(dataclasses.asdict(status_report))
@@ -49,7 +63,21 @@ def send_report(status_report: StatusReport):
     except Exception as e:
         raise Exception(f"failed to fetch the kuberhealthy url: {e}")
 
-    response = requests.post(kh_url, data=data, headers={"Content-Type": "application/json"})
+    try:
+        run_uuid = get_kuberhealthy_run_uuid()
+    except Exception as e:
+        raise Exception(f"failed to fetch the kuberhealthy run uuid: {e}")
+
+    body = data.encode("utf-8")
+    headers = {"Content-Type": "application/json", "kh-run-uuid": run_uuid}
+
+    # sign the report with the key of this run. checks started by versions of kuberhealthy that do not give out
+    # run keys send their reports unsigned.
+    run_key = os.environ.get("KH_RUN_KEY", "")
+    if run_key:
+        headers["kh-signature"] = sign_report(run_key, body)
+
+    response = requests.post(kh_url, data=body, headers=headers)
     try:
         response.raise_for_status()
     except HTTPError as e:
@@ -20,6 +20,7 @@ import (
 
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external/checkclient"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external/nodeCheck"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external/status"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/kubeClient"
 )
 
@@ -51,6 +52,7 @@ type Checker struct {
 	client           *kubernetes.Clientset
 	MaxTimeInFailure time.Duration
 	Hostname         string
+	lookupDuration   time.Duration // how long the DNS lookups took during the last run
 }
 
 func init() {
@@ -141,7 +143,9 @@ func (dc *Checker) Run(client *kubernetes.Clientset) error {
 	dc.client = client
 	// run the check in a goroutine and notify the doneChan when completed
 	go func(doneChan chan error) {
+		lookupStart := time.Now()
 		err := dc.doChecks()
+		dc.lookupDuration = time.Since(lookupStart)
 		doneChan <- err
 	}(doneChan)
 
@@ -157,10 +161,13 @@ func (dc *Checker) Run(client *kubernetes.Clientset) error {
 		}
 		return err
 	case err := <-doneChan:
+		lookupMetrics := []status.Metric{
+			{Name: "lookup_duration", Value: dc.lookupDuration.Seconds(), Unit: "seconds", Labels: map[string]string{"hostname": dc.Hostname}},
+		}
 		if err != nil {
-			return reportKHFailure(err.Error())
+			return reportKHFailure(err.Error(), lookupMetrics)
 		}
-		return reportKHSuccess()
+		return reportKHSuccess(lookupMetrics)
 	}
 }
 
@@ -273,8 +280,10 @@ func (dc *Checker) doChecks() error {
 }
 
 // reportKHSuccess reports success to Kuberhealthy servers and verifies the report successfully went through
-func reportKHSuccess() error {
-	err := checkclient.ReportSuccess()
+func reportKHSuccess(lookupMetrics []status.Metric) error {
+	report := status.NewReport([]string{})
+	report.Metrics = lookupMetrics
+	err := checkclient.SendReport(report)
 	if err != nil {
 		log.Println("Error reporting success to Kuberhealthy servers:", err)
 		return err
@@ -284,8 +293,10 @@ func reportKHSuccess() error {
 }
 
 // reportKHFailure reports failure to Kuberhealthy servers and verifies the report successfully went through
-func reportKHFailure(errorMessage string) error {
-	err := checkclient.ReportFailure([]string{errorMessage})
+func reportKHFailure(errorMessage string, lookupMetrics []status.Metric) error {
+	report := status.NewReport([]string{errorMessage})
+	report.Metrics = lookupMetrics
+	err := checkclient.SendReport(report)
 	if err != nil {
 		log.Println("Error reporting failure to Kuberhealthy servers:", err)
 		return err
@@ -16,6 +16,7 @@ import (
 
 	kh "github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external/checkclient"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external/nodeCheck"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external/status"
 )
 
 var (
@@ -147,6 +148,7 @@ func main() {
 	checksFailed := 0
 
 	// if we have a pause, start a ticker
+	var requestDurationTotal time.Duration
 	var ticker *time.Ticker
 	if secondInt > 0 {
 		ticker = time.NewTicker(time.Duration(secondInt) * time.Second)
@@ -156,11 +158,13 @@ func main() {
 	// This for loop makes a http GET request to a known internet address, address can be changed in deployment spec yaml
 	// and returns a http status every second.
 	for checksRan < countInt {
+		requestStart := time.Now()
 		r, err := callAPI(APIRequest{
 			URL:  parsedUrl,
 			Type: requestType,
 			Body: bytes.NewBuffer([]byte(requestBody)),
 		})
+		requestDurationTotal += time.Since(requestStart)
 		checksRan++
 
 		if err != nil {
@@ -189,12 +193,24 @@ func main() {
 	log.Infoln(checksFailed, "checks failed")
 
 	// Check to see if the number of requests passed at passingPercent and reports to Kuberhealthy accordingly
+	var errorMessages []string
 	if checksPassed < passInt {
 		reportErr := fmt.Errorf("unable to retrieve a valid response (expected status: %d) from %s %s checks failed %d out of %d attempts", expectedStatusCodeInt, requestType, parsedUrl.Redacted(), checksFailed, checksRan)
-		ReportFailureAndExit(reportErr)
+		log.Errorln(reportErr)
+		errorMessages = append(errorMessages, reportErr.Error())
 	}
 
-	err = kh.ReportSuccess()
+	// include the request counts and latency from this run in the report
+	report := status.NewReport(errorMessages)
+	urlLabels := map[string]string{"url": parsedUrl.Redacted()}
+	report.AddMetric("checks_ran", float64(checksRan), "", urlLabels)
+	report.AddMetric("checks_passed", float64(checksPassed), "", urlLabels)
+	report.AddMetric("checks_failed", float64(checksFailed), "", urlLabels)
+	if checksRan > 0 {
+		report.AddMetric("request_duration_average", (requestDurationTotal / time.Duration(checksRan)).Seconds(), "seconds", urlLabels)
+	}
+
+	err = kh.SendReport(report)
 	if err != nil {
 		log.Fatalln("error when reporting to kuberhealthy:", err.Error())
 	}
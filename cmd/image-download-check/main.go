@@ -8,6 +8,7 @@ import (
 	v1 "github.com/google/go-containerregistry/pkg/v1"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external/checkclient"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external/nodeCheck"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external/status"
 	log "github.com/sirupsen/logrus"
 )
 
@@ -37,16 +38,16 @@ func main() {
 	var err error
 
 	// run check
-	pass := checkPass()
+	pass, imageMetrics := checkPass()
 
 	// report success or failure to Kuberhealthy servers
 	if pass {
-		err = reportKHSuccess()
+		err = reportKHSuccess(imageMetrics)
 		if err != nil {
 			log.Println("there was an error reporting success to KH", err)
 		}
 	} else {
-		err = reportKHFailure("check has failed, reporting failure to KH")
+		err = reportKHFailure("check has failed, reporting failure to KH", imageMetrics)
 		if err != nil {
 			log.Println("there was an error reporting failure to KH", err)
 		}
@@ -54,14 +55,15 @@ func main() {
 }
 
 // checkPass implements the logic to pull an image, track a start and end time, then
-// determines if the actual pull time is greater than the specified timeoutLimit
-func checkPass() bool {
+// determines if the actual pull time is greater than the specified timeoutLimit.  The
+// measurements taken of the download are returned to be reported to Kuberhealthy.
+func checkPass() (bool, []status.Metric) {
 
 	// initialize a start time
 	startTime := time.Now()
 
 	// download image
-	img, err := downloadImage()
+	img, imageMetrics, err := downloadImage()
 	if err != nil {
 		log.Fatalln(err)
 	}
@@ -71,6 +73,7 @@ func checkPass() bool {
 	endTime := time.Now()
 	duration := endTime.Sub(startTime)
 	log.Println("image took this many seconds to download: ", duration.Seconds())
+	imageMetrics = append(imageMetrics, status.Metric{Name: "download_duration", Value: duration.Seconds(), Unit: "seconds"})
 
 	// determine if duration exceeds the time limit threshold
 	timeoutLimitDuration, err := time.ParseDuration(timeoutLimit)
@@ -82,48 +85,55 @@ func checkPass() bool {
 	log.Println("checking to see if", duration, "<", timeoutLimitDuration)
 	if duration < timeoutLimitDuration {
 		log.Println("check passes, download duration is less than timeout limit.")
-		return true
+		return true, imageMetrics
 	}
 
 	log.Println("check fails, download duration is greater than timeout limit.")
-	return false
+	return false, imageMetrics
 }
 
-// downloadImage pulls an image from a specified fullImageURL
-func downloadImage() (v1.Image, error) {
+// downloadImage pulls an image from a specified fullImageURL and returns the layer
+// count and size of the image as metrics
+func downloadImage() (v1.Image, []status.Metric, error) {
 
 	// pull image
 	i, err := crane.Pull(fullImageURL)
 	if err != nil {
-		return nil, err
+		return nil, nil, err
 	}
 
 	// save image tarball to path
 	err = crane.Save(i, "emptytag", "/dev/null")
 	if err != nil {
-		return nil, err
+		return nil, nil, err
 	}
 
 	// get layer count - informative
 	l, err := i.Layers()
 	if err != nil {
-		return nil, err
+		return nil, nil, err
 	}
 	log.Println("layer count", len(l))
 
 	// get image size - informative
 	s, err := i.Size()
 	if err != nil {
-		return nil, err
+		return nil, nil, err
 	}
 	log.Println("image size", s)
 
-	return i, nil
+	imageMetrics := []status.Metric{
+		{Name: "image_layers", Value: float64(len(l))},
+		{Name: "image_size", Value: float64(s), Unit: "bytes"},
+	}
+	return i, imageMetrics, nil
 }
 
 // reportKHSuccess reports success to Kuberhealthy servers and verifies the report successfully went through
-func reportKHSuccess() error {
-	err := checkclient.ReportSuccess()
+func reportKHSuccess(imageMetrics []status.Metric) error {
+	report := status.NewReport([]string{})
+	report.Metrics = imageMetrics
+	err := checkclient.SendReport(report)
 	if err != nil {
 		log.Println("Error reporting success to Kuberhealthy servers:", err)
 		return err
@@ -133,8 +143,10 @@ func reportKHSuccess() error {
 }
 
 // reportKHFailure reports failure to Kuberhealthy servers and verifies the report successfully went through
-func reportKHFailure(errorMessage string) error {
-	err := checkclient.ReportFailure([]string{errorMessage})
+func reportKHFailure(errorMessage string, imageMetrics []status.Metric) error {
+	report := status.NewReport([]string{errorMessage})
+	report.Metrics = imageMetrics
+	err := checkclient.SendReport(report)
 	if err != nil {
 		log.Println("Error reporting failure to Kuberhealthy servers:", err)
 		return err
@@ -0,0 +1,360 @@
+package main
+
+import (
+	"encoding/json"
+	"net/http"
+	"sort"
+	"strings"
+	"time"
+
+	log "github.com/sirupsen/logrus"
+	v1 "k8s.io/api/core/v1"
+	"k8s.io/apimachinery/pkg/api/meta"
+	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
+	"k8s.io/apimachinery/pkg/fields"
+	"k8s.io/client-go/tools/cache"
+
+	khcheckv1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khcheck/v1"
+	khjobv1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khjob/v1"
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external"
+)
+
+// WorkloadSpecSummary summarizes the spec of a khcheck or khjob for the check API
+type WorkloadSpecSummary struct {
+	RunInterval  string       `json:"runInterval,omitempty"`  // the interval the check runs on
+	Schedule     string       `json:"schedule,omitempty"`     // the cron schedule the check runs on
+	Timeout      string       `json:"timeout"`                // the maximum time a run is allowed to take
+	Phase        string       `json:"phase,omitempty"`        // the phase of a khjob
+	Images       []string     `json:"images"`                 // the images of the containers in the checker pod
+	Suspend      bool         `json:"suspend,omitempty"`      // true when the check is suspended
+	SuspendUntil *metav1.Time `json:"suspendUntil,omitempty"` // the time the check is suspended until
+	DependsOn    []string     `json:"dependsOn,omitempty"`    // the khchecks that must be passing for the check to run
+}
+
+// WorkloadStatus is the status of a single khcheck or khjob as returned by the check API
+type WorkloadStatus struct {
+	Name      string                    `json:"name"`
+	Namespace string                    `json:"namespace"`
+	Spec      WorkloadSpecSummary       `json:"spec"`
+	Status    khstatev1.WorkloadDetails `json:"status"`            // the current khstate of the check or job
+	LastRun   *metav1.Time              `json:"lastRun,omitempty"` // the last time the check or job reported in
+	NextRun   *metav1.Time              `json:"nextRun,omitempty"` // the estimated time of the next scheduled check run
+}
+
+// apiError is the body returned by the check API when a request fails
+type apiError struct {
+	Error string `json:"error"`
+}
+
+// registerAPIHandlers adds the check API routes to the default http mux
+func (k *Kuberhealthy) registerAPIHandlers() {
+	http.HandleFunc("GET /api/v1/checks", func(w http.ResponseWriter, r *http.Request) {
+		err := k.listChecksAPIHandler(w, r)
+		if err != nil {
+			log.Errorln("check API error:", err)
+		}
+	})
+	http.HandleFunc("GET /api/v1/checks/{namespace}/{name}", func(w http.ResponseWriter, r *http.Request) {
+		err := k.checkAPIHandler(w, r)
+		if err != nil {
+			log.Errorln("check API error:", err)
+		}
+	})
+	http.HandleFunc("POST /api/v1/checks/{namespace}/{name}/run", func(w http.ResponseWriter, r *http.Request) {
+		err := k.runCheckAPIHandler(w, r)
+		if err != nil {
+			log.Errorln("check API error:", err)
+		}
+	})
+	http.HandleFunc("GET /api/v1/jobs", func(w http.ResponseWriter, r *http.Request) {
+		err := k.listJobsAPIHandler(w, r)
+		if err != nil {
+			log.Errorln("check API error:", err)
+		}
+	})
+	http.HandleFunc("GET /api/v1/jobs/{namespace}/{name}", func(w http.ResponseWriter, r *http.Request) {
+		err := k.jobAPIHandler(w, r)
+		if err != nil {
+			log.Errorln("check API error:", err)
+		}
+	})
+}
+
+// newJobInformer creates an informer that caches khjob resources so that the check API does not list them from the
+// API server on every request
+func (k *Kuberhealthy) newJobInformer() (cache.Store, cache.Controller) {
+	khJobListWatch := cache.NewListWatchFromClient(khJobClient.RESTClient(), jobCRDResource, k.TargetNamespace, fields.Everything())
+	return cache.NewInformer(khJobListWatch, &khjobv1.KuberhealthyJob{}, checkResyncPeriod, cache.ResourceEventHandlerFuncs{})
+}
+
+// listChecksAPIHandler returns the status of every khcheck from the khcheck informer cache, optionally filtered with
+// the ?namespace= query
+func (k *Kuberhealthy) listChecksAPIHandler(w http.ResponseWriter, r *http.Request) error {
+	log.Infoln("Client connected to check API from", r.RemoteAddr, r.UserAgent())
+
+	namespaces, authorized := k.authorizeAPIRequest(w, r, namespacesFromQuery(r), k.checkStore)
+	if !authorized {
+		return nil
+	}
+	statuses := []WorkloadStatus{}
+	now := time.Now()
+	for _, obj := range k.checkStore.List() {
+		kc, ok := obj.(*khcheckv1.KuberhealthyCheck)
+		if !ok {
+			continue
+		}
+		if namespaces != nil && !containsString(kc.Namespace, namespaces) {
+			continue
+		}
+		statuses = append(statuses, k.checkStatus(*kc, now))
+	}
+	sortWorkloadStatuses(statuses)
+
+	return writeAPIResponse(w, http.StatusOK, statuses)
+}
+
+// checkAPIHandler returns the status of a single khcheck from the khcheck informer cache
+func (k *Kuberhealthy) checkAPIHandler(w http.ResponseWriter, r *http.Request) error {
+	log.Infoln("Client connected to check API from", r.RemoteAddr, r.UserAgent())
+
+	namespace := r.PathValue("namespace")
+	name := r.PathValue("name")
+	_, authorized := k.authorizeAPIRequest(w, r, []string{namespace}, k.checkStore)
+	if !authorized {
+		return nil
+	}
+	obj, exists, err := k.checkStore.GetByKey(namespace + "/" + name)
+	if err != nil {
+		writeAPIError(w, http.StatusInternalServerError, "failed to get khcheck: "+err.Error())
+		return err
+	}
+	kc, ok := obj.(*khcheckv1.KuberhealthyCheck)
+	if !exists || !ok {
+		writeAPIError(w, http.StatusNotFound, "khcheck "+namespace+"/"+name+" not found")
+		return nil
+	}
+
+	return writeAPIResponse(w, http.StatusOK, k.checkStatus(*kc, time.Now()))
+}
+
+// listJobsAPIHandler returns the status of every khjob from the khjob informer cache, optionally filtered with the
+// ?namespace= query
+func (k *Kuberhealthy) listJobsAPIHandler(w http.ResponseWriter, r *http.Request) error {
+	log.Infoln("Client connected to check API from", r.RemoteAddr, r.UserAgent())
+
+	namespaces, authorized := k.authorizeAPIRequest(w, r, namespacesFromQuery(r), k.jobStore)
+	if !authorized {
+		return nil
+	}
+	statuses := []WorkloadStatus{}
+	for _, obj := range k.jobStore.List() {
+		kj, ok := obj.(*khjobv1.KuberhealthyJob)
+		if !ok {
+			continue
+		}
+		if namespaces != nil && !containsString(kj.Namespace, namespaces) {
+			continue
+		}
+		statuses = append(statuses, k.jobStatus(*kj))
+	}
+	sortWorkloadStatuses(statuses)
+
+	return writeAPIResponse(w, http.StatusOK, statuses)
+}
+
+// jobAPIHandler returns the status of a single khjob from the khjob informer cache
+func (k *Kuberhealthy) jobAPIHandler(w http.ResponseWriter, r *http.Request) error {
+	log.Infoln("Client connected to check API from", r.RemoteAddr, r.UserAgent())
+
+	namespace := r.PathValue("namespace")
+	name := r.PathValue("name")
+	_, authorized := k.authorizeAPIRequest(w, r, []string{namespace}, k.jobStore)
+	if !authorized {
+		return nil
+	}
+	obj, exists, err := k.jobStore.GetByKey(namespace + "/" + name)
+	if err != nil {
+		writeAPIError(w, http.StatusInternalServerError, "failed to get khjob: "+err.Error())
+		return err
+	}
+	kj, ok := obj.(*khjobv1.KuberhealthyJob)
+	if !exists || !ok {
+		writeAPIError(w, http.StatusNotFound, "khjob "+namespace+"/"+name+" not found")
+		return nil
+	}
+
+	return writeAPIResponse(w, http.StatusOK, k.jobStatus(*kj))
+}
+
+// authorizeAPIRequest returns the namespaces out of the requested ones that the caller of the check API may see.  When
+// status page authentication is enabled, the caller must be allowed to get khstates in the namespaces, and the
+// namespaces of the resources in the store are considered when none were requested.  Returns false when the caller
+// was not authorized, after replying to them with an error.
+func (k *Kuberhealthy) authorizeAPIRequest(w http.ResponseWriter, r *http.Request, requested []string, store cache.Store) ([]string, bool) {
+	if !cfg.StatusPageAuth {
+		return requested, true
+	}
+
+	namespaces, statusCode, err := k.authorizeNamespaces(r, requested, func() []string {
+		return storeNamespaces(store)
+	})
+	if err != nil {
+		log.Warningln("Check API request from", r.RemoteAddr, "was not authorized:", err)
+		writeAPIError(w, statusCode, err.Error())
+		return nil, false
+	}
+	return namespaces, true
+}
+
+// storeNamespaces returns every namespace that has a resource in the supplied informer cache
+func storeNamespaces(store cache.Store) []string {
+	var namespaces []string
+	for _, obj := range store.List() {
+		o, err := meta.Accessor(obj)
+		if err != nil {
+			continue
+		}
+		if !containsString(o.GetNamespace(), namespaces) {
+			namespaces = append(namespaces, o.GetNamespace())
+		}
+	}
+	return namespaces
+}
+
+// sortWorkloadStatuses sorts check API statuses by namespace and then by name, as the informer caches are unordered
+func sortWorkloadStatuses(statuses []WorkloadStatus) {
+	sort.Slice(statuses, func(i, j int) bool {
+		if statuses[i].Namespace != statuses[j].Namespace {
+			return statuses[i].Namespace < statuses[j].Namespace
+		}
+		return statuses[i].Name < statuses[j].Name
+	})
+}
+
+// checkStatus builds the check API status of a khcheck from its spec and its cached khstate
+func (k *Kuberhealthy) checkStatus(kc khcheckv1.KuberhealthyCheck, now time.Time) WorkloadStatus {
+	status := WorkloadStatus{
+		Name:      kc.Name,
+		Namespace: kc.Namespace,
+		Spec: WorkloadSpecSummary{
+			RunInterval:  kc.Spec.RunInterval,
+			Schedule:     kc.Spec.Schedule,
+			Timeout:      kc.Spec.Timeout,
+			Images:       podSpecImages(kc.Spec.PodSpec),
+			Suspend:      kc.Spec.Suspend,
+			SuspendUntil: kc.Spec.SuspendUntil,
+			DependsOn:    kc.Spec.DependsOn,
+		},
+	}
+
+	details, exists := k.stateReflector.WorkloadDetails(kc.Namespace, kc.Name)
+	if exists {
+		status.Status = details
+		status.LastRun = details.LastRun
+	}
+	status.NextRun = nextCheckRun(kc, status.LastRun, now)
+
+	return status
+}
+
+// jobStatus builds the check API status of a khjob from its spec and its cached khstate
+func (k *Kuberhealthy) jobStatus(kj khjobv1.KuberhealthyJob) WorkloadStatus {
+	status := WorkloadStatus{
+		Name:      kj.Name,
+		Namespace: kj.Namespace,
+		Spec: WorkloadSpecSummary{
+			Timeout: kj.Spec.Timeout,
+			Phase:   string(kj.Spec.Phase),
+			Images:  podSpecImages(kj.Spec.PodSpec),
+		},
+	}
+
+	details, exists := k.stateReflector.WorkloadDetails(kj.Namespace, kj.Name)
+	if exists {
+		status.Status = details
+		status.LastRun = details.LastRun
+	}
+
+	return status
+}
+
+// nextCheckRun estimates when a khcheck will next run from its schedule or run interval and the time it last
+// reported in.  Checks without a last run are expected to run right away.  Checks that are suspended without an end
+// time, or whose schedule never matches again, have no next run.
+func nextCheckRun(kc khcheckv1.KuberhealthyCheck, lastRun *metav1.Time, now time.Time) *metav1.Time {
+	if kc.Spec.Suspend && kc.Spec.SuspendUntil == nil {
+		return nil
+	}
+
+	c := &external.Checker{}
+	if kc.Spec.SuspendUntil != nil {
+		c.SuspendUntil = kc.Spec.SuspendUntil.Time
+	}
+
+	var err error
+	c.RunInterval, err = time.ParseDuration(kc.Spec.RunInterval)
+	if err != nil {
+		c.RunInterval = DefaultRunInterval
+	}
+	if len(kc.Spec.Schedule) > 0 {
+		c.RunSchedule, err = external.ParseSchedule(kc.Spec.Schedule, now)
+		if err != nil {
+			c.RunSchedule = nil
+		}
+	}
+
+	var lastRunTime time.Time
+	if lastRun != nil {
+		lastRunTime = lastRun.Time
+	}
+
+	nextRunTime := c.NextRunTime(lastRunTime, now)
+	if nextRunTime.IsZero() {
+		return nil
+	}
+	nextRun := metav1.NewTime(nextRunTime)
+	return &nextRun
+}
+
+// podSpecImages returns the images of all containers in a pod spec
+func podSpecImages(podSpec v1.PodSpec) []string {
+	images := []string{}
+	for _, c := range podSpec.Containers {
+		images = append(images, c.Image)
+	}
+	return images
+}
+
+// namespacesFromQuery returns the namespaces requested with the ?namespace= query as a comma separated list
+func namespacesFromQuery(r *http.Request) []string {
+	var namespaces []string
+	for _, namespace := range strings.Split(r.URL.Query().Get("namespace"), ",") {
+		if len(namespace) != 0 {
+			namespaces = append(namespaces, namespace)
+		}
+	}
+	return namespaces
+}
+
+// writeAPIResponse writes the supplied value as JSON to a check API caller
+func writeAPIResponse(w http.ResponseWriter, statusCode int, v interface{}) error {
+	b, err := json.MarshalIndent(v, "", "  ")
+	if err != nil {
+		w.WriteHeader(http.StatusInternalServerError)
+		return err
+	}
+
+	w.Header().Set("Content-Type", "application/json")
+	w.WriteHeader(statusCode)
+	_, err = w.Write(b)
+	return err
+}
+
+// writeAPIError writes an error message as JSON to a check API caller
+func writeAPIError(w http.ResponseWriter, statusCode int, message string) {
+	err := writeAPIResponse(w, statusCode, apiError{Error: message})
+	if err != nil {
+		log.Warningln("Error writing check API error to caller:", err)
+	}
+}
@@ -0,0 +1,307 @@
+package main
+
+import (
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"reflect"
+	"testing"
+	"time"
+
+	"github.com/prometheus/client_golang/prometheus"
+	authenticationv1 "k8s.io/api/authentication/v1"
+	authorizationv1 "k8s.io/api/authorization/v1"
+	v1 "k8s.io/api/core/v1"
+	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
+	"k8s.io/client-go/kubernetes"
+	"k8s.io/client-go/rest"
+	"k8s.io/client-go/tools/cache"
+
+	khcheckv1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khcheck/v1"
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/masterCalculation"
+)
+
+// TestNextCheckRun tests next run estimation for interval and cron scheduled khchecks
+func TestNextCheckRun(t *testing.T) {
+	now := time.Date(2021, 1, 1, 12, 3, 0, 0, time.UTC)
+	lastRun := metav1.NewTime(now.Add(-time.Minute))
+
+	kc := khcheckv1.KuberhealthyCheck{Spec: khcheckv1.CheckConfig{RunInterval: "5m"}}
+	if next := nextCheckRun(kc, nil, now); !next.Time.Equal(now) {
+		t.Fatalf("expected a check that never ran to run now, got %s", next.Time)
+	}
+	if next := nextCheckRun(kc, &lastRun, now); !next.Time.Equal(lastRun.Add(time.Minute * 5)) {
+		t.Fatalf("expected interval check to run 5m after its last run, got %s", next.Time)
+	}
+
+	kc.Spec.RunInterval = "bogus"
+	if next := nextCheckRun(kc, &lastRun, now); !next.Time.Equal(lastRun.Add(DefaultRunInterval)) {
+		t.Fatalf("expected an invalid run interval to fall back to the default, got %s", next.Time)
+	}
+
+	kc.Spec.Schedule = "*/15 * * * *"
+	expected := time.Date(2021, 1, 1, 12, 15, 0, 0, time.UTC)
+	if next := nextCheckRun(kc, &lastRun, now); !next.Time.Equal(expected) {
+		t.Fatalf("expected cron check to run at %s, got %s", expected, next.Time)
+	}
+}
+
+// TestPodSpecImages tests that all container images are pulled from a pod spec
+func TestPodSpecImages(t *testing.T) {
+	podSpec := v1.PodSpec{Containers: []v1.Container{{Image: "a:1"}, {Image: "b:2"}}}
+	images := podSpecImages(podSpec)
+	if !reflect.DeepEqual(images, []string{"a:1", "b:2"}) {
+		t.Fatalf("unexpected images: %v", images)
+	}
+	if images := podSpecImages(v1.PodSpec{}); images == nil || len(images) != 0 {
+		t.Fatalf("expected an empty image list for an empty pod spec, got %v", images)
+	}
+}
+
+// TestNamespacesFromQuery tests parsing of the namespace query parameter
+func TestNamespacesFromQuery(t *testing.T) {
+	r := httptest.NewRequest("GET", "/api/v1/checks?namespace=kuberhealthy,,kube-system", nil)
+	namespaces := namespacesFromQuery(r)
+	if !reflect.DeepEqual(namespaces, []string{"kuberhealthy", "kube-system"}) {
+		t.Fatalf("unexpected namespaces: %v", namespaces)
+	}
+
+	r = httptest.NewRequest("GET", "/api/v1/checks", nil)
+	if namespaces := namespacesFromQuery(r); len(namespaces) != 0 {
+		t.Fatalf("expected no namespaces without a query, got %v", namespaces)
+	}
+}
+
+// TestNextCheckRunSuspended tests next run estimation for suspended khchecks
+func TestNextCheckRunSuspended(t *testing.T) {
+	now := time.Date(2021, 1, 1, 12, 3, 0, 0, time.UTC)
+
+	kc := khcheckv1.KuberhealthyCheck{Spec: khcheckv1.CheckConfig{RunInterval: "5m", Suspend: true}}
+	if next := nextCheckRun(kc, nil, now); next != nil {
+		t.Fatalf("expected no next run for a check suspended without an end time, got %s", next.Time)
+	}
+
+	suspendUntil := metav1.NewTime(now.Add(time.Hour))
+	kc.Spec.SuspendUntil = &suspendUntil
+	if next := nextCheckRun(kc, nil, now); !next.Time.Equal(suspendUntil.Time) {
+		t.Fatalf("expected a check suspended until %s to run then, got %s", suspendUntil.Time, next.Time)
+	}
+}
+
+// TestCheckAPIHandlersFromCache tests that the check API serves khchecks from the informer cache and their state from
+// the state reflector
+func TestCheckAPIHandlersFromCache(t *testing.T) {
+	previousConfig := cfg
+	defer func() {
+		cfg = previousConfig
+	}()
+	cfg = &Config{}
+
+	kh := &Kuberhealthy{
+		checkStore:     cache.NewStore(cache.MetaNamespaceKeyFunc),
+		stateReflector: &StateReflector{store: cache.NewStore(cache.MetaNamespaceKeyFunc)},
+	}
+	for _, kc := range []*khcheckv1.KuberhealthyCheck{
+		{ObjectMeta: metav1.ObjectMeta{Name: "dns", Namespace: "kuberhealthy"}, Spec: khcheckv1.CheckConfig{RunInterval: "5m"}},
+		{ObjectMeta: metav1.ObjectMeta{Name: "deployment", Namespace: "kuberhealthy"}, Spec: khcheckv1.CheckConfig{RunInterval: "5m"}},
+		{ObjectMeta: metav1.ObjectMeta{Name: "http", Namespace: "default"}, Spec: khcheckv1.CheckConfig{RunInterval: "5m"}},
+	} {
+		err := kh.checkStore.Add(kc)
+		if err != nil {
+			t.Fatal("failed to add khcheck to the store:", err)
+		}
+	}
+	err := kh.stateReflector.store.Add(&khstatev1.KuberhealthyState{
+		ObjectMeta: metav1.ObjectMeta{Name: "dns", Namespace: "kuberhealthy"},
+		Spec:       khstatev1.WorkloadDetails{OK: true, Errors: []string{}},
+	})
+	if err != nil {
+		t.Fatal("failed to add khstate to the store:", err)
+	}
+
+	w := httptest.NewRecorder()
+	err = kh.listChecksAPIHandler(w, httptest.NewRequest("GET", "/api/v1/checks?namespace=kuberhealthy", nil))
+	if err != nil {
+		t.Fatal("failed to list checks:", err)
+	}
+	var statuses []WorkloadStatus
+	err = json.Unmarshal(w.Body.Bytes(), &statuses)
+	if err != nil {
+		t.Fatal("failed to decode check statuses:", err)
+	}
+	if len(statuses) != 2 || statuses[0].Name != "deployment" || statuses[1].Name != "dns" {
+		t.Fatalf("expected the khchecks in the kuberhealthy namespace sorted by name, got %+v", statuses)
+	}
+	if statuses[0].Status.OK || !statuses[1].Status.OK {
+		t.Fatalf("expected only the dns check to have a cached OK state, got %+v", statuses)
+	}
+
+	r := httptest.NewRequest("GET", "/api/v1/checks/default/missing", nil)
+	r.SetPathValue("namespace", "default")
+	r.SetPathValue("name", "missing")
+	w = httptest.NewRecorder()
+	err = kh.checkAPIHandler(w, r)
+	if err != nil {
+		t.Fatal("failed to get check:", err)
+	}
+	if w.Code != http.StatusNotFound {
+		t.Fatalf("expected a missing khcheck to return %d, got %d", http.StatusNotFound, w.Code)
+	}
+}
+
+// TestCheckAPIAuthorization tests that with status page authentication enabled, callers of the check API must present
+// a bearer token and only see checks in namespaces where they can get khstates, and that metrics are only authorized
+// when metrics authentication is enabled too
+func TestCheckAPIAuthorization(t *testing.T) {
+	previousConfig, previousClient, previousElector := cfg, kubernetesClient, masterElector
+	defer func() {
+		cfg, kubernetesClient, masterElector = previousConfig, previousClient, previousElector
+	}()
+	cfg = &Config{StatusPageAuth: true}
+	masterElector = &masterCalculation.Elector{}
+
+	// alice may only get khstates in the kuberhealthy namespace
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		var response interface{}
+		switch r.URL.Path {
+		case "/apis/authentication.k8s.io/v1/tokenreviews":
+			review := authenticationv1.TokenReview{}
+			err := json.NewDecoder(r.Body).Decode(&review)
+			if err != nil {
+				w.WriteHeader(http.StatusBadRequest)
+				return
+			}
+			review.TypeMeta = metav1.TypeMeta{APIVersion: "authentication.k8s.io/v1", Kind: "TokenReview"}
+			if review.Spec.Token == "alice-token" {
+				review.Status.Authenticated = true
+				review.Status.User = authenticationv1.UserInfo{Username: "alice"}
+			}
+			response = review
+		case "/apis/authorization.k8s.io/v1/subjectaccessreviews":
+			review := authorizationv1.SubjectAccessReview{}
+			err := json.NewDecoder(r.Body).Decode(&review)
+			if err != nil {
+				w.WriteHeader(http.StatusBadRequest)
+				return
+			}
+			review.TypeMeta = metav1.TypeMeta{APIVersion: "authorization.k8s.io/v1", Kind: "SubjectAccessReview"}
+			attributes := review.Spec.ResourceAttributes
+			review.Status.Allowed = review.Spec.User == "alice" && attributes.Resource == "khstates" &&
+				attributes.Verb == "get" && attributes.Namespace == "kuberhealthy"
+			response = review
+		default:
+			w.WriteHeader(http.StatusNotFound)
+			return
+		}
+		w.Header().Set("Content-Type", "application/json")
+		err := json.NewEncoder(w).Encode(response)
+		if err != nil {
+			t.Error("failed to write review:", err)
+		}
+	}))
+	defer server.Close()
+	kubernetesClient = kubernetes.NewForConfigOrDie(&rest.Config{Host: server.URL})
+
+	kh := &Kuberhealthy{
+		checkStore:      cache.NewStore(cache.MetaNamespaceKeyFunc),
+		stateReflector:  &StateReflector{store: cache.NewStore(cache.MetaNamespaceKeyFunc)},
+		metricsRegistry: prometheus.NewRegistry(),
+	}
+	for _, kc := range []*khcheckv1.KuberhealthyCheck{
+		{ObjectMeta: metav1.ObjectMeta{Name: "dns", Namespace: "kuberhealthy"}, Spec: khcheckv1.CheckConfig{RunInterval: "5m"}},
+		{ObjectMeta: metav1.ObjectMeta{Name: "http", Namespace: "default"}, Spec: khcheckv1.CheckConfig{RunInterval: "5m"}},
+	} {
+		err := kh.checkStore.Add(kc)
+		if err != nil {
+			t.Fatal("failed to add khcheck to the store:", err)
+		}
+	}
+
+	// callers without a bearer token are rejected
+	w := httptest.NewRecorder()
+	err := kh.listChecksAPIHandler(w, httptest.NewRequest("GET", "/api/v1/checks", nil))
+	if err != nil {
+		t.Fatal("failed to list checks:", err)
+	}
+	if w.Code != http.StatusUnauthorized {
+		t.Fatalf("expected a caller without a bearer token to get %d, got %d", http.StatusUnauthorized, w.Code)
+	}
+
+	// metrics can be scraped without a bearer token unless metrics authentication is enabled
+	w = httptest.NewRecorder()
+	err = kh.prometheusMetricsHandler(w, httptest.NewRequest("GET", "/metrics", nil))
+	if err != nil {
+		t.Fatal("failed to get metrics:", err)
+	}
+	if w.Code != http.StatusOK {
+		t.Fatalf("expected a metrics caller without a bearer token to get %d, got %d", http.StatusOK, w.Code)
+	}
+
+	cfg.MetricsAuth = true
+	w = httptest.NewRecorder()
+	err = kh.prometheusMetricsHandler(w, httptest.NewRequest("GET", "/metrics", nil))
+	if err != nil {
+		t.Fatal("failed to get metrics:", err)
+	}
+	if w.Code != http.StatusUnauthorized {
+		t.Fatalf("expected a metrics caller without a bearer token to get %d, got %d", http.StatusUnauthorized, w.Code)
+	}
+
+	// callers can not get checks in namespaces they can not get khstates in
+	r := httptest.NewRequest("GET", "/api/v1/checks/default/http", nil)
+	r.Header.Set("Authorization", "Bearer alice-token")
+	r.SetPathValue("namespace", "default")
+	r.SetPathValue("name", "http")
+	w = httptest.NewRecorder()
+	err = kh.checkAPIHandler(w, r)
+	if err != nil {
+		t.Fatal("failed to get check:", err)
+	}
+	if w.Code != http.StatusForbidden {
+		t.Fatalf("expected an unauthorized caller to get %d, got %d", http.StatusForbidden, w.Code)
+	}
+
+	// listing checks only returns the checks in namespaces the caller can get khstates in
+	r = httptest.NewRequest("GET", "/api/v1/checks", nil)
+	r.Header.Set("Authorization", "Bearer alice-token")
+	w = httptest.NewRecorder()
+	err = kh.listChecksAPIHandler(w, r)
+	if err != nil {
+		t.Fatal("failed to list checks:", err)
+	}
+	var statuses []WorkloadStatus
+	err = json.Unmarshal(w.Body.Bytes(), &statuses)
+	if err != nil {
+		t.Fatal("failed to decode check statuses:", err)
+	}
+	if w.Code != http.StatusOK || len(statuses) != 1 || statuses[0].Name != "dns" {
+		t.Fatalf("expected only the khcheck in the kuberhealthy namespace, got %d %+v", w.Code, statuses)
+	}
+	// before any khchecks or khstates exist, callers see an empty result rather than being rejected
+	empty := &Kuberhealthy{
+		checkStore:     cache.NewStore(cache.MetaNamespaceKeyFunc),
+		stateReflector: &StateReflector{store: cache.NewStore(cache.MetaNamespaceKeyFunc)},
+	}
+	r = httptest.NewRequest("GET", "/api/v1/checks", nil)
+	r.Header.Set("Authorization", "Bearer alice-token")
+	w = httptest.NewRecorder()
+	err = empty.listChecksAPIHandler(w, r)
+	if err != nil {
+		t.Fatal("failed to list checks:", err)
+	}
+	if w.Code != http.StatusOK || w.Body.String() != "[]" {
+		t.Fatalf("expected an empty check list before any khchecks exist, got %d %s", w.Code, w.Body.String())
+	}
+
+	r = httptest.NewRequest("GET", "/", nil)
+	r.Header.Set("Authorization", "Bearer alice-token")
+	w = httptest.NewRecorder()
+	err = empty.healthCheckHandler(w, r)
+	if err != nil {
+		t.Fatal("failed to get the status page:", err)
+	}
+	if w.Code != http.StatusOK {
+		t.Fatalf("expected the status page to return %d before any khstates exist, got %d", http.StatusOK, w.Code)
+	}
+}
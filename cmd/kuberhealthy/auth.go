@@ -0,0 +1,114 @@
+package main
+
+import (
+	"context"
+	"errors"
+	"net/http"
+	"strings"
+
+	authenticationv1 "k8s.io/api/authentication/v1"
+	authorizationv1 "k8s.io/api/authorization/v1"
+	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
+	"k8s.io/client-go/kubernetes"
+)
+
+// kuberhealthyAPIGroup is the API group of the kuberhealthy custom resources
+const kuberhealthyAPIGroup = "comcast.github.io"
+
+// errMissingBearerToken is returned when a request that requires authentication has no bearer token
+var errMissingBearerToken = errors.New("request has no bearer token")
+
+// bearerToken returns the bearer token from the Authorization header of a request
+func bearerToken(r *http.Request) (string, error) {
+	header := r.Header.Get("Authorization")
+	if !strings.HasPrefix(header, "Bearer ") {
+		return "", errMissingBearerToken
+	}
+	token := strings.TrimSpace(strings.TrimPrefix(header, "Bearer "))
+	if len(token) == 0 {
+		return "", errMissingBearerToken
+	}
+	return token, nil
+}
+
+// authenticateRequest verifies the bearer token of a request with a kubernetes TokenReview and returns the user
+// that the token belongs to
+func authenticateRequest(ctx context.Context, client kubernetes.Interface, r *http.Request) (authenticationv1.UserInfo, error) {
+	token, err := bearerToken(r)
+	if err != nil {
+		return authenticationv1.UserInfo{}, err
+	}
+
+	review := &authenticationv1.TokenReview{
+		Spec: authenticationv1.TokenReviewSpec{
+			Token: token,
+		},
+	}
+	review, err = client.AuthenticationV1().TokenReviews().Create(ctx, review, metav1.CreateOptions{})
+	if err != nil {
+		return authenticationv1.UserInfo{}, errors.New("failed to review bearer token: " + err.Error())
+	}
+	if !review.Status.Authenticated {
+		if len(review.Status.Error) > 0 {
+			return authenticationv1.UserInfo{}, errors.New("bearer token was not authenticated: " + review.Status.Error)
+		}
+		return authenticationv1.UserInfo{}, errors.New("bearer token was not authenticated")
+	}
+
+	return review.Status.User, nil
+}
+
+// authorizeUser checks with a kubernetes SubjectAccessReview that the user is allowed to perform the verb on the
+// named kuberhealthy resource
+func authorizeUser(ctx context.Context, client kubernetes.Interface, user authenticationv1.UserInfo, verb string, resource string, namespace string, name string) (bool, error) {
+	extra := make(map[string]authorizationv1.ExtraValue)
+	for k, v := range user.Extra {
+		extra[k] = authorizationv1.ExtraValue(v)
+	}
+
+	review := &authorizationv1.SubjectAccessReview{
+		Spec: authorizationv1.SubjectAccessReviewSpec{
+			User:   user.Username,
+			UID:    user.UID,
+			Groups: user.Groups,
+			Extra:  extra,
+			ResourceAttributes: &authorizationv1.ResourceAttributes{
+				Namespace: namespace,
+				Verb:      verb,
+				Group:     kuberhealthyAPIGroup,
+				Resource:  resource,
+				Name:      name,
+			},
+		},
+	}
+	review, err := client.AuthorizationV1().SubjectAccessReviews().Create(ctx, review, metav1.CreateOptions{})
+	if err != nil {
+		return false, errors.New("failed to review access of user " + user.Username + ": " + err.Error())
+	}
+
+	return review.Status.Allowed, nil
+}
+
+// authorizedNamespaces returns the namespaces out of the candidates that the user is allowed to get khstates in.  A
+// user that can get khstates in every namespace is checked with a single review and all is returned as true, so
+// that callers do not need a review per namespace.
+func authorizedNamespaces(ctx context.Context, client kubernetes.Interface, user authenticationv1.UserInfo, candidates []string) (namespaces []string, all bool, err error) {
+	all, err = authorizeUser(ctx, client, user, "get", "khstates", "", "")
+	if err != nil {
+		return nil, false, err
+	}
+	if all {
+		return candidates, true, nil
+	}
+
+	for _, namespace := range candidates {
+		allowed, err := authorizeUser(ctx, client, user, "get", "khstates", namespace, "")
+		if err != nil {
+			return nil, false, err
+		}
+		if allowed {
+			namespaces = append(namespaces, namespace)
+		}
+	}
+	return namespaces, false, nil
+}
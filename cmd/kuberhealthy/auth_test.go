@@ -0,0 +1,139 @@
+package main
+
+import (
+	"context"
+	"net/http/httptest"
+	"testing"
+
+	authenticationv1 "k8s.io/api/authentication/v1"
+	authorizationv1 "k8s.io/api/authorization/v1"
+	"k8s.io/apimachinery/pkg/runtime"
+	"k8s.io/client-go/kubernetes/fake"
+	k8stesting "k8s.io/client-go/testing"
+)
+
+// TestBearerToken tests parsing of bearer tokens from the Authorization header
+func TestBearerToken(t *testing.T) {
+	r := httptest.NewRequest("POST", "/api/v1/checks/kuberhealthy/dns/run", nil)
+	_, err := bearerToken(r)
+	if err != errMissingBearerToken {
+		t.Fatalf("expected a missing bearer token error, got %v", err)
+	}
+
+	r.Header.Set("Authorization", "Basic Zm9vOmJhcg==")
+	_, err = bearerToken(r)
+	if err != errMissingBearerToken {
+		t.Fatalf("expected a missing bearer token error for basic auth, got %v", err)
+	}
+
+	r.Header.Set("Authorization", "Bearer abc123")
+	token, err := bearerToken(r)
+	if err != nil || token != "abc123" {
+		t.Fatalf("expected token abc123, got %q (%v)", token, err)
+	}
+}
+
+// TestAuthenticateAndAuthorize tests that callers are authenticated with a TokenReview and authorized with a
+// SubjectAccessReview for the requested khcheck
+func TestAuthenticateAndAuthorize(t *testing.T) {
+	client := fake.NewSimpleClientset()
+	client.PrependReactor("create", "tokenreviews", func(action k8stesting.Action) (bool, runtime.Object, error) {
+		review := action.(k8stesting.CreateAction).GetObject().(*authenticationv1.TokenReview)
+		if review.Spec.Token == "good" {
+			review.Status.Authenticated = true
+			review.Status.User = authenticationv1.UserInfo{Username: "alice", Groups: []string{"oncall"}}
+		}
+		return true, review, nil
+	})
+	client.PrependReactor("create", "subjectaccessreviews", func(action k8stesting.Action) (bool, runtime.Object, error) {
+		review := action.(k8stesting.CreateAction).GetObject().(*authorizationv1.SubjectAccessReview)
+		attributes := review.Spec.ResourceAttributes
+		review.Status.Allowed = review.Spec.User == "alice" && attributes.Group == kuberhealthyAPIGroup &&
+			attributes.Resource == "khchecks" && attributes.Verb == "update" && attributes.Namespace == "kuberhealthy"
+		return true, review, nil
+	})
+
+	r := httptest.NewRequest("POST", "/api/v1/checks/kuberhealthy/dns/run", nil)
+	r.Header.Set("Authorization", "Bearer bad")
+	_, err := authenticateRequest(context.Background(), client, r)
+	if err == nil {
+		t.Fatal("expected an unauthenticated token to be rejected")
+	}
+
+	r.Header.Set("Authorization", "Bearer good")
+	user, err := authenticateRequest(context.Background(), client, r)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if user.Username != "alice" {
+		t.Fatalf("expected user alice, got %s", user.Username)
+	}
+
+	allowed, err := authorizeUser(context.Background(), client, user, "update", "khchecks", "kuberhealthy", "dns")
+	if err != nil {
+		t.Fatal(err)
+	}
+	if !allowed {
+		t.Fatal("expected alice to be allowed to update khchecks in the kuberhealthy namespace")
+	}
+
+	allowed, err = authorizeUser(context.Background(), client, user, "update", "khchecks", "kube-system", "dns")
+	if err != nil {
+		t.Fatal(err)
+	}
+	if allowed {
+		t.Fatal("expected alice to not be allowed to update khchecks in the kube-system namespace")
+	}
+}
+
+// TestAuthorizedNamespaces tests that only the namespaces a user can get khstates in are returned, and that a user
+// that can get khstates everywhere is allowed with a single review
+func TestAuthorizedNamespaces(t *testing.T) {
+	var reviews int
+	client := fake.NewSimpleClientset()
+	client.PrependReactor("create", "subjectaccessreviews", func(action k8stesting.Action) (bool, runtime.Object, error) {
+		reviews++
+		review := action.(k8stesting.CreateAction).GetObject().(*authorizationv1.SubjectAccessReview)
+		attributes := review.Spec.ResourceAttributes
+		if attributes.Resource != "khstates" || attributes.Verb != "get" {
+			return true, review, nil
+		}
+		switch review.Spec.User {
+		case "admin":
+			review.Status.Allowed = true
+		case "alice":
+			review.Status.Allowed = attributes.Namespace == "team-a"
+		}
+		return true, review, nil
+	})
+
+	candidates := []string{"team-a", "team-b", "kuberhealthy"}
+
+	namespaces, all, err := authorizedNamespaces(context.Background(), client, authenticationv1.UserInfo{Username: "alice"}, candidates)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if all || len(namespaces) != 1 || namespaces[0] != "team-a" {
+		t.Fatalf("expected alice to only be allowed in team-a, got %v (all: %v)", namespaces, all)
+	}
+
+	reviews = 0
+	namespaces, all, err = authorizedNamespaces(context.Background(), client, authenticationv1.UserInfo{Username: "admin"}, candidates)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if !all || len(namespaces) != len(candidates) {
+		t.Fatalf("expected admin to be allowed in all namespaces, got %v (all: %v)", namespaces, all)
+	}
+	if reviews != 1 {
+		t.Fatalf("expected a single access review for a cluster wide user, got %d", reviews)
+	}
+
+	namespaces, _, err = authorizedNamespaces(context.Background(), client, authenticationv1.UserInfo{Username: "mallory"}, candidates)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if len(namespaces) != 0 {
+		t.Fatalf("expected mallory to not be allowed in any namespace, got %v", namespaces)
+	}
+}
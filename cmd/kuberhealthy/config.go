@@ -6,7 +6,10 @@ import (
 	"time"
 
 	"github.com/codingsince1985/checksum"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/masterCalculation"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/metrics"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/notifiers"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/sharding"
 	log "github.com/sirupsen/logrus"
 	"gopkg.in/yaml.v2"
 )
@@ -15,22 +18,55 @@ import (
 type Config struct {
 	kubeConfigFile            string                    `yaml:"kubeConfigFile"`
 	ListenAddress             string                    `yaml:"listenAddress"`
+	MetricsListenAddress      string                    `yaml:"metricsListenAddress,omitempty"`
+	TLS                       TLSConfig                 `yaml:"tls,omitempty"`
+	StatusPageAuth            bool                      `yaml:"statusPageAuth,omitempty"`
+	MetricsAuth               bool                      `yaml:"metricsAuth,omitempty"`
 	EnableForceMaster         bool                      `yaml:"enableForceMaster"`
 	LogLevel                  string                    `yaml:"logLevel"`
 	InfluxUsername            string                    `yaml:"influxUsername"`
 	InfluxPassword            string                    `yaml:"influxPassword"`
 	InfluxURL                 string                    `yaml:"influxURL"`
 	InfluxDB                  string                    `yaml:"influxDB"`
+	InfluxOrg                 string                    `yaml:"influxOrg,omitempty"`
+	InfluxBucket              string                    `yaml:"influxBucket,omitempty"`
+	InfluxToken               string                    `yaml:"influxToken,omitempty"`
+	InfluxTLS                 metrics.InfluxTLSConfig   `yaml:"influxTLS,omitempty"`
+	InfluxBatch               metrics.InfluxBatchConfig `yaml:"influxBatch,omitempty"`
 	EnableInflux              bool                      `yaml:"enableInflux"`
+	EnableDogStatsD           bool                      `yaml:"enableDogStatsD,omitempty"`
+	DogStatsD                 metrics.DogStatsDConfig   `yaml:"dogStatsD,omitempty"`
+	MetricForwarders          []metrics.ForwarderConfig `yaml:"metricForwarders,omitempty"`
 	ExternalCheckReportingURL string                    `yaml:"externalCheckReportingURL"`
+	RequireSignedReports      bool                      `yaml:"requireSignedReports,omitempty"`
+	UnsignedReportImages      []string                  `yaml:"unsignedReportImages,omitempty"`
 	MaxKHJobAge               time.Duration             `yaml:"maxKHJobAge"`
 	MaxCheckPodAge            time.Duration             `yaml:"maxCheckPodAge"`
 	MaxCompletedPodCount      int                       `yaml:"maxCompletedPodCount"`
 	MaxErrorPodCount          int                       `yaml:"maxErrorPodCount"`
 	StateMetadata             map[string]string         `yaml:"stateMetadata,omitempty"`
+	RunHistoryLength          int                       `yaml:"runHistoryLength,omitempty"`
+	RunQueue                  RunQueueConfig            `yaml:"runQueue,omitempty"`
 	PromMetricsConfig         metrics.PromMetricsConfig `yaml:"promMetricsConfig,omitempty"`
+	Notifiers                 []notifiers.Config        `yaml:"notifiers,omitempty"`
+	OTLP                      metrics.OTLPConfig        `yaml:"otlp,omitempty"`
 	TargetNamespace           string                    `yaml:"namespace"` // TargetNamespace sets the namespace that Kuberhealthy will operate in.  By default, this is blank, which means
 	// all namespaces.  However, for multi-tennant environments you may wish to set this.
+	LeaderElection masterCalculation.LeaderElectionConfig `yaml:"leaderElection,omitempty"` // settings for the lease used to elect the master pod. changes require a restart.
+	Sharding       sharding.Config                        `yaml:"sharding,omitempty"`       // settings for running checks on every replica. changes require a restart.
+}
+
+// runHistoryLength returns the number of past runs to keep on each khstate
+func (c *Config) runHistoryLength() int {
+	if c.RunHistoryLength == 0 {
+		return DefaultRunHistoryLength
+	}
+	return c.RunHistoryLength
+}
+
+// validate returns an error if the settings of the config can not be used together
+func (c *Config) validate() error {
+	return c.TLS.validate()
 }
 
 // Load loads file from disk
@@ -162,12 +198,19 @@ func configReloadNotifier(ctx context.Context, notifyChan chan struct{}) {
 
 		log.Debugln("configReloader: loading new configuration")
 
-		// setup config
+		// setup config and keep the current config if the new one is not valid
+		previous := cfg
 		err := setUpConfig()
 		if err != nil {
 			log.Errorln("configReloader: Error reloading and setting up config:", err)
 			continue
 		}
+		err = cfg.validate()
+		if err != nil {
+			log.Errorln("configReloader: Invalid configuration. Keeping the current configuration:", err)
+			cfg = previous
+			continue
+		}
 		log.Debugln("configReloader: loaded new configuration:", cfg)
 
 		// reparse and set logging level
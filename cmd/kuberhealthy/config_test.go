@@ -30,6 +30,32 @@ func TestRenderConfig(t *testing.T) {
 	fmt.Println(string(b))
 }
 
+// TestValidateConfig tests that client certificates can only be required when they can be verified
+func TestValidateConfig(t *testing.T) {
+	var testCases = []struct {
+		description string
+		tls         TLSConfig
+		expectErr   bool
+	}{
+		{"TLS disabled", TLSConfig{}, false},
+		{"TLS enabled", TLSConfig{CertFile: "tls.crt", KeyFile: "tls.key"}, false},
+		{"client certificates required", TLSConfig{CertFile: "tls.crt", KeyFile: "tls.key", ClientCAFile: "ca.crt", RequireClientCert: true}, false},
+		{"client certificates required without a client CA file", TLSConfig{CertFile: "tls.crt", KeyFile: "tls.key", RequireClientCert: true}, true},
+		{"client certificates required with TLS disabled", TLSConfig{ClientCAFile: "ca.crt", RequireClientCert: true}, true},
+	}
+
+	for _, tc := range testCases {
+		c := Config{TLS: tc.tls}
+		err := c.validate()
+		if tc.expectErr && err == nil {
+			t.Fatal("Expected an error validating config with", tc.description)
+		}
+		if !tc.expectErr && err != nil {
+			t.Fatal("Unexpected error validating config with", tc.description+":", err)
+		}
+	}
+}
+
 // TestConfigReloadNotifications tests the notification of files changing with
 // limiting on output duration.
 // x = file write
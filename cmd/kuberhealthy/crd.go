@@ -8,6 +8,7 @@ import (
 
 	log "github.com/sirupsen/logrus"
 	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
+	"k8s.io/client-go/util/retry"
 
 	khjobv1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khjob/v1"
 	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
@@ -15,8 +16,8 @@ import (
 )
 
 // setCheckStateResource puts a check state's state into the specified CRD resource.  It sets the AuthoritativePod
-// to the server's hostname and sets the LastUpdate time to now.
-func setCheckStateResource(checkName string, checkNamespace string, state khstatev1.WorkloadDetails) error {
+// to the server's hostname and sets the LastUpdate time to now.  The state that was replaced is returned.
+func setCheckStateResource(checkName string, checkNamespace string, state khstatev1.WorkloadDetails) (khstatev1.WorkloadDetails, error) {
 
 	name := sanitizeResourceName(checkName)
 
@@ -24,7 +25,7 @@ func setCheckStateResource(checkName string, checkNamespace string, state khstat
 	// int found within
 	existingState, err := khStateClient.KuberhealthyStates(checkNamespace).Get(name, metav1.GetOptions{})
 	if err != nil {
-		return errors.New("Error retrieving CRD for: " + name + " " + err.Error())
+		return khstatev1.WorkloadDetails{}, errors.New("Error retrieving CRD for: " + name + " " + err.Error())
 	}
 	resourceVersion := existingState.GetResourceVersion()
 
@@ -33,13 +34,135 @@ func setCheckStateResource(checkName string, checkNamespace string, state khstat
 	now := metav1.Now() // set the time the khstate was last
 	state.LastRun = &now
 
+	// record this run on top of the run history already stored in the khstate
+	state.History = appendRunHistory(existingState.Spec.History, state, now, cfg.runHistoryLength())
+	state.TotalRuns, state.TotalFailures, state.CountedUUID = countRun(existingState.Spec, state)
+
 	khState := khstatev1.NewKuberhealthyState(name, state)
 	khState.SetResourceVersion(resourceVersion)
 	// TODO - if "try again" message found in error, then try again
 
 	log.Debugln(checkNamespace, checkName, "writing khstate with ok:", state.OK, "and errors:", state.Errors, "at last run:", state.LastRun)
 	_, err = khStateClient.KuberhealthyStates(checkNamespace).Update(&khState)
-	return err
+	return existingState.Spec, err
+}
+
+// setCheckSuspended marks the khstate of a check as suspended or resumed
+func setCheckSuspended(c *external.Checker, suspended bool) error {
+	return updateCheckStateFields(c, func(details *khstatev1.WorkloadDetails) bool {
+		if details.Suspended == suspended {
+			return false
+		}
+		details.Suspended = suspended
+		return true
+	})
+}
+
+// setCheckBlocked marks the khstate of a check as blocked by its dependencies for the supplied reason
+func setCheckBlocked(c *external.Checker, reason string) error {
+	return updateCheckStateFields(c, func(details *khstatev1.WorkloadDetails) bool {
+		if details.Blocked && details.BlockedReason == reason {
+			return false
+		}
+		details.Blocked = true
+		details.BlockedReason = reason
+		return true
+	})
+}
+
+// updateCheckStateFields applies a change to the khstate of a check without recording a run, so that the result and
+// run history of the last run are kept.  The change func returns false when it had nothing to change.
+func updateCheckStateFields(c *external.Checker, change func(details *khstatev1.WorkloadDetails) bool) error {
+
+	// make sure the khstate exists so that checks that have not run yet are shown too
+	err := ensureStateResourceExists(c.Name(), c.CheckNamespace(), khstatev1.KHCheck)
+	if err != nil {
+		return err
+	}
+
+	name := sanitizeResourceName(c.Name())
+	return retry.RetryOnConflict(retry.DefaultRetry, func() error {
+		khState, err := khStateClient.KuberhealthyStates(c.CheckNamespace()).Get(name, metav1.GetOptions{})
+		if err != nil {
+			return errors.New("Error retrieving CRD for: " + name + " " + err.Error())
+		}
+		if !change(&khState.Spec) {
+			return nil
+		}
+
+		khState.Spec.Namespace = c.CheckNamespace()
+		if len(khState.Spec.AuthoritativePod) == 0 {
+			khState.Spec.AuthoritativePod = podHostname
+		}
+
+		log.Debugln(c.CheckNamespace(), c.Name(), "writing khstate with suspended:", khState.Spec.Suspended, "and blocked:", khState.Spec.Blocked)
+		_, err = khStateClient.KuberhealthyStates(c.CheckNamespace()).Update(&khState)
+		return err
+	})
+}
+
+// appendRunHistory adds the run in state to the end of the supplied run history and drops the oldest runs beyond
+// maxLength.  A khstate is written more than once per run, so when the latest entry in the history is for the same run
+// UUID, it is updated instead of adding another entry.  A maxLength below one disables run history.
+func appendRunHistory(history []khstatev1.RunHistoryEntry, state khstatev1.WorkloadDetails, now metav1.Time, maxLength int) []khstatev1.RunHistoryEntry {
+	if maxLength < 1 {
+		return nil
+	}
+
+	// record the result of the run itself rather than the state shown after thresholds are applied
+	entry := khstatev1.RunHistoryEntry{
+		Time:        now,
+		OK:          state.OK,
+		Errors:      state.Errors,
+		RunDuration: state.RunDuration,
+		Node:        state.Node,
+		UUID:        state.CurrentUUID,
+	}
+	if state.LastRunResult != nil {
+		entry.OK = state.LastRunResult.OK
+		entry.Errors = state.LastRunResult.Errors
+	}
+
+	// copy the history so that we never modify the slice of a cached khstate
+	newHistory := make([]khstatev1.RunHistoryEntry, 0, len(history)+1)
+	newHistory = append(newHistory, history...)
+
+	last := len(newHistory) - 1
+	if last >= 0 && len(entry.UUID) > 0 && newHistory[last].UUID == entry.UUID {
+		entry.Time = newHistory[last].Time
+		if len(entry.Node) == 0 {
+			entry.Node = newHistory[last].Node
+		}
+		newHistory[last] = entry
+	} else {
+		newHistory = append(newHistory, entry)
+	}
+
+	if len(newHistory) > maxLength {
+		newHistory = newHistory[len(newHistory)-maxLength:]
+	}
+	return newHistory
+}
+
+// countRun returns the run and failure totals of a khstate once the run in state is counted, along with the UUID of
+// the counted run.  A khstate is written more than once per run, so a run is only counted the first time its UUID is
+// written.
+func countRun(existing khstatev1.WorkloadDetails, state khstatev1.WorkloadDetails) (int64, int64, string) {
+	if len(state.CurrentUUID) > 0 && existing.CountedUUID == state.CurrentUUID {
+		return existing.TotalRuns, existing.TotalFailures, existing.CountedUUID
+	}
+
+	// count the result of the run itself rather than the state shown after thresholds are applied
+	runOK := state.OK
+	if state.LastRunResult != nil {
+		runOK = state.LastRunResult.OK
+	}
+
+	totalFailures := existing.TotalFailures
+	if !runOK {
+		totalFailures++
+	}
+	return existing.TotalRuns + 1, totalFailures, state.CurrentUUID
 }
 
 // sanitizeResourceName cleans up the check names for use in CRDs.
@@ -79,6 +202,19 @@ func ensureStateResourceExists(checkName string, checkNamespace string, workload
 	return nil
 }
 
+// getStoredState retrieves the details stored in the khstate of a check or job straight from the API server rather
+// than from the state reflector, which can lag behind recent writes.  Returns false if the khstate does not exist.
+func getStoredState(name string, namespace string) (khstatev1.WorkloadDetails, bool, error) {
+	khState, err := khStateClient.KuberhealthyStates(namespace).Get(sanitizeResourceName(name), metav1.GetOptions{})
+	if k8sErrors.IsNotFound(err) {
+		return khstatev1.WorkloadDetails{}, false, nil
+	}
+	if err != nil {
+		return khstatev1.WorkloadDetails{}, false, errors.New("Error retrieving custom khstate resource: " + name + " " + err.Error())
+	}
+	return khState.Spec, true, nil
+}
+
 // getCheckState retrieves the check values from the kuberhealthy khstate
 // custom resource
 func getCheckState(c *external.Checker) (khstatev1.WorkloadDetails, error) {
@@ -0,0 +1,80 @@
+package main
+
+import (
+	"testing"
+
+	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
+
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
+)
+
+// TestAppendRunHistory tests that run history is bounded and that writes from the same run update a single entry
+func TestAppendRunHistory(t *testing.T) {
+	now := metav1.Now()
+
+	var history []khstatev1.RunHistoryEntry
+	for _, uuid := range []string{"a", "b", "c", "d"} {
+		history = appendRunHistory(history, khstatev1.WorkloadDetails{OK: true, CurrentUUID: uuid}, now, 3)
+	}
+	if len(history) != 3 {
+		t.Fatal("Expected run history to be bounded to 3 entries but got", len(history))
+	}
+	if history[0].UUID != "b" || history[2].UUID != "d" {
+		t.Fatal("Expected the oldest run to be dropped from history but got", history)
+	}
+
+	// a second write for the same run updates the latest entry with the final node and duration
+	state := khstatev1.WorkloadDetails{
+		OK:            true,
+		CurrentUUID:   "d",
+		Node:          "node-1",
+		RunDuration:   "5s",
+		LastRunResult: &khstatev1.RunResult{OK: false, Errors: []string{"failed"}},
+	}
+	history = appendRunHistory(history, state, metav1.Now(), 3)
+	if len(history) != 3 {
+		t.Fatal("Expected a write for the same run to not add a history entry but got", len(history))
+	}
+	latest := history[2]
+	if latest.Node != "node-1" || latest.RunDuration != "5s" {
+		t.Fatal("Expected latest history entry to be updated but got", latest)
+	}
+	if latest.OK || len(latest.Errors) != 1 {
+		t.Fatal("Expected history to record the result of the run but got", latest.OK, latest.Errors)
+	}
+	if !latest.Time.Equal(&now) {
+		t.Fatal("Expected history entry to keep the time it was first recorded but got", latest.Time)
+	}
+
+	// run history can be disabled
+	if appendRunHistory(history, state, now, 0) != nil {
+		t.Fatal("Expected no run history when the history length is 0")
+	}
+}
+
+// TestCountRun tests that each run is counted once no matter how many times its khstate is written
+func TestCountRun(t *testing.T) {
+	var existing khstatev1.WorkloadDetails
+
+	existing.TotalRuns, existing.TotalFailures, existing.CountedUUID = countRun(existing, khstatev1.WorkloadDetails{OK: true, CurrentUUID: "a"})
+	if existing.TotalRuns != 1 || existing.TotalFailures != 0 || existing.CountedUUID != "a" {
+		t.Fatal("Expected a passing run to be counted but got", existing.TotalRuns, existing.TotalFailures, existing.CountedUUID)
+	}
+
+	// the failure of the run is counted even while thresholds keep the check OK
+	state := khstatev1.WorkloadDetails{
+		OK:            true,
+		CurrentUUID:   "b",
+		LastRunResult: &khstatev1.RunResult{OK: false},
+	}
+	existing.TotalRuns, existing.TotalFailures, existing.CountedUUID = countRun(existing, state)
+	if existing.TotalRuns != 2 || existing.TotalFailures != 1 {
+		t.Fatal("Expected a failed run to be counted but got", existing.TotalRuns, existing.TotalFailures)
+	}
+
+	// a second write for the same run is not counted again
+	existing.TotalRuns, existing.TotalFailures, existing.CountedUUID = countRun(existing, state)
+	if existing.TotalRuns != 2 || existing.TotalFailures != 1 {
+		t.Fatal("Expected a second write for the same run to not be counted but got", existing.TotalRuns, existing.TotalFailures)
+	}
+}
@@ -0,0 +1,69 @@
+package main
+
+import (
+	"strings"
+
+	log "github.com/sirupsen/logrus"
+
+	khcheckv1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khcheck/v1"
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external"
+)
+
+// checkDependencies returns the dependencies of every khcheck, keyed by the namespace/name of the check.  Checks that
+// run on other replicas when checks are sharded are included so that their dependencies are followed too.
+func (k *Kuberhealthy) checkDependencies() map[string][]string {
+	dependencies := make(map[string][]string)
+	for _, obj := range k.checkStore.List() {
+		kc, ok := obj.(*khcheckv1.KuberhealthyCheck)
+		if !ok {
+			continue
+		}
+		dependencies[kc.Namespace+"/"+kc.Name] = external.DependencyKeys(kc)
+	}
+	return dependencies
+}
+
+// dependencyBlockedReason returns why the check with the supplied namespace/name is blocked from running, or a blank
+// string when none of its dependencies are failing.  Dependencies of dependencies are followed so that a failure
+// blocks every check downstream of it.  Dependencies that have not reported yet or are suspended do not block.
+func dependencyBlockedReason(checkKey string, dependencies map[string][]string, lookup func(namespace string, name string) (khstatev1.WorkloadDetails, bool)) string {
+	visited := map[string]bool{checkKey: true}
+	queue := append([]string{}, dependencies[checkKey]...)
+
+	for len(queue) > 0 {
+		dependency := queue[0]
+		queue = queue[1:]
+		if visited[dependency] {
+			continue
+		}
+		visited[dependency] = true
+
+		namespace, name, found := strings.Cut(dependency, "/")
+		if !found {
+			log.Warningln("Ignoring dependency", dependency, "of check", checkKey, "because it is not in namespace/name form")
+			continue
+		}
+
+		details, exists := lookup(namespace, name)
+		if !exists {
+			log.Debugln("Dependency", dependency, "of check", checkKey, "has not reported a state yet")
+		}
+		if exists && !details.Suspended && !details.OK {
+			return "dependency " + dependency + " is failing"
+		}
+
+		queue = append(queue, dependencies[dependency]...)
+	}
+
+	return ""
+}
+
+// skipRequestedRun drops the pending on demand run of a check that is not going to run
+func (k *Kuberhealthy) skipRequestedRun(c *external.Checker) {
+	requestedRunUUID := c.TakeRunRequest()
+	if len(requestedRunUUID) > 0 {
+		log.Infoln("Dropping on demand run", requestedRunUUID, "of check", c.Name(), "in namespace", c.CheckNamespace())
+		k.clearRunNowAnnotation(c, requestedRunUUID)
+	}
+}
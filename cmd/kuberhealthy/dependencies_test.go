@@ -0,0 +1,47 @@
+package main
+
+import (
+	"testing"
+
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
+)
+
+// TestDependencyBlockedReason tests that checks are blocked by failing dependencies, including transitive ones
+func TestDependencyBlockedReason(t *testing.T) {
+	states := map[string]khstatev1.WorkloadDetails{
+		"kuberhealthy/dns":        {OK: true},
+		"kuberhealthy/deployment": {OK: true},
+		"kuberhealthy/http":       {OK: true},
+	}
+	lookup := func(namespace string, name string) (khstatev1.WorkloadDetails, bool) {
+		details, exists := states[namespace+"/"+name]
+		return details, exists
+	}
+	dependencies := map[string][]string{
+		"kuberhealthy/http":       {"kuberhealthy/deployment", "kuberhealthy/missing"},
+		"kuberhealthy/deployment": {"kuberhealthy/dns"},
+		"kuberhealthy/dns":        {"kuberhealthy/http"},
+	}
+
+	if reason := dependencyBlockedReason("kuberhealthy/http", dependencies, lookup); reason != "" {
+		t.Fatalf("expected check with passing dependencies to not be blocked, got %q", reason)
+	}
+
+	// a failure blocks checks downstream of it, but the cycle back to the failing check does not block it
+	states["kuberhealthy/dns"] = khstatev1.WorkloadDetails{OK: false}
+	if reason := dependencyBlockedReason("kuberhealthy/http", dependencies, lookup); reason != "dependency kuberhealthy/dns is failing" {
+		t.Fatalf("expected check to be blocked by its failing transitive dependency, got %q", reason)
+	}
+	if reason := dependencyBlockedReason("kuberhealthy/deployment", dependencies, lookup); reason != "dependency kuberhealthy/dns is failing" {
+		t.Fatalf("expected check to be blocked by its failing dependency, got %q", reason)
+	}
+	if reason := dependencyBlockedReason("kuberhealthy/dns", dependencies, lookup); reason != "" {
+		t.Fatalf("expected failing check to not be blocked by a dependency cycle, got %q", reason)
+	}
+
+	// suspended dependencies do not block
+	states["kuberhealthy/dns"] = khstatev1.WorkloadDetails{OK: false, Suspended: true}
+	if reason := dependencyBlockedReason("kuberhealthy/deployment", dependencies, lookup); reason != "" {
+		t.Fatalf("expected check to not be blocked by a suspended dependency, got %q", reason)
+	}
+}
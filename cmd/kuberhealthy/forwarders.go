@@ -0,0 +1,139 @@
+package main
+
+import (
+	"errors"
+
+	log "github.com/sirupsen/logrus"
+
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/metrics"
+)
+
+// metricForwarderConfigs returns the configuration of every enabled metric forwarder.  The influx, DogStatsD and
+// OTLP settings at the top level of the config are forwarders named after their type, alongside the forwarders listed
+// under metricForwarders.  Forwarders without a name are named after their type, and names must be unique so the
+// health metrics of each forwarder can be told apart.
+func metricForwarderConfigs(c *Config) ([]metrics.ForwarderConfig, error) {
+	forwarderConfigs := []metrics.ForwarderConfig{}
+
+	if c.EnableInflux {
+		forwarderConfigs = append(forwarderConfigs, metrics.ForwarderConfig{
+			Type: metrics.InfluxForwarder,
+			Influx: metrics.InfluxForwarderConfig{
+				URL:      c.InfluxURL,
+				Database: c.InfluxDB,
+				Username: c.InfluxUsername,
+				Password: c.InfluxPassword,
+				Org:      c.InfluxOrg,
+				Bucket:   c.InfluxBucket,
+				Token:    c.InfluxToken,
+				TLS:      c.InfluxTLS,
+				Batch:    c.InfluxBatch,
+			},
+		})
+	}
+	if c.EnableDogStatsD {
+		forwarderConfigs = append(forwarderConfigs, metrics.ForwarderConfig{Type: metrics.DogStatsDForwarder, DogStatsD: c.DogStatsD})
+	}
+	if c.OTLP.EnableMetrics {
+		forwarderConfigs = append(forwarderConfigs, metrics.ForwarderConfig{Type: metrics.OTLPForwarder, OTLP: c.OTLP})
+	}
+
+	for _, fc := range c.MetricForwarders {
+		if !fc.Enabled {
+			continue
+		}
+		forwarderConfigs = append(forwarderConfigs, fc)
+	}
+
+	names := map[string]bool{}
+	for i := range forwarderConfigs {
+		if len(forwarderConfigs[i].Name) == 0 {
+			forwarderConfigs[i].Name = forwarderConfigs[i].Type
+		}
+		name := forwarderConfigs[i].Name
+		if names[name] {
+			return nil, errors.New("more than one metric forwarder is named " + name)
+		}
+		names[name] = true
+	}
+
+	return forwarderConfigs, nil
+}
+
+// newMetricForwarder creates a fan out to every enabled metric forwarder in the config.  Returns nil when no metric
+// forwarders are enabled.
+func newMetricForwarder(c *Config) (*metrics.FanOut, error) {
+	forwarderConfigs, err := metricForwarderConfigs(c)
+	if err != nil {
+		return nil, err
+	}
+	if len(forwarderConfigs) == 0 {
+		return nil, nil
+	}
+
+	forwarders := []*metrics.Forwarder{}
+	for _, fc := range forwarderConfigs {
+		metricClient, err := metrics.NewForwarderClient(fc)
+		if err != nil {
+			// close the forwarders created so far so their clients do not leak
+			_ = metrics.NewFanOut(forwarders...).Close()
+			return nil, errors.New("error setting up metric forwarder " + fc.Name + ": " + err.Error())
+		}
+		log.Infoln("Forwarding metrics to", fc.Type, "metric forwarder", fc.Name)
+		forwarders = append(forwarders, metrics.NewForwarder(fc.Name, fc.Type, metricClient, fc.TagMapping, fc.QueueSize))
+	}
+	return metrics.NewFanOut(forwarders...), nil
+}
+
+// configureMetricForwarders sets up sending check metrics to every enabled metric forwarder
+func (k *Kuberhealthy) configureMetricForwarders() {
+	metricForwarder, err := newMetricForwarder(cfg)
+	if err != nil {
+		log.Fatalln("Error configuring metric forwarders:", err)
+	}
+	k.setMetricForwarder(metricForwarder)
+}
+
+// reloadMetricForwarders replaces the metric forwarders with the ones in the current config.  The current forwarders
+// are kept if the new ones can not be set up.
+func (k *Kuberhealthy) reloadMetricForwarders() {
+	metricForwarder, err := newMetricForwarder(cfg)
+	if err != nil {
+		log.Errorln("Error reloading metric forwarders. Keeping the current metric forwarders:", err)
+		return
+	}
+	k.setMetricForwarder(metricForwarder)
+}
+
+// setMetricForwarder replaces the metric forwarder and closes the previous one, which sends its buffered metrics.  The
+// health of the new forwarders is served on /metrics in place of the previous ones.
+func (k *Kuberhealthy) setMetricForwarder(metricForwarder *metrics.FanOut) {
+	k.forwarderMu.Lock()
+	previous := k.metricForwarder
+	k.metricForwarder = metricForwarder
+	if previous != nil {
+		k.metricsRegistry.Unregister(previous)
+	}
+	if metricForwarder != nil {
+		err := k.metricsRegistry.Register(metricForwarder)
+		if err != nil {
+			log.Errorln("Error registering metric forwarder health metrics:", err)
+		}
+	}
+	k.forwarderMu.Unlock()
+
+	if previous == nil {
+		return
+	}
+	err := previous.Close()
+	if err != nil {
+		log.Errorln("Error closing metric forwarders:", err)
+	}
+}
+
+// forwarder returns the current metric forwarder, or nil when no metric forwarders are enabled
+func (k *Kuberhealthy) forwarder() *metrics.FanOut {
+	k.forwarderMu.RLock()
+	defer k.forwarderMu.RUnlock()
+	return k.metricForwarder
+}
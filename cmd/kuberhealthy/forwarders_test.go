@@ -0,0 +1,50 @@
+package main
+
+import (
+	"testing"
+
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/metrics"
+)
+
+// TestMetricForwarderConfigs tests that the top level forwarder settings and the enabled listed forwarders are all
+// configured
+func TestMetricForwarderConfigs(t *testing.T) {
+	c := &Config{
+		EnableInflux:    true,
+		InfluxURL:       "http://influxdb:8086",
+		EnableDogStatsD: true,
+		MetricForwarders: []metrics.ForwarderConfig{
+			{Name: "collector", Type: metrics.OTLPForwarder, Enabled: true},
+			{Name: "disabled", Type: metrics.DogStatsDForwarder},
+		},
+	}
+
+	forwarderConfigs, err := metricForwarderConfigs(c)
+	if err != nil {
+		t.Fatal("Error configuring metric forwarders:", err)
+	}
+
+	names := []string{}
+	for _, fc := range forwarderConfigs {
+		names = append(names, fc.Name)
+	}
+	expected := []string{"influx", "dogstatsd", "collector"}
+	if len(names) != len(expected) {
+		t.Fatalf("expected forwarders %v, got %v", expected, names)
+	}
+	for i := range expected {
+		if names[i] != expected[i] {
+			t.Fatalf("expected forwarders %v, got %v", expected, names)
+		}
+	}
+	if forwarderConfigs[0].Influx.URL != c.InfluxURL {
+		t.Fatal("Expected the influx forwarder to use the top level influx settings, got", forwarderConfigs[0].Influx.URL)
+	}
+
+	// forwarder names must be unique
+	c.MetricForwarders = append(c.MetricForwarders, metrics.ForwarderConfig{Type: metrics.InfluxForwarder, Enabled: true})
+	_, err = metricForwarderConfigs(c)
+	if err == nil {
+		t.Fatal("Expected an error for two metric forwarders named influx")
+	}
+}
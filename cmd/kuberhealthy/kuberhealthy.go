@@ -2,24 +2,26 @@ package main
 
 import (
 	"context"
+	"crypto/tls"
 	"encoding/json"
 	"errors"
 	"fmt"
 	"io"
 	"net"
 	"net/http"
-	"reflect"
 	"strconv"
 	"strings"
 	"sync"
 	"time"
 
 	"github.com/google/uuid"
+	"github.com/prometheus/client_golang/prometheus"
 	log "github.com/sirupsen/logrus"
 	v1 "k8s.io/api/core/v1"
 	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
 	"k8s.io/apimachinery/pkg/watch"
 	"k8s.io/client-go/kubernetes"
+	"k8s.io/client-go/tools/cache"
 
 	khcheckv1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khcheck/v1"
 	khjobv1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khjob/v1"
@@ -27,23 +29,34 @@ import (
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external/status"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/health"
-	"github.com/kuberhealthy/kuberhealthy/v2/pkg/masterCalculation"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/metrics"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/tracing"
 )
 
 // Kuberhealthy represents the kuberhealthy server and its checks
 type Kuberhealthy struct {
-	Checks             []*external.Checker
-	ListenAddr         string // the listen address, such as ":80"
-	MetricForwarder    metrics.Client
+	Checks             []*external.Checker // the running checks, sorted by namespace/name. guarded by checksMu
+	ListenAddr         string              // the listen address, such as ":80"
+	Tracer             *tracing.Tracer     // records a trace of each khcheck run when OTLP traces are enabled
 	overrideKubeClient *kubernetes.Clientset
-	cancelChecksFunc   context.CancelFunc // invalidates the context of all running checks
-	cancelReaperFunc   context.CancelFunc // invalidates the context of the reaper
-	wg                 sync.WaitGroup     // used to track running checks
-	shutdownCtxFunc    context.CancelFunc // used to shutdown the main control select
-	stateReflector     *StateReflector    // a reflector that can cache the current state of the khState resources
-	TargetNamespace    string             // the namespace that this instance will operate on. to include all namespaces, set this to a blank
-	config             *Config            // the config struct loaded at setup
+	cancelChecksFunc   context.CancelFunc      // invalidates the context of all running checks
+	checksCtx          context.Context         // the context checks are started with. nil while checks are stopped
+	checksMu           sync.RWMutex            // guards Checks, checksCtx and runners
+	runners            map[string]*checkRunner // the running checks by the namespace/name of their khcheck
+	checkStore         cache.Store             // the khcheck resources cached by the check informer
+	checkInformer      cache.Controller        // reconciles checks when khcheck resources change
+	jobStore           cache.Store             // the khjob resources cached by the job informer
+	jobInformer        cache.Controller        // keeps the khjob cache up to date for the check API
+	cancelReaperFunc   context.CancelFunc      // invalidates the context of the reaper
+	wg                 sync.WaitGroup          // used to track running checks
+	shutdownCtxFunc    context.CancelFunc      // used to shutdown the main control select
+	stateReflector     *StateReflector         // a reflector that can cache the current state of the khState resources
+	metricForwarder    *metrics.FanOut         // sends check metrics to every enabled metric forwarder. guarded by forwarderMu
+	forwarderMu        sync.RWMutex            // guards metricForwarder
+	runQueue           *RunQueue               // limits how many check runs execute at the same time
+	metricsRegistry    *prometheus.Registry    // the run queue and metric forwarder collectors served on /metrics
+	TargetNamespace    string                  // the namespace that this instance will operate on. to include all namespaces, set this to a blank
+	config             *Config                 // the config struct loaded at setup
 }
 
 // NewKuberhealthy creates a new kuberhealthy checker instance restricted to the desired
@@ -55,12 +68,18 @@ func NewKuberhealthy(cfg *Config) *Kuberhealthy {
 		config:          cfg,
 	}
 	kh.stateReflector = NewStateReflector(kh.TargetNamespace)
+	kh.runQueue = NewRunQueue(cfg.RunQueue)
+	kh.metricsRegistry = prometheus.NewRegistry()
+	kh.metricsRegistry.MustRegister(kh.runQueue)
+	kh.runners = make(map[string]*checkRunner)
+	kh.checkStore, kh.checkInformer = kh.newCheckInformer()
+	kh.jobStore, kh.jobInformer = kh.newJobInformer()
 	return kh
 }
 
 // setCheckExecutionError sets an execution error for a check name in
 // its crd status
-func (k *Kuberhealthy) setCheckExecutionError(checkName string, checkNamespace string, exErr error) error {
+func (k *Kuberhealthy) setCheckExecutionError(checkName string, checkNamespace string, exErr error, attempts int) error {
 	details := khstatev1.NewWorkloadDetails(khstatev1.KHCheck)
 	check, err := k.getCheck(checkName, checkNamespace)
 	if err != nil {
@@ -69,8 +88,10 @@ func (k *Kuberhealthy) setCheckExecutionError(checkName string, checkNamespace s
 	if check.Namespace != "" {
 		details.Namespace = check.CheckNamespace()
 	}
-	details.OK = false
-	details.Errors = []string{"Check execution error: " + exErr.Error()}
+	details.LastRunResult = &khstatev1.RunResult{
+		OK:     false,
+		Errors: []string{"Check execution error: " + exErr.Error()},
+	}
 
 	// we need to maintain the current UUID, which means fetching it first
 	khc, err := k.getCheck(checkName, checkNamespace)
@@ -83,6 +104,10 @@ func (k *Kuberhealthy) setCheckExecutionError(checkName string, checkNamespace s
 		return fmt.Errorf("error when setting execution error on check (getting check state for current UUID) %s %s %w", checkName, checkNamespace, err)
 	}
 	details.CurrentUUID = checkState.CurrentUUID
+	details.Attempts = attempts
+
+	// the execution error is only shown once the check's failure threshold is reached
+	khc.ApplyRunThresholds(checkState, &details)
 	log.Debugln("Setting execution state of check", checkName, "to", details.OK, details.Errors, details.CurrentUUID, details.GetKHWorkload())
 
 	// store the check state with the CRD
@@ -127,14 +152,9 @@ func (k *Kuberhealthy) setJobExecutionError(jobName string, jobNamespace string,
 	return nil
 }
 
-// AddCheck adds a check to Kuberhealthy.  Must be done before Start or StartChecks
-// are called.
-func (k *Kuberhealthy) AddCheck(c *external.Checker) {
-	k.Checks = append(k.Checks, c)
-}
-
-// Shutdown causes the kuberhealthy chec k group to shutdown gracefully
-func (k *Kuberhealthy) Shutdown(doneChan chan struct{}) {
+// Shutdown causes the kuberhealthy chec k group to shutdown gracefully.  Queued notifications are dropped once ctx
+// is done.
+func (k *Kuberhealthy) Shutdown(ctx context.Context, doneChan chan struct{}) {
 	if k.shutdownCtxFunc != nil {
 		log.Infoln("shutdown: aborting control context")
 		k.shutdownCtxFunc() // stop the control system
@@ -142,6 +162,10 @@ func (k *Kuberhealthy) Shutdown(doneChan chan struct{}) {
 	time.Sleep(5 * time.Second) // help prevent more checks from starting in a race before control system stop happens
 	log.Infoln("shutdown: stopping checks")
 	k.StopChecks() // stop all checks
+	log.Infoln("shutdown: flushing metric forwarders")
+	k.setMetricForwarder(nil) // send buffered metrics before exiting
+	log.Infoln("shutdown: sending queued notifications")
+	setStateNotifier(ctx, nil)
 	log.Infoln("shutdown: ready for main program shutdown")
 	doneChan <- struct{}{}
 }
@@ -150,26 +174,28 @@ func (k *Kuberhealthy) Shutdown(doneChan chan struct{}) {
 // All checks are sent a shutdown command at the same time.
 func (k *Kuberhealthy) StopChecks() {
 
-	log.Infoln("control:", len(k.Checks), "checks stopping...")
+	// take the running checks so that the reconciler does not start or stop any while they shut down
+	k.checksMu.Lock()
+	runners := k.runners
+	k.runners = make(map[string]*checkRunner)
+	k.Checks = nil
+	k.checksCtx = nil
 	if k.cancelChecksFunc != nil {
 		k.cancelChecksFunc()
 	}
+	k.checksMu.Unlock()
 
 	// call a shutdown on all checks concurrently
-	for _, c := range k.Checks {
-		go func(c *external.Checker, kh *Kuberhealthy) {
-			log.Infoln("control: check", c.Name(), "stopping...")
-			err := c.Shutdown()
-			if err != nil {
-				log.Errorln("control: ERROR stopping check", c.Name(), err)
-			}
-			kh.wg.Done()
-			log.Infoln("control: check", c.Name(), "stopped")
-		}(c, k)
+	log.Infoln("control:", len(runners), "checks stopping...")
+	for _, runner := range runners {
+		k.stopCheckRunner(runner)
 	}
 
-	// wait for all checks to stop cleanly
+	// wait for all checks to stop cleanly, including checks that were replaced and are still stopping
 	log.Infoln("control: waiting for all checks to stop")
+	for _, runner := range runners {
+		<-runner.stopped
+	}
 	k.wg.Wait()
 
 	log.Infoln("control: all checks stopped.")
@@ -181,28 +207,39 @@ func (k *Kuberhealthy) Start(ctx context.Context) {
 	// start the khState reflector
 	go k.stateReflector.Start()
 
-	// if influxdb is enabled, configure it
-	if cfg.EnableInflux {
-		k.configureInfluxForwarding()
+	// configure the enabled metric forwarders
+	k.configureMetricForwarders()
+
+	// if OpenTelemetry traces are enabled, configure them
+	if cfg.OTLP.EnableTraces {
+		k.configureOTLPTracing()
 	}
 
 	// Start the web server and restart it if it crashes
 	go k.StartWebServer()
 
-	// find all the external checks from the khcheckcrd resources on the cluster and keep them in sync.
-	// use rate limiting to avoid reconfiguration spam
-	maxUpdateInterval := time.Second * 10
-	externalChecksUpdateChan := make(chan struct{}, 50)
-	externalChecksUpdateChanLimited := make(chan struct{}, 50)
-	go notifyChanLimiter(maxUpdateInterval, externalChecksUpdateChan, externalChecksUpdateChanLimited)
-	go k.monitorExternalChecks(ctx, externalChecksUpdateChan)
+	// cache the khcheck resources on the cluster and keep the running checks in sync with them.  the cache is
+	// filled before we can become master so that checks start with the full set of khchecks.
+	go k.checkInformer.Run(ctx.Done())
+	go k.jobInformer.Run(ctx.Done())
+	if !cache.WaitForCacheSync(ctx.Done(), k.checkInformer.HasSynced, k.jobInformer.HasSynced) {
+		log.Errorln("control: khcheck and khjob informer caches failed to sync")
+	}
 
-	// we use two channels to indicate when we gain or lose master status. use rate limiting to avoid
-	// reconfiguration spam
+	// we use two channels to indicate when we gain or lose master status
 	becameMasterChan := make(chan struct{}, 10)
 	lostMasterChan := make(chan struct{}, 10)
 	go k.masterMonitor(ctx, becameMasterChan, lostMasterChan)
 
+	// when checks are sharded, every replica runs the checks it owns and rebalances them as replicas come and go
+	var shardsChangedChan chan struct{}
+	if shardMembership != nil {
+		shardsChangedChan = make(chan struct{}, 1)
+		go shardMembership.Run(ctx, shardsChangedChan)
+		log.Infoln("control: Sharding checks across replicas. Starting checks.")
+		k.StartChecks(ctx)
+	}
+
 	// monitor for kuberhealthy jobs and trigger when a new job is added
 	go k.monitorKHJobs(ctx)
 
@@ -212,7 +249,7 @@ func (k *Kuberhealthy) Start(ctx context.Context) {
 
 	// loop and select channels to do appropriate thing when:
 	// - master kuberhealthy pod changes
-	// - new khchecks are added or modified
+	// - replicas sharing checks change
 	// - kuberhealthy configuration changes
 	for {
 		select {
@@ -220,30 +257,35 @@ func (k *Kuberhealthy) Start(ctx context.Context) {
 			log.Infoln("control: shutting down from context abort...")
 			return
 		case <-becameMasterChan: // we have become the current master instance and should run checks
-			// reset checks and re-add from configuration settings
-			log.Infoln("control: Became master. Reconfiguring and starting checks.")
-			k.StartChecks(ctx)
+			// reset checks and re-add from configuration settings.  sharded checks are already running.
+			if shardMembership == nil {
+				log.Infoln("control: Became master. Reconfiguring and starting checks.")
+				k.StartChecks(ctx)
+			}
 			k.StartReaper(ctx)
 		case <-lostMasterChan: // we are no longer master
-			log.Infoln("control: Lost master. Stopping checks.")
-			k.StopChecks()
-			k.StopReaper()
-		case <-externalChecksUpdateChanLimited: // external check change detected
-			log.Infoln("control: Witnessed a khcheck resource change...")
-
-			// if we are master, stop, reconfigure our khchecks, and start again with the new configuration
-			if isMaster {
-				log.Infoln("control: Reloading external check configurations due to khcheck update")
-				k.RestartChecks(ctx)
-				k.RestartReaper(ctx)
+			if shardMembership == nil {
+				log.Infoln("control: Lost master. Stopping checks.")
+				k.StopChecks()
 			}
+			k.StopReaper()
+		case <-shardsChangedChan: // replicas joined or left, so some checks may have a new owner
+			log.Infoln("control: Replicas sharing checks changed. Rebalancing checks.")
+			k.reconcileChecks()
 		case <-configReloadChan:
 			log.Infoln("control: Witnessed a kuberhealthy configuration change...")
 
-			// if we are master, stop, reconfigure our khchecks, and start again with the new configuration
-			if isMaster {
+			// every replica forwards the metrics of the reports it receives, so every replica reloads its forwarders.
+			// the same goes for the notifiers of the states it writes.
+			k.reloadMetricForwarders()
+			reloadNotifiers()
+
+			// if we are running checks, stop, reconfigure our khchecks, and start again with the new configuration
+			if shardMembership != nil || masterElector.IsMaster() {
 				log.Infoln("control: Reloading external check configurations due to kuberhealthy configuration update")
 				k.RestartChecks(ctx)
+			}
+			if masterElector.IsMaster() {
 				k.RestartReaper(ctx)
 			}
 		}
@@ -255,6 +297,11 @@ func (k *Kuberhealthy) StartReaper(ctx context.Context) {
 	reaperCtx, reaperCtxCancel := context.WithCancel(ctx)
 	k.cancelReaperFunc = reaperCtxCancel
 	go reaper(reaperCtx, k.TargetNamespace)
+
+	// checks start on every replica when they are sharded, so only the master reaps khstate resources
+	if shardMembership != nil {
+		go k.khStateResourceReaper(reaperCtx, k.TargetNamespace)
+	}
 }
 
 // StopReaper stops the check reaper
@@ -449,72 +496,6 @@ func (k *Kuberhealthy) getKHState(namespace string, checkName string) (khstatev1
 	return khStateClient.KuberhealthyStates(namespace).Get(checkName, metav1.GetOptions{})
 }
 
-// watchForKHCheckChanges watches for changes to khcheck objects and returns them through the specified channel
-func (k *Kuberhealthy) watchForKHCheckChanges(ctx context.Context, c chan struct{}) {
-
-	log.Debugln("Spawned watcher for KH check changes")
-
-	for {
-		log.Debugln("Starting a watch for khcheck object changes")
-
-		// wait a second so we don't retry too quickly on error
-		time.Sleep(time.Second)
-
-		// start a watch on khcheck resources
-		watcher, err := khCheckClient.KuberhealthyChecks(k.TargetNamespace).Watch(metav1.ListOptions{})
-		// watcher, err := watchUnstructuredKHChecks(ctx, k.TargetNamespace)
-		if err != nil {
-			log.Errorln("error creating watcher for khcheck objects:", err)
-			continue
-		}
-
-		// watch for the watcher context to end, or the parent context.  If the parent context ends, we close the watcher.
-		// if the watcher context ends, we shut down this go routine to prevent a leak as it restarts
-		watcherCtx, watcherCtxCancel := context.WithCancel(context.Background())
-		go func(watchCtx context.Context, ctx context.Context, watcher watch.Interface) {
-			select {
-			case <-watchCtx.Done():
-				break
-			case <-ctx.Done():
-				watcher.Stop()
-			}
-			log.Debugln("khcheck change monitor watch stopping")
-		}(watcherCtx, ctx, watcher)
-
-		// loop over results and return them to the calling channel until we hit an error, then close and restart
-		for khc := range watcher.ResultChan() {
-			switch khc.Type {
-			case watch.Added:
-				log.Debugln("khcheck monitor saw an added event")
-				c <- struct{}{}
-			case watch.Modified:
-				log.Debugln("khcheck monitor saw a modified event")
-				c <- struct{}{}
-			case watch.Deleted:
-				log.Debugln("khcheck monitor saw a deleted event")
-				c <- struct{}{}
-			case watch.Error:
-				log.Debugln("khcheck monitor saw an error event")
-				e := khc.Object.(*metav1.Status)
-				log.Errorln("Error when watching for khcheck changes:", e.Reason)
-				continue
-			default:
-				log.Warningln("khcheck monitor saw an unknown event type and ignored it:", khc.Type)
-			}
-		}
-
-		// if the watcher breaks, shutdown the parent context monitor go routine
-		watcherCtxCancel()
-
-		select {
-		case <-ctx.Done():
-			log.Debugln("khcheck monitor closing due to context cancellation")
-			return
-		default:
-		}
-	}
-}
-
 func verifyNewKHJob(khJobName string, khJobNamespace string) bool {
 
 	kj, err := khJobClient.KuberhealthyJobs(khJobNamespace).Get(khJobName, metav1.GetOptions{})
@@ -527,186 +508,93 @@ func verifyNewKHJob(khJobName string, khJobNamespace string) bool {
 	return kj.Spec.Phase == ""
 }
 
-// monitorExternalChecks watches for changes to the external check CRDs
-func (k *Kuberhealthy) monitorExternalChecks(ctx context.Context, notify chan struct{}) {
-
-	// make a map of resource versions so we know when things change
-	knownSettings := make(map[string]khcheckv1.CheckConfig)
-
-	// start watching for events to changes in the background
-	c := make(chan struct{})
-	go k.watchForKHCheckChanges(ctx, c)
-
-	// each time  we see a change in our khcheck structs, we should look at every object to see if something has changed
-	for {
-
-		// wait for the change channel to detect a change before scanning again
-		<-c
-		log.Debugln("Change notification received. Scanning for external check changes...")
-
-		khChecks, err := k.listKHChecks(k.TargetNamespace)
-		if err != nil {
-			log.Errorln("error listing unstructured khChecks: %w", err)
-			continue
-		}
-
-		// this bool indicates if we should send a change signal to the channel
-		var foundChange bool
-
-		// if a khcheck has been deleted, then we signal for change and purge it from the knownSettings map.
-		for mapName := range knownSettings {
-			var existsInItems bool // indicates the item exists in the item listing
-
-			for _, kc := range khChecks.Items {
-
-				itemMapName := kc.Namespace + "/" + kc.Name
-				if itemMapName == mapName {
-					existsInItems = true
-					break
-				}
-			}
-			if !existsInItems {
-				log.Debugln("Detected khcheck deletion for", mapName)
-				delete(knownSettings, mapName)
-				foundChange = true
-			}
-		}
-
-		for _, kc := range khChecks.Items {
-			if err != nil {
-				log.Errorln("Error converting unstructured object to khcheck:", err)
-				continue
-			}
-
-			mapName := kc.Namespace + "/" + kc.Name
-
-			log.Debugln("Scanning khcheck CRD", mapName, "for changes since last seen...")
-
-			if len(kc.Namespace) < 1 {
-				log.Warning("Got khcheck update from object with no namespace...")
-				continue
-			}
-			if len(kc.Name) < 1 {
-				log.Warning("Got khcheck update from object with no name...")
-				continue
-			}
-
-			// if we don't know about this check yet, just store the state and continue.  The check is already
-			// loaded on the first check configuration run.
-			_, exists := knownSettings[mapName]
-			if !exists {
-				log.Debugln("First time seeing khcheck of name", mapName)
-				knownSettings[mapName] = kc.Spec
-				foundChange = true
-			}
-
-			// check if run interval has changed
-			if knownSettings[mapName].RunInterval != kc.Spec.RunInterval {
-				log.Debugln("The khcheck run interval for", mapName, "has changed.")
-				foundChange = true
-			}
-
-			// check if run timeout has changed
-			if knownSettings[mapName].Timeout != kc.Spec.Timeout {
-				log.Debugln("The khcheck timeout for", mapName, "has changed.")
-				foundChange = true
-			}
-
-			// check if extraLabels has changed
-			if !foundChange && !reflect.DeepEqual(knownSettings[mapName].ExtraLabels, kc.Spec.ExtraLabels) {
-				log.Debugln("The khcheck extra labels for", mapName, "has changed.")
-				foundChange = true
-			}
-
-			// check if extraAnnotations has changed
-			if !foundChange && !reflect.DeepEqual(knownSettings[mapName].ExtraAnnotations, kc.Spec.ExtraAnnotations) {
-				log.Debugln("The khcheck extra annotations for", mapName, "has changed.")
-				foundChange = true
-			}
-
-			// check if CheckConfig has changed (PodSpec)
-			if !foundChange && !reflect.DeepEqual(knownSettings[mapName].PodSpec, kc.Spec.PodSpec) {
-				log.Debugln("The khcheck for", mapName, "has changed.")
-				foundChange = true
-			}
-
-			// finally, update known settings before continuing to the next interval
-			knownSettings[mapName] = kc.Spec
-		}
-
-		// if a change was detected, we signal the notify channel
-		if foundChange {
-			log.Debugln("Signaling that a change was found in external check configuration")
-			notify <- struct{}{}
-		}
-	}
-}
+// configureCheck creates an external checker from a khcheck resource
+func (k *Kuberhealthy) configureCheck(kc khcheckv1.KuberhealthyCheck) *external.Checker {
+	var err error
+	log.Debugln("Loading check CRD:", kc.Name)
 
-// setExternalChecks syncs up the state of the external-checks installed in this
-// Kuberhealthy struct.
-func (k *Kuberhealthy) addExternalChecks(ctx context.Context) error {
+	log.Debugf("External check custom resource loaded: %v", kc)
 
-	log.Debugln("Fetching khcheck configurations...")
+	// create a new kubernetes client for this external checker
+	log.Infoln("Enabling external check:", kc.Name)
+	c := external.New(kubernetesClient, &kc, khCheckClient, khStateClient, cfg.ExternalCheckReportingURL)
+	c.Tracer = k.Tracer
 
-	khChecks, err := k.listKHChecks(k.TargetNamespace)
+	// parse the run interval string from the custom resource and setup the run interval.  checks with a
+	// cron schedule do not need a run interval, so we only complain about it when there is no schedule.
+	c.RunInterval, err = time.ParseDuration(kc.Spec.RunInterval)
 	if err != nil {
-		return err
+		if len(kc.Spec.Schedule) == 0 {
+			log.Errorln("Error parsing duration for check", c.CheckName, "in namespace", c.Namespace, err)
+			log.Errorln("Defaulting check to a runtime of ten minutes.")
+		}
+		c.RunInterval = DefaultRunInterval
 	}
 
-	log.Debugln("Found", len(khChecks.Items), "external checks to load")
+	log.Debugln("RunInterval for check:", c.CheckName, "set to", c.RunInterval)
 
-	// iterate on each check CRD resource and add it as a check
-	for _, kc := range khChecks.Items {
+	// parse the cron schedule if present.  when set, it takes the place of the run interval
+	if len(kc.Spec.Schedule) > 0 {
+		c.RunSchedule, err = external.ParseSchedule(kc.Spec.Schedule, time.Now())
 		if err != nil {
-			log.Errorln("Error converting unstructured object to khcheck:", err)
-			continue
+			log.Errorln("Error parsing schedule for check", c.CheckName, "in namespace", c.Namespace, err)
+			log.Errorln("Falling back to a run interval of", c.RunInterval)
+			c.RunSchedule = nil
 		}
-		log.Debugln("Loading check CRD:", kc.Name)
-
-		log.Debugf("External check custom resource loaded: %v", kc)
-
-		// create a new kubernetes client for this external checker
-		log.Infoln("Enabling external check:", kc.Name)
-		c := external.New(kubernetesClient, &kc, khCheckClient, khStateClient, cfg.ExternalCheckReportingURL)
+		log.Debugln("RunSchedule for check:", c.CheckName, "set to", kc.Spec.Schedule)
+	}
 
-		// parse the run interval string from the custom resource and setup the run interval
-		c.RunInterval, err = time.ParseDuration(kc.Spec.RunInterval)
+	// parse the run jitter if present
+	if len(kc.Spec.Jitter) > 0 {
+		c.RunJitter, err = time.ParseDuration(kc.Spec.Jitter)
 		if err != nil {
-			log.Errorln("Error parsing duration for check", c.CheckName, "in namespace", c.Namespace, err)
-			log.Errorln("Defaulting check to a runtime of ten minutes.")
-			c.RunInterval = DefaultRunInterval
+			log.Errorln("Error parsing jitter for check", c.CheckName, "in namespace", c.Namespace, err)
+			log.Errorln("Defaulting check to run without jitter.")
+			c.RunJitter = 0
 		}
+	}
 
-		log.Debugln("RunInterval for check:", c.CheckName, "set to", c.RunInterval)
+	log.Debugln("RunJitter for check:", c.CheckName, "set to", c.RunJitter)
 
-		// parse the user specified timeout if present
-		c.RunTimeout = DefaultTimeout
-		if len(kc.Spec.Timeout) > 0 {
-			c.RunTimeout, err = time.ParseDuration(kc.Spec.Timeout)
-			if err != nil {
-				log.Errorln("Error parsing timeout for check", c.CheckName, "in namespace", c.Namespace, err)
-				log.Errorln("Defaulting check to a timeout of", DefaultTimeout)
-			}
+	// parse the retry backoff if present
+	c.RetryBackoff = external.DefaultRetryBackoff
+	if len(kc.Spec.RetryBackoff) > 0 {
+		c.RetryBackoff, err = time.ParseDuration(kc.Spec.RetryBackoff)
+		if err != nil || c.RetryBackoff <= 0 {
+			log.Errorln("Error parsing retry backoff for check", c.CheckName, "in namespace", c.Namespace, err)
+			log.Errorln("Defaulting check to a retry backoff of", external.DefaultRetryBackoff)
+			c.RetryBackoff = external.DefaultRetryBackoff
 		}
+	}
 
-		log.Debugln("RunTimeout for check:", c.CheckName, "set to", c.RunTimeout)
+	log.Debugln("Retries for check:", c.CheckName, "set to", c.Retries, "with a backoff of", c.RetryBackoff)
 
-		// add on extra annotations and labels
-		if c.ExtraAnnotations != nil {
-			log.Debugln("External check setting extra annotations:", c.ExtraAnnotations)
-			c.ExtraAnnotations = kc.Spec.ExtraAnnotations
-		}
-		if c.ExtraLabels != nil {
-			log.Debugln("External check setting extra labels:", c.ExtraLabels)
-			c.ExtraLabels = kc.Spec.ExtraLabels
+	// parse the user specified timeout if present
+	c.RunTimeout = DefaultTimeout
+	if len(kc.Spec.Timeout) > 0 {
+		c.RunTimeout, err = time.ParseDuration(kc.Spec.Timeout)
+		if err != nil {
+			log.Errorln("Error parsing timeout for check", c.CheckName, "in namespace", c.Namespace, err)
+			log.Errorln("Defaulting check to a timeout of", DefaultTimeout)
 		}
-		log.Debugln("External check labels and annotations:", c.ExtraLabels, c.ExtraAnnotations)
+	}
 
-		// add the check into the checker
-		k.AddCheck(c)
+	log.Debugln("RunTimeout for check:", c.CheckName, "set to", c.RunTimeout)
+
+	// add on extra annotations and labels
+	if c.ExtraAnnotations != nil {
+		log.Debugln("External check setting extra annotations:", c.ExtraAnnotations)
+		c.ExtraAnnotations = kc.Spec.ExtraAnnotations
 	}
+	if c.ExtraLabels != nil {
+		log.Debugln("External check setting extra labels:", c.ExtraLabels)
+		c.ExtraLabels = kc.Spec.ExtraLabels
+	}
+	log.Debugln("External check labels and annotations:", c.ExtraLabels, c.ExtraAnnotations)
 
-	return nil
+	// pick up any on demand run that was requested before the check was loaded
+	requestRunFromAnnotation(kc, c)
+	return c
 }
 
 // addExternalJobs syncs up the state of the all jobs installed in this Kuberhealthy struct.
@@ -747,6 +635,7 @@ func (k *Kuberhealthy) configureJob(job khjobv1.KuberhealthyJob) *external.Check
 // triggerKHJob checks if its master, sets the context, and runs the khjob in a goroutine
 func (k *Kuberhealthy) triggerKHJob(ctx context.Context, job khjobv1.KuberhealthyJob) {
 
+	isMaster := masterElector.IsMaster()
 	log.Debugln("khjob trigger, isMaster:", isMaster)
 	// only the master pod should be running khjobs or khjobs are duplicated
 	if isMaster {
@@ -754,135 +643,37 @@ func (k *Kuberhealthy) triggerKHJob(ctx context.Context, job khjobv1.Kuberhealth
 	}
 }
 
-// StartChecks starts all checks concurrently and ensures they stay running
+// StartChecks starts all checks concurrently and ensures they stay running.  From then on, the check informer starts,
+// restarts and stops checks as their khcheck resources change.
 func (k *Kuberhealthy) StartChecks(ctx context.Context) {
 	// wait for all check wg to be done, just in case
 	k.wg.Wait()
-
-	log.Infoln("control: Reloading check configuration...")
-	k.configureChecks(ctx)
-
-	// sleep to make a more graceful switch-up during lots of master and check changes coming in
-	log.Infoln("control:", len(k.Checks), "checks starting!")
+	k.runQueue.SetConfig(cfg.RunQueue)
 
 	// create a context for checks to abort with
 	checkGroupCtx, cancelFunc := context.WithCancel(ctx)
+	k.checksMu.Lock()
+	k.checksCtx = checkGroupCtx
 	k.cancelChecksFunc = cancelFunc
+	k.checksMu.Unlock()
 
-	// start each check with this check group's context
-	for _, c := range k.Checks {
-		k.wg.Add(1)
-		// start the check in its own routine
-		go k.runCheck(checkGroupCtx, c)
-	}
-
-	// spin up the khState reaper with a context after checks have been configured and started
-	log.Infoln("control: reaper starting!")
-	go k.khStateResourceReaper(ctx, k.TargetNamespace)
-}
-
-// masterStatusWatcher watches for master change events and updates the global upcomingMasterState along
-// with the global lastMasterChangeTime
-func (k *Kuberhealthy) masterStatusWatcher(ctx context.Context) {
-
-	// continue reconnecting to the api to resume the pod watch if it ends
-	for {
-		log.Debugln("master status watcher starting up...")
-
-		// don't retry our watch too fast
-		time.Sleep(time.Second * 5)
-
-		// setup a pod watching client for kuberhealthy pods
-		watcher, err := kubernetesClient.CoreV1().Pods(podNamespace).Watch(ctx, metav1.ListOptions{
-			LabelSelector: "app=kuberhealthy",
-		})
-		if err != nil {
-			log.Errorln("error when attempting to watch for kuberhealthy pod changes:", err)
-			continue
-		}
-
-		// watch for the parent context to expire as well as this watch context. if the parent context expires,
-		// then we stop the watcher.  if the watcher context expires, we terminate the go routine to prevent a
-		// goroutine leak
-		watcherCtx, watcherCtxCancel := context.WithCancel(context.Background())
-		go func(watchCtx context.Context, ctx context.Context, watcher watch.Interface) {
-			select {
-			case <-watchCtx.Done():
-				break
-			case <-ctx.Done():
-				watcher.Stop()
-			}
-			log.Debugln("master status monitor watch stopping")
-		}(watcherCtx, ctx, watcher)
-
-		// on each update from the watch, we re-check our master status.
-		for range watcher.ResultChan() {
-
-			// update the time we last saw a master event
-			lastMasterChangeTime = time.Now()
-
-			// determine if we are becoming master or not
-			var err error
-			upcomingMasterState, err = masterCalculation.IAmMaster(kubernetesClient)
-			if err != nil {
-				log.Errorln(err)
-			}
-
-			// update the time we last saw a master event
-			log.Debugln("master status monitor saw a master event")
-			lastMasterChangeTime = time.Now()
-		}
-
-		// cancel the watcher by revoking its context
-		watcherCtxCancel()
+	// start a check for each khcheck in the informer cache
+	log.Infoln("control: Loading check configuration...")
+	k.reconcileChecks()
+	log.Infoln("control:", len(k.loadedChecks()), "checks starting!")
 
-		// if the context has expired, then shut down the master status watcher entirely
-		select {
-		case <-ctx.Done():
-			log.Debugln("master status monitor stopping due to context cancellation")
-			return
-		default:
-		}
+	// spin up the khState reaper with a context after checks have been configured and started.  when checks are
+	// sharded, the reaper is started by the master instead.
+	if shardMembership == nil {
+		log.Infoln("control: reaper starting!")
+		go k.khStateResourceReaper(ctx, k.TargetNamespace)
 	}
 }
 
-// masterMonitor periodically evaluates the current and upcoming master state
-// and makes it so when appropriate
+// masterMonitor takes part in lease based master election and notifies the supplied channels when we
+// gain or lose master status
 func (k *Kuberhealthy) masterMonitor(ctx context.Context, becameMasterChan chan struct{}, lostMasterChan chan struct{}) {
-
-	// watch master pod event changes and recalculate the current master state of this pdo with each
-	go k.masterStatusWatcher(ctx)
-
-	interval := time.Second * 10
-
-	ticker := time.NewTicker(interval)
-	defer ticker.Stop()
-
-	// on each tick, we ensure that enough time has passed since the last master change
-	// event, then we calculate if we should become or lose master.
-	for range ticker.C {
-
-		if time.Since(lastMasterChangeTime) < interval {
-			log.Println("control: waiting for master changes to settle...")
-			continue
-		}
-
-		// dupe the global to prevent races
-		goingToBeMaster := upcomingMasterState
-
-		// stop checks if we are no longer the master
-		if goingToBeMaster && !isMaster {
-			becameMasterChan <- struct{}{}
-		}
-
-		// start checks if we are now master
-		if !goingToBeMaster && isMaster {
-			lostMasterChan <- struct{}{}
-		}
-
-		// refresh global isMaster state
-		isMaster = goingToBeMaster
-	}
+	masterElector.Run(ctx, becameMasterChan, lostMasterChan)
 }
 
 // runJob runs the job and sets its status
@@ -902,6 +693,12 @@ func (k *Kuberhealthy) runJob(ctx context.Context, job khjobv1.KuberhealthyJob)
 	default:
 	}
 
+	// only the holder of the master lease may run jobs
+	if !masterElector.IsMaster() {
+		log.Warningln("Skipping run of job", j.Name(), "in namespace", j.CheckNamespace(), "because this pod does not hold the master lease")
+		return
+	}
+
 	// Run the job
 	log.Infoln("Running job:", j.Name())
 	// Record job run start time
@@ -944,6 +741,9 @@ func (k *Kuberhealthy) runJob(ctx context.Context, job khjobv1.KuberhealthyJob)
 	details.OK, details.Errors = j.CurrentStatus()
 	details.RunDuration = jobRunDuration.String()
 	details.CurrentUUID = jobDetails.CurrentUUID
+	details.Warnings = jobDetails.Warnings
+	details.Severity = jobDetails.Severity
+	details.Metrics = jobDetails.Metrics
 
 	// Fetch node information from running check pod using kh run uuid
 	selector := "kuberhealthy-run-id=" + details.CurrentUUID
@@ -956,7 +756,7 @@ func (k *Kuberhealthy) runJob(ctx context.Context, job khjobv1.KuberhealthyJob)
 	log.Debugln("node name:", details.Node, "nodeName", j.Node)
 
 	// send data to the metric forwarder if configured
-	if k.MetricForwarder != nil {
+	if metricForwarder := k.forwarder(); metricForwarder != nil {
 		checkStatus := 0
 		if details.OK {
 			checkStatus = 1
@@ -977,10 +777,11 @@ func (k *Kuberhealthy) runJob(ctx context.Context, job khjobv1.KuberhealthyJob)
 			{j.Name() + "." + j.CheckNamespace(): checkStatus},
 			{"RunDuration." + j.Name() + "." + j.CheckNamespace(): runDuration.Seconds()},
 		}
-		err = k.MetricForwarder.Push(metric, tags)
+		err = metricForwarder.Push(metric, tags)
 		if err != nil {
 			log.Errorln("Error forwarding metrics", err)
 		}
+		k.forwardReportedMetrics(metricForwarder, j.Name(), j.CheckNamespace(), details)
 	}
 
 	log.Infoln("Setting state of job", j.Name(), "in namespace", j.CheckNamespace(), "to", details.OK, details.Errors, details.RunDuration, details.CurrentUUID, details.GetKHWorkload())
@@ -1003,13 +804,50 @@ func (k *Kuberhealthy) runCheck(ctx context.Context, c *external.Checker) {
 
 	log.Println("Starting check:", c.CheckNamespace(), "/", c.Name())
 
-	// run on an interval specified by the package
-	ticker := time.NewTicker(c.Interval())
+	// the scheduled time of the last run, without jitter, is used to calculate when the next run should happen
+	var lastScheduledRun time.Time
+
+	// track if the khstate of the check is marked as suspended so that it is only updated when that changes
+	currentDetails, _ := k.stateReflector.WorkloadDetails(c.CheckNamespace(), c.Name())
+	markedSuspended := currentDetails.Suspended
 
 	// run the check forever and write its results to the kuberhealthy
 	// CRD resource for the check
 	for {
 
+		// show the check as suspended or resumed when its suspension changes
+		suspended := c.Suspended(time.Now())
+		if suspended != markedSuspended && ownsCheck(c.CheckNamespace()+"/"+c.Name()) {
+			log.Infoln("Setting suspended state of check", c.Name(), "in namespace", c.CheckNamespace(), "to", suspended)
+			err := setCheckSuspended(c, suspended)
+			if err != nil {
+				log.Errorln("Error setting suspended state of check", c.Name(), "in namespace", c.CheckNamespace()+":", err)
+			} else {
+				markedSuspended = suspended
+			}
+		}
+
+		// wait for the next run on the interval or cron schedule specified by the check, or for an on demand run.
+		// checks without an upcoming scheduled run only run on demand.
+		scheduledRun := c.NextRunTime(lastScheduledRun, time.Now())
+		var nextRunChan <-chan time.Time
+		if scheduledRun.IsZero() {
+			log.Warningln("Check", c.Name(), "in namespace", c.CheckNamespace(), "has no upcoming scheduled run. Waiting for on demand runs.")
+		} else {
+			nextRunTime := scheduledRun.Add(c.Jitter())
+			if time.Until(nextRunTime) > 0 {
+				log.Infoln("Waiting until", nextRunTime.Format(time.RFC3339), "for next run of check", c.Name(), "in namespace", c.CheckNamespace())
+			}
+			nextRunChan = time.After(time.Until(nextRunTime))
+		}
+		var onDemandRun bool
+		select {
+		case <-ctx.Done():
+		case <-nextRunChan:
+		case <-c.RunRequests():
+			onDemandRun = true
+		}
+
 		// break out if context cancels
 		select {
 		case <-ctx.Done():
@@ -1020,23 +858,83 @@ func (k *Kuberhealthy) runCheck(ctx context.Context, c *external.Checker) {
 		default:
 		}
 
-		// Run the check
-		log.Infoln("Running check:", c.Name())
-		// Record check run start time
+		// Record check run start time.  on demand runs do not move the regular schedule of the check.
 		checkStartTime := time.Now()
-		err := c.Run(ctx, kubernetesClient)
+		if !onDemandRun {
+			lastScheduledRun = scheduledRun
+		}
+
+		// only the holder of the master lease, or the replica that owns the check when checks are sharded, may run
+		// checks.  this fences off runs from a pod that has lost the check but has not yet stopped it.
+		if k.skipUnownedRun(c, onDemandRun) {
+			continue
+		}
+
+		// suspended checks skip their runs, including any that were requested on demand
+		if c.Suspended(time.Now()) {
+			log.Infoln("Skipping run of check", c.Name(), "in namespace", c.CheckNamespace(), "because it is suspended")
+			if onDemandRun {
+				k.skipRequestedRun(c)
+			}
+			continue
+		}
+
+		// checks with a failing dependency are shown as blocked instead of running so that one failure does not
+		// cause every check that relies on it to fail as well
+		blockedReason := dependencyBlockedReason(c.CheckNamespace()+"/"+c.Name(), k.checkDependencies(), k.stateReflector.WorkloadDetails)
+		if len(blockedReason) > 0 {
+			log.Infoln("Skipping run of check", c.Name(), "in namespace", c.CheckNamespace(), "because it is blocked:", blockedReason)
+			err := setCheckBlocked(c, blockedReason)
+			if err != nil {
+				log.Errorln("Error setting blocked state of check", c.Name(), "in namespace", c.CheckNamespace()+":", err)
+			}
+			if onDemandRun {
+				k.skipRequestedRun(c)
+			}
+			continue
+		}
+
+		// each attempt of the run waits for the run queue to let it start so that the number of runs executing at
+		// once stays within the configured limits.  the run duration does not include the time spent waiting in the
+		// run queue before the first attempt.
+		var runStarted bool
+		acquireRunSlot := func(ctx context.Context) (func(), error) {
+			release, err := k.runQueue.Acquire(ctx, c.CheckNamespace())
+			if err == nil && !runStarted {
+				runStarted = true
+				checkStartTime = time.Now()
+			}
+			return release, err
+		}
+
+		// on demand runs use the UUID that was handed to the requester of the run
+		runUUID := uuid.New().String()
+		if onDemandRun {
+			requestedRunUUID := c.TakeRunRequest()
+			if len(requestedRunUUID) > 0 {
+				runUUID = requestedRunUUID
+				log.Infoln("Running check", c.Name(), "in namespace", c.CheckNamespace(), "on demand with run uuid", runUUID)
+				k.clearRunNowAnnotation(c, runUUID)
+			}
+		}
+
+		// Run the check, retrying runs that fail to execute
+		log.Infoln("Running check:", c.Name())
+		attempts, err := c.RunWithRetries(ctx, kubernetesClient, runUUID, acquireRunSlot)
+		if attempts == 0 {
+			log.Infoln("Shutting down check run while waiting in the run queue:", c.Name(), "in namespace", c.CheckNamespace())
+			return
+		}
 		if err != nil {
 			log.Errorln("Error running check:", c.Name(), "in namespace", c.CheckNamespace()+":", err)
 			if strings.Contains(err.Error(), "pod deleted expectedly") {
 				log.Infoln("Skipping this run due to expected pod removal before completion")
-				<-ticker.C
 			}
 			// set any check run errors in the CRD
-			err = k.setCheckExecutionError(c.Name(), c.CheckNamespace(), err)
+			err = k.setCheckExecutionError(c.Name(), c.CheckNamespace(), err, attempts)
 			if err != nil {
 				log.Errorln("Error setting check execution error:", err)
 			}
-			<-ticker.C
 			continue
 		}
 		log.Debugln("Done running check:", c.Name(), "in namespace", c.CheckNamespace())
@@ -1054,9 +952,19 @@ func (k *Kuberhealthy) runCheck(ctx context.Context, c *external.Checker) {
 		}
 		details := khstatev1.NewWorkloadDetails(khstatev1.KHCheck)
 		details.Namespace = c.CheckNamespace()
-		details.OK, details.Errors = c.CurrentStatus()
 		details.RunDuration = checkRunDuration.String()
 		details.CurrentUUID = checkDetails.CurrentUUID
+		details.Warnings = checkDetails.Warnings
+		details.Metrics = checkDetails.Metrics
+		details.Attempts = attempts
+
+		// set the state shown for the check from the result of this run once the check's thresholds are reached
+		details.LastRunResult = checkDetails.LastRunResult
+		if details.LastRunResult == nil {
+			runOK, runErrors := c.CurrentStatus()
+			details.LastRunResult = &khstatev1.RunResult{OK: runOK, Errors: runErrors}
+		}
+		c.ApplyRunThresholds(checkDetails, &details)
 
 		// Fetch node information from running check pod using kh run uuid
 		selector := "kuberhealthy-run-id=" + details.CurrentUUID
@@ -1069,7 +977,7 @@ func (k *Kuberhealthy) runCheck(ctx context.Context, c *external.Checker) {
 		log.Debugln("node name:", details.Node, "nodeName", c.Node)
 
 		// send data to the metric forwarder if configured
-		if k.MetricForwarder != nil {
+		if metricForwarder := k.forwarder(); metricForwarder != nil {
 			checkStatus := 0
 			if details.OK {
 				checkStatus = 1
@@ -1090,10 +998,11 @@ func (k *Kuberhealthy) runCheck(ctx context.Context, c *external.Checker) {
 				{c.Name() + "." + c.CheckNamespace(): checkStatus},
 				{"RunDuration." + c.Name() + "." + c.CheckNamespace(): runDuration.Seconds()},
 			}
-			err = k.MetricForwarder.Push(metric, tags)
+			err = metricForwarder.Push(metric, tags)
 			if err != nil {
 				log.Errorln("Error forwarding metrics", err)
 			}
+			k.forwardReportedMetrics(metricForwarder, c.Name(), c.CheckNamespace(), details)
 		}
 
 		log.Infoln("Setting state of check", c.Name(), "in namespace", c.CheckNamespace(), "to", details.OK, details.Errors, details.RunDuration, details.CurrentUUID, details.GetKHWorkload())
@@ -1103,9 +1012,30 @@ func (k *Kuberhealthy) runCheck(ctx context.Context, c *external.Checker) {
 		if err != nil {
 			log.Errorln("Error storing CRD state for check:", c.Name(), "in namespace", c.CheckNamespace(), err)
 		}
+	}
+}
 
-		log.Infoln("Waiting for next run of check", c.Name(), "in namespace", c.CheckNamespace())
-		<-ticker.C // wait for next run
+// forwardReportedMetrics sends the metrics reported by a check or job run to the metric forwarder.  Each metric is
+// pushed with the labels it was reported with so that they become tags on the forwarded point.
+func (k *Kuberhealthy) forwardReportedMetrics(metricForwarder *metrics.FanOut, name string, namespace string, details khstatev1.WorkloadDetails) {
+	for _, m := range details.Metrics {
+		tags := map[string]string{}
+		for l, v := range m.Labels {
+			tags[l] = v
+		}
+		tags["KuberhealthyPod"] = details.AuthoritativePod
+		tags["Namespace"] = namespace
+		tags["Name"] = name
+		if len(m.Unit) > 0 {
+			tags["Unit"] = m.Unit
+		}
+		metric := metrics.Metric{
+			{m.Name + "." + name + "." + namespace: m.Value},
+		}
+		err := metricForwarder.Push(metric, tags)
+		if err != nil {
+			log.Errorln("Error forwarding reported metric", m.Name, "for", name, "in namespace", namespace+":", err)
+		}
 	}
 }
 
@@ -1118,8 +1048,12 @@ func (k *Kuberhealthy) storeCheckState(checkName string, checkNamespace string,
 		return err
 	}
 
-	// put the status on the CRD from the check
-	err = setCheckStateResource(checkName, checkNamespace, details)
+	// make sure the stored severity always agrees with the OK state
+	details.Severity = details.GetSeverity()
+
+	// put the status on the CRD from the check.  the state it replaced is kept so that a transition from it can be
+	// notified once the new state is stored.
+	previous, err := setCheckStateResource(checkName, checkNamespace, details)
 
 	//TODO: Make this retry of updating custom resources repeatable
 	//
@@ -1144,27 +1078,42 @@ func (k *Kuberhealthy) storeCheckState(checkName string, checkNamespace string,
 		delay = delay + delay
 
 		// try setting the check state again
-		err = setCheckStateResource(checkName, checkNamespace, details)
+		previous, err = setCheckStateResource(checkName, checkNamespace, details)
 
 		// count how many times we've retried
 		tries++
 	}
+	if err != nil {
+		return err
+	}
 
-	return err
+	k.notifyTransition(checkName, checkNamespace, previous, details)
+	return nil
 }
 
 // StartWebServer starts a JSON status web server at the specified listener.
 func (k *Kuberhealthy) StartWebServer() {
 	log.Infoln("Configuring web server")
-	http.HandleFunc("/metrics", func(w http.ResponseWriter, r *http.Request) {
+	metricsHandler := func(w http.ResponseWriter, r *http.Request) {
 		err := k.prometheusMetricsHandler(w, r)
 		if err != nil {
 			log.Errorln(err)
 		}
-	})
+	}
+	http.HandleFunc("/metrics", metricsHandler)
 
-	// Accept status reports coming from external checker pods
+	// Serve the status of individual checks and jobs
+	k.registerAPIHandlers()
+
+	// Accept status reports coming from external checker pods.  TLS settings are only applied at startup, so whether
+	// client certificates are required is taken from the config the web server was started with.
+	requireClientCert := cfg.TLS.RequireClientCert
 	http.HandleFunc("/externalCheckStatus", func(w http.ResponseWriter, r *http.Request) {
+		if requireClientCert && !hasVerifiedClientCert(r) {
+			w.WriteHeader(http.StatusUnauthorized)
+			log.Warningln("externalCheckStatus endpoint rejected a report from", r.RemoteAddr, "without a verified client certificate")
+			return
+		}
 		err := k.externalCheckReportHandler(w, r)
 		if err != nil {
 			log.Errorln("externalCheckStatus endpoint error:", err)
@@ -1179,10 +1128,43 @@ func (k *Kuberhealthy) StartWebServer() {
 		}
 	})
 
+	// serve metrics on their own plain HTTP listener when configured, so they can be scraped without TLS
+	if len(cfg.MetricsListenAddress) > 0 {
+		metricsMux := http.NewServeMux()
+		metricsMux.HandleFunc("/metrics", metricsHandler)
+		go func() {
+			for {
+				log.Infoln("Starting metrics web services on port", cfg.MetricsListenAddress)
+				err := http.ListenAndServe(cfg.MetricsListenAddress, metricsMux)
+				if err != nil {
+					log.Errorln("Metrics web server ERROR:", err)
+				}
+				time.Sleep(time.Second / 2)
+			}
+		}()
+	}
+
+	// load the web server certificates and keep them up to date when TLS is enabled
+	var tlsConfig *tls.Config
+	if cfg.TLS.enabled() {
+		reloader, err := newCertReloader(context.Background(), cfg.TLS)
+		if err != nil {
+			log.Fatalln("Error configuring web server TLS:", err)
+		}
+		tlsConfig = reloader.tlsConfig()
+	}
+
 	// start web server any time it exits
 	for {
-		log.Infoln("Starting web services on port", k.ListenAddr)
-		err := http.ListenAndServe(k.ListenAddr, nil)
+		var err error
+		if tlsConfig != nil {
+			log.Infoln("Starting TLS web services on port", k.ListenAddr)
+			server := &http.Server{Addr: k.ListenAddr, TLSConfig: tlsConfig}
+			err = server.ListenAndServeTLS("", "")
+		} else {
+			log.Infoln("Starting web services on port", k.ListenAddr)
+			err = http.ListenAndServe(k.ListenAddr, nil)
+		}
 		if err != nil {
 			log.Errorln("Web server ERROR:", err)
 		}
@@ -1195,6 +1177,8 @@ type PodReportInfo struct {
 	Name      string
 	UUID      string
 	Namespace string
+	RunKey    string   // the key the pod was given to sign its report with
+	Images    []string // the images of the containers of the pod
 }
 
 // validateExternalRequest calls the Kubernetes API to fetch details about a pod using a selector string.
@@ -1229,6 +1213,7 @@ func (k *Kuberhealthy) validateExternalRequest(ctx context.Context, selector str
 	// we found our pod, lets return all its env vars from all its containers
 	for _, container := range pod.Spec.Containers {
 		envVars = append(envVars, container.Env...)
+		reportInfo.Images = append(reportInfo.Images, container.Image)
 	}
 
 	log.Debugln("Env vars found on pod with selector", selector, envVars)
@@ -1243,6 +1228,9 @@ func (k *Kuberhealthy) validateExternalRequest(ctx context.Context, selector str
 			podUUID = e.Value
 			foundUUID = true
 		}
+		if e.Name == external.KHRunKey {
+			reportInfo.RunKey = e.Value
+		}
 	}
 
 	// verify that we found the UUID
@@ -1344,6 +1332,47 @@ func (k *Kuberhealthy) fetchPodBySelector(ctx context.Context, selector string)
 	return podList.Items[0], nil
 }
 
+// verifyReportSignature checks the signature of a check report against the run key found on the reporting pod.
+// Unsigned reports are only accepted when allowUnsigned is set.  A signed report from a pod without a run key can not
+// be verified and is rejected.
+func verifyReportSignature(runKey string, signature string, body []byte, allowUnsigned bool) error {
+	if len(signature) == 0 {
+		if !allowUnsigned {
+			return errors.New("report was not signed with the " + external.ReportSignatureHeader + " header")
+		}
+		log.Debugln("Accepting unsigned check report")
+		return nil
+	}
+	if len(runKey) == 0 {
+		return errors.New("reporting pod has no " + external.KHRunKey + " environment variable to verify the report signature with")
+	}
+	if !external.VerifyReportSignature(runKey, body, signature) {
+		return errors.New("report signature does not match the run key of the reporting pod")
+	}
+	return nil
+}
+
+// allowsUnsignedReport indicates if an unsigned report is accepted from the supplied pod.  Pods that were given a run
+// key must sign their report, unless they run one of the configured unsignedReportImages that were built with older
+// clients.  Pods without a run key were started before reports were signed and may report unsigned, unless signed
+// reports are required.
+func allowsUnsignedReport(podReport PodReportInfo, requireSigned bool, unsignedImages []string) bool {
+	if requireSigned {
+		return false
+	}
+	if len(podReport.RunKey) == 0 {
+		return true
+	}
+	for _, image := range podReport.Images {
+		for _, unsignedImage := range unsignedImages {
+			if image == unsignedImage {
+				return true
+			}
+		}
+	}
+	return false
+}
+
 func (k *Kuberhealthy) externalCheckReportHandlerLog(s ...interface{}) {
 	log.Infoln(s...)
 }
@@ -1428,6 +1457,15 @@ func (k *Kuberhealthy) externalCheckReportHandler(w http.ResponseWriter, r *http
 	}
 	log.Debugln("Check report body:", string(b))
 
+	// verify that the report was signed by the pod with the key of its run
+	allowUnsigned := allowsUnsignedReport(podReport, cfg.RequireSignedReports, cfg.UnsignedReportImages)
+	err = verifyReportSignature(podReport.RunKey, r.Header.Get(external.ReportSignatureHeader), b, allowUnsigned)
+	if err != nil {
+		w.WriteHeader(http.StatusUnauthorized)
+		k.externalCheckReportHandlerLog(requestID, "Failed to verify check report signature:", err)
+		return nil
+	}
+
 	// decode the bytes into a status struct as used by the client
 	state := status.Report{}
 	err = json.Unmarshal(b, &state)
@@ -1454,6 +1492,27 @@ func (k *Kuberhealthy) externalCheckReportHandler(w http.ResponseWriter, r *http
 		}
 	}
 
+	// ensure that the severity reported is valid and agrees with the OK state
+	switch state.Severity {
+	case "":
+	case status.SeverityOK, status.SeverityWarning:
+		if !state.OK {
+			w.WriteHeader(http.StatusBadRequest)
+			k.externalCheckReportHandlerLog(requestID, "Client attempted to report OK false with severity", state.Severity)
+			return nil
+		}
+	case status.SeverityFailing:
+		if state.OK {
+			w.WriteHeader(http.StatusBadRequest)
+			k.externalCheckReportHandlerLog(requestID, "Client attempted to report OK true with severity", state.Severity)
+			return nil
+		}
+	default:
+		w.WriteHeader(http.StatusBadRequest)
+		k.externalCheckReportHandlerLog(requestID, "Client attempted to report an unknown severity:", state.Severity)
+		return nil
+	}
+
 	checkRunDuration := time.Duration(0).String()
 	khWorkload := determineKHWorkload(podReport.Name, podReport.Namespace)
 
@@ -1473,6 +1532,47 @@ func (k *Kuberhealthy) externalCheckReportHandler(w http.ResponseWriter, r *http
 	details.RunDuration = checkRunDuration
 	details.Namespace = podReport.Namespace
 	details.CurrentUUID = podReport.UUID
+	details.Warnings = state.Warnings
+	details.Severity = khstatev1.Severity(state.Severity)
+	details.LastRunResult = &khstatev1.RunResult{
+		OK:       state.OK,
+		Errors:   state.Errors,
+		Severity: khstatev1.Severity(state.Severity),
+	}
+
+	// the state shown for a check only changes once the master has applied the check's failure and success
+	// thresholds at the end of the run, so until then we keep showing the state stored in the khstate of the check
+	if khWorkload == khstatev1.KHCheck {
+		currentDetails, exists, err := getStoredState(podReport.Name, podReport.Namespace)
+		if err != nil {
+			w.WriteHeader(http.StatusInternalServerError)
+			k.externalCheckReportHandlerLog(requestID, "Failed to get the current state of the check:", err)
+			return fmt.Errorf("failed to get the current state of check %s: %w", podReport.Name, err)
+		}
+		details.OK = true
+		details.Errors = []string{}
+		details.Severity = khstatev1.SeverityOK
+		if exists && len(currentDetails.AuthoritativePod) > 0 {
+			details.OK = currentDetails.OK
+			details.Errors = currentDetails.Errors
+			details.Severity = currentDetails.Severity
+			details.ConsecutiveFailures = currentDetails.ConsecutiveFailures
+			details.ConsecutiveSuccesses = currentDetails.ConsecutiveSuccesses
+			details.Suspended = currentDetails.Suspended
+		}
+	}
+	for _, m := range state.Metrics {
+		if len(m.Name) == 0 {
+			k.externalCheckReportHandlerLog(requestID, "Dropping reported metric without a name")
+			continue
+		}
+		details.Metrics = append(details.Metrics, khstatev1.Metric{
+			Name:   m.Name,
+			Value:  m.Value,
+			Unit:   m.Unit,
+			Labels: m.Labels,
+		})
+	}
 
 	// since the check is validated, we can proceed to update the status now
 	k.externalCheckReportHandlerLog(requestID, "Setting check with name", podReport.Name, "in namespace", podReport.Namespace, "to 'OK' state:", details.OK, "uuid", details.CurrentUUID, details.GetKHWorkload())
@@ -1505,15 +1605,32 @@ func (k *Kuberhealthy) writeHealthCheckError(w http.ResponseWriter, r *http.Requ
 
 func (k *Kuberhealthy) prometheusMetricsHandler(w http.ResponseWriter, r *http.Request) error {
 	log.Infoln("Client connected to prometheus metrics endpoint from", r.RemoteAddr, r.UserAgent())
-	state := k.getCurrentState([]string{})
 
-	m := metrics.GenerateMetrics(state, cfg.PromMetricsConfig)
-	// write summarized health check results back to caller
-	_, err := w.Write([]byte(m))
-	if err != nil {
-		log.Warningln("Error writing health check results to caller:", err)
+	// when metrics authentication is enabled, only export checks from namespaces the caller can get khstates in so
+	// that their error labels are not exposed to anyone that can reach the service
+	var namespaces []string
+	if cfg.MetricsAuth {
+		var statusCode int
+		var err error
+		namespaces, statusCode, err = k.authorizeNamespaces(r, nil, k.stateNamespaces)
+		if err != nil {
+			log.Warningln("Metrics request from", r.RemoteAddr, "was not authorized:", err)
+			w.WriteHeader(statusCode)
+			if statusCode == http.StatusInternalServerError {
+				return err
+			}
+			return nil
+		}
 	}
-	return err
+	state := k.getCurrentState(namespaces)
+
+	// write summarized health check results along with the health of the run queue and metric forwarders back to
+	// caller in the format it asked for.  The state is collected from a registry of its own because it differs
+	// between callers when metrics authentication is enabled.
+	stateRegistry := prometheus.NewRegistry()
+	stateRegistry.MustRegister(metrics.NewStateCollector(state, cfg.PromMetricsConfig))
+	metrics.Handler(prometheus.Gatherers{stateRegistry, k.metricsRegistry}).ServeHTTP(w, r)
+	return nil
 }
 
 // healthCheckHandler returns the current status of checks loaded into Kuberhealthy
@@ -1545,9 +1662,37 @@ func (k *Kuberhealthy) healthCheckHandler(w http.ResponseWriter, r *http.Request
 		}
 	}
 
+	// when status page authentication is enabled, only show checks from namespaces the caller can get khstates in
+	if cfg.StatusPageAuth {
+		var statusCode int
+		namespaces, statusCode, err = k.authorizeNamespaces(r, namespaces, k.stateNamespaces)
+		if err != nil {
+			log.Warningln("Status page request from", r.RemoteAddr, "was not authorized:", err)
+			w.WriteHeader(statusCode)
+			if statusCode == http.StatusInternalServerError {
+				return err
+			}
+			return nil
+		}
+	}
+
 	// fetch the current status from our khstate resources
 	state := k.getCurrentState(namespaces)
 
+	// browsers are sent an HTML dashboard that always includes the run history of each check
+	if health.WantsHTML(r) {
+		err = state.WriteHTMLStatusResponse(w)
+		if err != nil {
+			log.Warningln("Error writing status dashboard to caller:", err)
+		}
+		return err
+	}
+
+	// the run history of each check is only shown when requested with ?history=true
+	if values.Get("history") != "true" {
+		state.RemoveHistory()
+	}
+
 	// write summarized health check results back to caller
 	err = state.WriteHTTPStatusResponse(w)
 	if err != nil {
@@ -1557,24 +1702,19 @@ func (k *Kuberhealthy) healthCheckHandler(w http.ResponseWriter, r *http.Request
 }
 
 // getCurrentState fetches the current state of all checks from requested namespaces
-// their CRD objects and returns the summary as a health.State. Without a requested namespace,
-// this will return the state of ALL found checks.
+// their CRD objects and returns the summary as a health.State. When namespaces is nil,
+// this will return the state of ALL found checks, while an empty list returns none of them.
 // Failures to fetch CRD state return an error.
 func (k *Kuberhealthy) getCurrentState(namespaces []string) health.State {
 
-	currentMaster, err := masterCalculation.CalculateMaster(kubernetesClient)
-	if err != nil {
-		log.Errorln("Failed to calculate master:", err)
-	}
-
 	var currentState health.State
-	if len(namespaces) != 0 {
+	if namespaces != nil {
 		currentState = k.getCurrentStatusForNamespaces(namespaces)
 	} else {
 		currentState = k.stateReflector.CurrentStatus()
 	}
 
-	currentState.CurrentMaster = currentMaster
+	currentState.CurrentMaster = masterElector.CurrentMaster()
 	if len(cfg.StateMetadata) != 0 {
 		currentState.Metadata = cfg.StateMetadata
 	}
@@ -1582,6 +1722,52 @@ func (k *Kuberhealthy) getCurrentState(namespaces []string) health.State {
 	return currentState
 }
 
+// authorizeNamespaces authenticates the caller of the status page, check API or metrics and returns the namespaces
+// out of the requested ones that the caller can get khstates in.  When no namespaces were requested, the namespaces
+// returned by available are considered, and nil is returned when the caller can get khstates in every namespace.  The
+// returned status code is the one to reply with when an error is returned.
+func (k *Kuberhealthy) authorizeNamespaces(r *http.Request, requested []string, available func() []string) ([]string, int, error) {
+	user, err := authenticateRequest(r.Context(), kubernetesClient, r)
+	if err != nil {
+		return nil, http.StatusUnauthorized, err
+	}
+
+	candidates := requested
+	if len(candidates) == 0 {
+		candidates = available()
+		// nothing exists yet, such as right after startup, so there is nothing to show rather than nothing allowed
+		if len(candidates) == 0 {
+			return []string{}, http.StatusOK, nil
+		}
+	}
+
+	namespaces, all, err := authorizedNamespaces(r.Context(), kubernetesClient, user, candidates)
+	if err != nil {
+		return nil, http.StatusInternalServerError, err
+	}
+	if all {
+		return requested, http.StatusOK, nil
+	}
+	if len(namespaces) == 0 {
+		return nil, http.StatusForbidden, errors.New("user " + user.Username + " is not allowed to get khstates in any of the requested namespaces")
+	}
+	return namespaces, http.StatusOK, nil
+}
+
+// stateNamespaces returns every namespace that has a khstate
+func (k *Kuberhealthy) stateNamespaces() []string {
+	states := k.stateReflector.CurrentStatus()
+	var namespaces []string
+	for _, details := range []map[string]khstatev1.WorkloadDetails{states.CheckDetails, states.JobDetails} {
+		for _, d := range details {
+			if !containsString(d.Namespace, namespaces) {
+				namespaces = append(namespaces, d.Namespace)
+			}
+		}
+	}
+	return namespaces
+}
+
 // getCurrentState fetches the current state of all checks from the requested namespaces
 // their CRD objects and returns the summary as a health.State.
 // Failures to fetch CRD state return an error.
@@ -1590,7 +1776,9 @@ func (k *Kuberhealthy) getCurrentStatusForNamespaces(namespaces []string) health
 	states := k.stateReflector.CurrentStatus()
 	statesForNamespaces := states
 	statesForNamespaces.Errors = []string{}
+	statesForNamespaces.Warnings = []string{}
 	statesForNamespaces.OK = true
+	statesForNamespaces.Severity = khstatev1.SeverityOK
 	statesForNamespaces.CheckDetails = make(map[string]khstatev1.WorkloadDetails)
 	statesForNamespaces.JobDetails = make(map[string]khstatev1.WorkloadDetails)
 	if len(namespaces) != 0 {
@@ -1619,15 +1807,20 @@ func validateCurrentStatusForNamespaces(details map[string]khstatev1.WorkloadDet
 			continue
 		}
 
-		// parse check status from CRD and add it to the global status of errors. Skip blank errors
-		for _, e := range checkState.Errors {
-			if len(strings.TrimSpace(e)) == 0 {
-				log.Warningln("Skipped an error that was blank when adding check details to current state.")
-				continue
+		// suspended and blocked checks are shown in the details, but do not change the global status
+		if !checkState.Suspended && !checkState.Blocked {
+			// parse check status from CRD and add it to the global status of errors. Skip blank errors
+			for _, e := range checkState.Errors {
+				if len(strings.TrimSpace(e)) == 0 {
+					log.Warningln("Skipped an error that was blank when adding check details to current state.")
+					continue
+				}
+				statesForNamespaces.AddError(e)
+				log.Debugln("Status page: Setting global OK state to false due to check details not being OK")
+				statesForNamespaces.OK = false
 			}
-			statesForNamespaces.AddError(e)
-			log.Debugln("Status page: Setting global OK state to false due to check details not being OK")
-			statesForNamespaces.OK = false
+			statesForNamespaces.AddWarning(checkState.Warnings...)
+			statesForNamespaces.AddSeverity(checkState.GetSeverity())
 		}
 
 		// update details struct
@@ -1644,7 +1837,7 @@ func validateCurrentStatusForNamespaces(details map[string]khstatev1.WorkloadDet
 
 // getCheck returns a Kuberhealthy check object from its name, returns an error otherwise
 func (k *Kuberhealthy) getCheck(name string, namespace string) (*external.Checker, error) {
-	for _, c := range k.Checks {
+	for _, c := range k.loadedChecks() {
 		if c.Name() == name && c.CheckNamespace() == namespace {
 			return c, nil
 		}
@@ -1665,21 +1858,6 @@ func (k *Kuberhealthy) getJob(name string, namespace string) (*external.Checker,
 	return k.configureJob(j), nil
 }
 
-// configureChecks removes all checks set in Kuberhealthy and reloads them
-// based on the configuration options
-func (k *Kuberhealthy) configureChecks(ctx context.Context) {
-	log.Infoln("control: Loading check configuration...")
-
-	// wipe all existing checks before we configure
-	k.Checks = []*external.Checker{}
-
-	// check external check configurations
-	err := k.addExternalChecks(ctx)
-	if err != nil {
-		log.Errorln("control: ERROR loading external checks:", err)
-	}
-}
-
 // isUUIDWhitelistedForCheck determines if the supplied uuid is whitelisted for the
 // check with the supplied name.  Only one UUID can be whitelisted at a time.
 // Operations are not atomic.  Whitelisting prevents expired or invalidated pods from
@@ -1699,15 +1877,13 @@ func (k *Kuberhealthy) isUUIDWhitelistedForCheck(checkName string, checkNamespac
 	return false, nil
 }
 
-// configureInfluxForwarding sets up initial influxdb metric sending
-func (k *Kuberhealthy) configureInfluxForwarding() {
-
-	// configure influxdb
-	metricClient, err := configureInflux()
+// configureOTLPTracing sets up sending khcheck run traces to an OpenTelemetry collector
+func (k *Kuberhealthy) configureOTLPTracing() {
+	otlpClient, err := metrics.NewOTLPClient(cfg.OTLP)
 	if err != nil {
-		log.Fatalln("Error setting up influx client:", err)
+		log.Fatalln("Error setting up OTLP client:", err)
 	}
-	k.MetricForwarder = metricClient
+	k.Tracer = tracing.NewTracer(otlpClient)
 }
 
 // func listUnstructuredKHChecks(ctx context.Context, namespace string) (*unstructured.UnstructuredList, error) {
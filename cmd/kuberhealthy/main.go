@@ -22,6 +22,7 @@ import (
 	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/kubeClient"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/masterCalculation"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/sharding"
 )
 
 // status represents the current Kuberhealthy OK:Error state
@@ -29,9 +30,14 @@ var cfg *Config
 var configPath = "/etc/config/kuberhealthy.yaml"
 
 var podNamespace = os.Getenv("POD_NAMESPACE")
-var isMaster bool                  // indicates this instance is the master and should be running checks
-var upcomingMasterState bool       // the upcoming master state on next interval
-var lastMasterChangeTime time.Time // indicates the last time a master change was seen
+
+// masterElector determines if this instance holds the master lease and should be running checks
+var masterElector *masterCalculation.Elector
+
+// shardMembership determines which checks this instance runs when checks are sharded across replicas.  nil when
+// sharding is disabled and the master runs every check.
+var shardMembership *sharding.Membership
+
 // Interval for how often check pods should get reaped. Default is 30s.
 var checkReaperRunInterval = os.Getenv("CHECK_REAPER_RUN_INTERVAL")
 
@@ -49,6 +55,9 @@ const DefaultRunInterval = time.Minute * 10
 // DefaultTimeout is the default timeout for external checks
 var DefaultTimeout = time.Minute * 5
 
+// DefaultRunHistoryLength is the default number of past runs kept on each khstate
+const DefaultRunHistoryLength = 20
+
 // KHCheckNameAnnotationKey is the key used in the annotation that holds the check's short name
 const KHCheckNameAnnotationKey = "comcast.github.io/check-name"
 
@@ -71,6 +80,9 @@ const checkCRDGroup = "comcast.github.io"
 const checkCRDVersion = "v1"
 const checkCRDResource = "khchecks"
 
+// constants for using the kuberhealthy job CRD
+const jobCRDResource = "khjobs"
+
 // kubernetesClient is the global kubernetes client
 var kubernetesClient *kubernetes.Clientset
 
@@ -113,9 +125,12 @@ func listenForInterrupts(k *Kuberhealthy) {
 	<-sigChan
 	log.Infoln("shutdown: Shutting down due to sigChan signal...")
 
-	// wait for check to fully shutdown before exiting
+	// wait for check to fully shutdown before exiting.  The shutdown stops waiting on notifiers a little before the
+	// termination grace period ends so that it can still complete gracefully.
+	shutdownCtx, shutdownCtxCancel := context.WithTimeout(context.Background(), terminationGracePeriod-(time.Second*10))
+	defer shutdownCtxCancel()
 	doneChan := make(chan struct{})
-	go k.Shutdown(doneChan)
+	go k.Shutdown(shutdownCtx, doneChan)
 
 	// wait for checks to be done shutting down before exiting
 	select {
@@ -243,6 +258,13 @@ func setUp() error {
 		log.SetLevel(log.DebugLevel)
 	}
 
+	// refuse to start with settings that can not be used together
+	err = cfg.validate()
+	if err != nil {
+		err := fmt.Errorf("invalid configuration: %s", err)
+		return err
+	}
+
 	// Handle force master mode
 	if cfg.EnableForceMaster {
 		log.Infoln("Enabling forced master mode")
@@ -266,5 +288,29 @@ func setUp() error {
 		return err
 	}
 
+	// setup lease based master election in the namespace kuberhealthy runs in
+	masterElector, err = masterCalculation.NewElector(kubernetesClient, podNamespace, podHostname, cfg.LeaderElection)
+	if err != nil {
+		err := fmt.Errorf("failed to configure master election: %s", err)
+		return err
+	}
+
+	// setup sharding of checks across replicas when enabled
+	if cfg.Sharding.Enabled {
+		shardMembership, err = sharding.NewMembership(kubernetesClient, podNamespace, podHostname, cfg.Sharding)
+		if err != nil {
+			err := fmt.Errorf("failed to configure sharding: %s", err)
+			return err
+		}
+	}
+
+	// setup the notifiers of state transitions so that invalid notifiers are found at startup
+	dispatcher, err := newStateNotifier(cfg)
+	if err != nil {
+		err := fmt.Errorf("failed to configure notifiers: %s", err)
+		return err
+	}
+	setStateNotifier(context.Background(), dispatcher)
+
 	return nil
 }
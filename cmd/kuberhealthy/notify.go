@@ -0,0 +1,100 @@
+package main
+
+import (
+	"context"
+	"sync"
+	"time"
+
+	log "github.com/sirupsen/logrus"
+
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/notifiers"
+)
+
+// stateNotifier sends the state transitions of checks and jobs to the configured notifiers.  nil when no notifiers
+// are configured.  guarded by stateNotifierMu
+var stateNotifier *notifiers.Dispatcher
+
+// stateNotifierMu guards stateNotifier
+var stateNotifierMu sync.RWMutex
+
+// newStateNotifier creates a dispatcher for the notifiers in the config.  Returns nil when no notifiers are configured.
+func newStateNotifier(c *Config) (*notifiers.Dispatcher, error) {
+	if len(c.Notifiers) == 0 {
+		return nil, nil
+	}
+	return notifiers.NewDispatcher(c.Notifiers, notifiers.DefaultQueueSize)
+}
+
+// reloadNotifiers replaces the notifiers with the ones in the current config.  The current notifiers are kept if
+// the new ones can not be set up.
+func reloadNotifiers() {
+	dispatcher, err := newStateNotifier(cfg)
+	if err != nil {
+		log.Errorln("Error reloading notifiers. Keeping the current notifiers:", err)
+		return
+	}
+	setStateNotifier(context.Background(), dispatcher)
+}
+
+// setStateNotifier replaces the state notifier and closes the previous one, which sends its queued notifications
+// until ctx is done
+func setStateNotifier(ctx context.Context, dispatcher *notifiers.Dispatcher) {
+	stateNotifierMu.Lock()
+	previous := stateNotifier
+	stateNotifier = dispatcher
+	stateNotifierMu.Unlock()
+
+	if previous != nil {
+		previous.Close(ctx)
+	}
+}
+
+// notifyTransition sends the state change of a check or job to the configured notifiers when its severity changed.
+// previous is the khstate that was replaced by details.
+func (k *Kuberhealthy) notifyTransition(name string, namespace string, previous khstatev1.WorkloadDetails, details khstatev1.WorkloadDetails) {
+	stateNotifierMu.RLock()
+	defer stateNotifierMu.RUnlock()
+	if stateNotifier == nil {
+		return
+	}
+
+	transition, changed := stateTransition(name, namespace, previous, details)
+	if !changed {
+		return
+	}
+	transition.Metadata = cfg.StateMetadata
+
+	log.Infoln("Notifying notifiers that", transition.Summary())
+	stateNotifier.Send(transition)
+}
+
+// stateTransition builds the transition from the previous to the new state of a check or job and reports if its
+// severity changed.  Checks and jobs that have never been written by kuberhealthy are treated as previously OK, so
+// that a new check that fails right away is notified while a new passing check is not.
+func stateTransition(name string, namespace string, previous khstatev1.WorkloadDetails, details khstatev1.WorkloadDetails) (notifiers.Transition, bool) {
+	previousSeverity := khstatev1.SeverityOK
+	if len(previous.AuthoritativePod) != 0 {
+		previousSeverity = previous.GetSeverity()
+	}
+	severity := details.GetSeverity()
+
+	transition := notifiers.Transition{
+		Name:             name,
+		Namespace:        namespace,
+		Workload:         string(details.GetKHWorkload()),
+		PreviousSeverity: previousSeverity,
+		Severity:         severity,
+		OK:               details.OK,
+		Errors:           details.Errors,
+		Warnings:         details.Warnings,
+		Node:             details.Node,
+		RunUUID:          details.CurrentUUID,
+		Time:             time.Now(),
+	}
+	if details.LastRun != nil {
+		transition.Time = details.LastRun.Time
+	}
+
+	return transition, previousSeverity != severity
+}
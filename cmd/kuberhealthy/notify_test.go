@@ -0,0 +1,53 @@
+package main
+
+import (
+	"testing"
+
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
+)
+
+// TestStateTransition tests that only changes in severity are considered transitions
+func TestStateTransition(t *testing.T) {
+	previous := khstatev1.NewWorkloadDetails(khstatev1.KHCheck)
+	previous.OK = true
+	previous.AuthoritativePod = "kuberhealthy-abc"
+
+	details := khstatev1.NewWorkloadDetails(khstatev1.KHCheck)
+	details.OK = true
+	details.Severity = khstatev1.SeverityOK
+
+	// a new check that passes is not a transition
+	if _, changed := stateTransition("dns", "kuberhealthy", khstatev1.WorkloadDetails{}, details); changed {
+		t.Fatal("expected a new passing check to not be a transition")
+	}
+
+	// a passing check that keeps passing is not a transition
+	if _, changed := stateTransition("dns", "kuberhealthy", previous, details); changed {
+		t.Fatal("expected a check that keeps passing to not be a transition")
+	}
+
+	// a passing check that starts failing is a transition
+	details.OK = false
+	details.Errors = []string{"dns lookup failed"}
+	transition, changed := stateTransition("dns", "kuberhealthy", previous, details)
+	if !changed {
+		t.Fatal("expected a check that starts failing to be a transition")
+	}
+	if transition.PreviousSeverity != khstatev1.SeverityOK || transition.Severity != khstatev1.SeverityFailing || transition.Workload != "KHCheck" {
+		t.Fatalf("unexpected transition: %+v", transition)
+	}
+
+	// a new check that fails right away is a transition
+	if _, changed := stateTransition("dns", "kuberhealthy", khstatev1.WorkloadDetails{}, details); !changed {
+		t.Fatal("expected a new failing check to be a transition")
+	}
+
+	// a failing check that becomes degraded is a transition
+	previous.OK = false
+	details.OK = true
+	details.Severity = khstatev1.SeverityWarning
+	transition, changed = stateTransition("dns", "kuberhealthy", previous, details)
+	if !changed || transition.PreviousSeverity != khstatev1.SeverityFailing || transition.Severity != khstatev1.SeverityWarning {
+		t.Fatalf("expected a failing check that becomes degraded to be a transition, got %+v", transition)
+	}
+}
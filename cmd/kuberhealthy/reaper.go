@@ -166,6 +166,10 @@ func (k *KubernetesAPI) listCompletedCheckerPods(ctx context.Context, namespace
 	log.Infoln("checkReaper: Found:", len(pods.Items), "checker pods")
 
 	for _, p := range pods.Items {
+		// pods of checks that run as a Job are garbage collected along with their Job by the TTL controller
+		if podOwnedByJob(p) {
+			continue
+		}
 		if p.Status.Phase == v1.PodSucceeded || p.Status.Phase == v1.PodFailed {
 			ReapCheckerPods[p.Name] = p
 		}
@@ -174,6 +178,12 @@ func (k *KubernetesAPI) listCompletedCheckerPods(ctx context.Context, namespace
 	return ReapCheckerPods, err
 }
 
+// podOwnedByJob indicates if a pod is controlled by a Job
+func podOwnedByJob(pod v1.Pod) bool {
+	owner := metav1.GetControllerOf(&pod)
+	return owner != nil && owner.Kind == "Job"
+}
+
 // deleteFilteredCheckerPods goes through map of all checker pods and deletes older checker pods
 func (k *KubernetesAPI) deleteFilteredCheckerPods(ctx context.Context, client *kubernetes.Clientset, reapCheckerPods map[string]v1.Pod) error {
 
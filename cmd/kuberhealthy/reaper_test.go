@@ -74,6 +74,8 @@ func TestParseDurationOrUseDefault(t *testing.T) {
 
 // TestListCheckerPods ensures that only completed (Successful or Failed) kuberhealthy checker pods are listed / returned
 func TestListCompletedCheckerPods(t *testing.T) {
+	isController := true
+
 	validKHPod := v1.Pod{
 		ObjectMeta: metav1.ObjectMeta{
 			Name:      "valid-kh-pod",
@@ -120,11 +122,33 @@ func TestListCompletedCheckerPods(t *testing.T) {
 		},
 	}
 
+	jobKHPod := v1.Pod{
+		ObjectMeta: metav1.ObjectMeta{
+			Name:      "job-kh-pod",
+			Namespace: "foo",
+			Labels: map[string]string{
+				"kuberhealthy-check-name": "job-kh-pod",
+			},
+			OwnerReferences: []metav1.OwnerReference{
+				{
+					APIVersion: "batch/v1",
+					Kind:       "Job",
+					Name:       "job-kh-pod",
+					Controller: &isController,
+				},
+			},
+		},
+		Status: v1.PodStatus{
+			Phase: v1.PodPhase("Succeeded"),
+		},
+	}
+
 	khCheckerPods := make(map[string]v1.Pod)
 	khCheckerPods[validKHPod.Name] = validKHPod
 	khCheckerPods[anotherValidKHPod.Name] = anotherValidKHPod
 	khCheckerPods[nonKHPod.Name] = nonKHPod
 	khCheckerPods[runningKHPod.Name] = runningKHPod
+	khCheckerPods[jobKHPod.Name] = jobKHPod
 
 	api := KubernetesAPI{
 		Client: fake.NewSimpleClientset(),
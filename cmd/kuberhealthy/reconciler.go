@@ -0,0 +1,221 @@
+package main
+
+import (
+	"context"
+	"reflect"
+	"sort"
+	"time"
+
+	log "github.com/sirupsen/logrus"
+	"k8s.io/apimachinery/pkg/fields"
+	"k8s.io/client-go/tools/cache"
+
+	khcheckv1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khcheck/v1"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external"
+)
+
+// checkResyncPeriod is how often the khcheck informer re-delivers every khcheck to the reconciler
+const checkResyncPeriod = time.Minute * 5
+
+// checkRunner is a running check and the khcheck spec it was configured from
+type checkRunner struct {
+	checker *external.Checker
+	spec    khcheckv1.CheckConfig
+	cancel  context.CancelFunc // stops the run loop of the check
+	done    chan struct{}      // closed when the run loop of the check returns
+	stopped chan struct{}      // closed when the check has been shut down and its run loop has returned
+}
+
+// newCheckInformer creates an informer that caches khcheck resources and reconciles the checks of every khcheck
+// that is added, changed or removed
+func (k *Kuberhealthy) newCheckInformer() (cache.Store, cache.Controller) {
+	khCheckListWatch := cache.NewListWatchFromClient(khCheckClient.RESTClient(), checkCRDResource, k.TargetNamespace, fields.Everything())
+	return cache.NewInformer(khCheckListWatch, &khcheckv1.KuberhealthyCheck{}, checkResyncPeriod, cache.ResourceEventHandlerFuncs{
+		AddFunc: func(obj interface{}) {
+			k.khCheckChanged(obj)
+		},
+		UpdateFunc: func(oldObj interface{}, newObj interface{}) {
+			k.khCheckChanged(newObj)
+		},
+		DeleteFunc: func(obj interface{}) {
+			k.khCheckChanged(obj)
+		},
+	})
+}
+
+// khCheckChanged reconciles the check of a khcheck delivered by the informer
+func (k *Kuberhealthy) khCheckChanged(obj interface{}) {
+	key, err := cache.DeletionHandlingMetaNamespaceKeyFunc(obj)
+	if err != nil {
+		log.Errorln("control: error getting the key of a changed khcheck:", err)
+		return
+	}
+	log.Debugln("control: witnessed a change to khcheck", key)
+	k.reconcileCheck(key)
+}
+
+// reconcileChecks reconciles the check of every khcheck in the informer cache
+func (k *Kuberhealthy) reconcileChecks() {
+	for _, key := range k.checkStore.ListKeys() {
+		k.reconcileCheck(key)
+	}
+}
+
+// reconcileCheck brings the running check of the khcheck with the supplied namespace/name key in line with the
+// khcheck in the informer cache.  New khchecks are started, removed khchecks are stopped and khchecks with a changed
+// spec are restarted with the new spec.  Other checks are left alone so that their in-flight runs are not disturbed.
+// Nothing is started while checks are stopped, such as when this instance is not the master.  When checks are
+// sharded, only the checks owned by this replica are run.
+func (k *Kuberhealthy) reconcileCheck(key string) {
+	obj, exists, err := k.checkStore.GetByKey(key)
+	if err != nil {
+		log.Errorln("control: error fetching khcheck", key, "from the informer cache:", err)
+		return
+	}
+
+	k.checksMu.Lock()
+	if k.checksCtx == nil {
+		k.checksMu.Unlock()
+		return
+	}
+	runner, running := k.runners[key]
+
+	// stop the check if its khcheck was removed or is now owned by another replica
+	if !exists || (shardMembership != nil && !shardMembership.Owns(key)) {
+		if running {
+			if exists {
+				log.Infoln("control: khcheck", key, "is now owned by another replica. Stopping its check.")
+			} else {
+				log.Infoln("control: khcheck", key, "was removed. Stopping its check.")
+			}
+			delete(k.runners, key)
+			k.Checks = checksFromRunners(k.runners)
+			k.stopCheckRunner(runner)
+		}
+		k.checksMu.Unlock()
+		return
+	}
+
+	kc := *obj.(*khcheckv1.KuberhealthyCheck).DeepCopy()
+
+	// hand on demand runs to checks whose spec has not changed
+	if running && reflect.DeepEqual(runner.spec, kc.Spec) {
+		k.checksMu.Unlock()
+		k.acceptRunNowRequest(kc)
+		return
+	}
+
+	// the new configuration of a changed check waits for the old one to stop so that their runs never overlap.  a
+	// check taken over from another replica waits for that replica to notice that it no longer owns the check.
+	var previous chan struct{}
+	var delay time.Duration
+	if running {
+		log.Infoln("control: khcheck", key, "has changed. Restarting its check.")
+		k.stopCheckRunner(runner)
+		previous = runner.stopped
+	} else {
+		if shardMembership != nil {
+			delay = shardMembership.HandoffDelay(key)
+		}
+		if delay > 0 {
+			log.Infoln("control: khcheck", key, "was taken over from another replica. Starting its check in", delay.String())
+		} else {
+			log.Infoln("control: khcheck", key, "was added. Starting its check.")
+		}
+	}
+	k.runners[key] = k.startCheckRunner(k.checksCtx, k.configureCheck(kc), kc.Spec, previous, delay)
+	k.Checks = checksFromRunners(k.runners)
+	k.checksMu.Unlock()
+}
+
+// startCheckRunner starts running a check in the background.  If previous is set, the check does not start until
+// previous is closed.  The check then waits for the supplied delay before it starts.
+func (k *Kuberhealthy) startCheckRunner(ctx context.Context, c *external.Checker, spec khcheckv1.CheckConfig, previous chan struct{}, delay time.Duration) *checkRunner {
+	runCtx, cancel := context.WithCancel(ctx)
+	runner := &checkRunner{
+		checker: c,
+		spec:    spec,
+		cancel:  cancel,
+		done:    make(chan struct{}),
+		stopped: make(chan struct{}),
+	}
+
+	k.wg.Add(1)
+	go func() {
+		defer k.wg.Done()
+		defer close(runner.done)
+		if previous != nil {
+			<-previous
+		}
+		if delay > 0 {
+			select {
+			case <-runCtx.Done():
+				return
+			case <-time.After(delay):
+			}
+		}
+		k.runCheck(runCtx, c)
+	}()
+	return runner
+}
+
+// stopCheckRunner cancels a running check and shuts it down in the background.  The stopped channel of the runner is
+// closed once the check has stopped.
+func (k *Kuberhealthy) stopCheckRunner(runner *checkRunner) {
+	runner.cancel()
+	go func() {
+		c := runner.checker
+		log.Infoln("control: check", c.Name(), "stopping...")
+		err := c.Shutdown()
+		if err != nil {
+			log.Errorln("control: ERROR stopping check", c.Name(), err)
+		}
+		<-runner.done
+		close(runner.stopped)
+		log.Infoln("control: check", c.Name(), "stopped")
+	}()
+}
+
+// checksFromRunners returns the checks of the supplied runners sorted by their namespace/name key
+func checksFromRunners(runners map[string]*checkRunner) []*external.Checker {
+	keys := make([]string, 0, len(runners))
+	for key := range runners {
+		keys = append(keys, key)
+	}
+	sort.Strings(keys)
+
+	checks := make([]*external.Checker, 0, len(keys))
+	for _, key := range keys {
+		checks = append(checks, runners[key].checker)
+	}
+	return checks
+}
+
+// loadedChecks returns the checks that are currently running
+func (k *Kuberhealthy) loadedChecks() []*external.Checker {
+	k.checksMu.RLock()
+	defer k.checksMu.RUnlock()
+	return k.Checks
+}
+
+// ownsCheck indicates if this instance should run the check with the supplied namespace/name key.  When checks are
+// sharded, each check is run by the replica that owns it.  Otherwise, the master runs every check.
+func ownsCheck(key string) bool {
+	if shardMembership != nil {
+		return shardMembership.Owns(key)
+	}
+	return masterElector.IsMaster()
+}
+
+// skipUnownedRun indicates if a run of the check must be skipped because this instance does not own the check.  An on
+// demand run that is skipped is dropped like the runs of suspended checks are, so that it does not stay pending.
+func (k *Kuberhealthy) skipUnownedRun(c *external.Checker, onDemandRun bool) bool {
+	if ownsCheck(c.CheckNamespace() + "/" + c.Name()) {
+		return false
+	}
+	log.Warningln("Skipping run of check", c.Name(), "in namespace", c.CheckNamespace(), "because this pod does not own the check")
+	if onDemandRun {
+		k.skipRequestedRun(c)
+	}
+	return true
+}
@@ -0,0 +1,230 @@
+package main
+
+import (
+	"context"
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"strconv"
+	"testing"
+	"time"
+
+	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
+	"k8s.io/client-go/kubernetes/fake"
+	"k8s.io/client-go/rest"
+	"k8s.io/client-go/tools/cache"
+
+	khcheckv1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khcheck/v1"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/masterCalculation"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/sharding"
+)
+
+// TestChecksFromRunners tests that the running checks are listed in namespace/name order
+func TestChecksFromRunners(t *testing.T) {
+	runners := map[string]*checkRunner{
+		"kuberhealthy/dns":        {checker: &external.Checker{CheckName: "dns", Namespace: "kuberhealthy"}},
+		"default/http":            {checker: &external.Checker{CheckName: "http", Namespace: "default"}},
+		"kuberhealthy/deployment": {checker: &external.Checker{CheckName: "deployment", Namespace: "kuberhealthy"}},
+	}
+
+	checks := checksFromRunners(runners)
+	expected := []string{"default/http", "kuberhealthy/deployment", "kuberhealthy/dns"}
+	if len(checks) != len(expected) {
+		t.Fatalf("expected %d checks, got %d", len(expected), len(checks))
+	}
+	for i, key := range expected {
+		if checks[i] != runners[key].checker {
+			t.Fatalf("expected check %d to be %s, got %s/%s", i, key, checks[i].CheckNamespace(), checks[i].Name())
+		}
+	}
+}
+
+// TestReconcileCheckWhileStopped tests that the reconciler does not start checks while checks are stopped, such as
+// when this instance is not the master
+func TestReconcileCheckWhileStopped(t *testing.T) {
+	kh := &Kuberhealthy{
+		runners:    make(map[string]*checkRunner),
+		checkStore: cache.NewStore(cache.MetaNamespaceKeyFunc),
+	}
+	err := kh.checkStore.Add(&khcheckv1.KuberhealthyCheck{
+		ObjectMeta: metav1.ObjectMeta{Name: "dns", Namespace: "kuberhealthy"},
+	})
+	if err != nil {
+		t.Fatal("failed to add khcheck to the store:", err)
+	}
+
+	kh.reconcileCheck("kuberhealthy/dns")
+	if len(kh.runners) != 0 || len(kh.loadedChecks()) != 0 {
+		t.Fatalf("expected no checks to start while checks are stopped, found %d running", len(kh.runners))
+	}
+}
+
+// TestSkipUnownedRun tests that runs of a check this instance does not own are skipped and that a skipped on demand
+// run is dropped along with its run-now annotation
+func TestSkipUnownedRun(t *testing.T) {
+	previousElector, previousClient := masterElector, khCheckClient
+	defer func() {
+		masterElector, khCheckClient = previousElector, previousClient
+	}()
+
+	// an elector that never became master does not own any checks
+	masterElector = &masterCalculation.Elector{}
+
+	kc := khcheckv1.KuberhealthyCheck{
+		TypeMeta:   metav1.TypeMeta{APIVersion: "comcast.github.io/v1", Kind: "KuberhealthyCheck"},
+		ObjectMeta: metav1.ObjectMeta{Name: "dns", Namespace: "kuberhealthy"},
+	}
+	c := external.New(nil, kc.DeepCopy(), nil, nil, "")
+	runUUID := c.RequestRun("")
+	kc.Annotations = map[string]string{external.KHRunNowAnnotationKey: runUUID}
+
+	// serve the khcheck and record its updates
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		if r.URL.Path != "/apis/comcast.github.io/v1/namespaces/kuberhealthy/khchecks/dns" {
+			w.WriteHeader(http.StatusNotFound)
+			return
+		}
+		if r.Method == http.MethodPut {
+			kc = khcheckv1.KuberhealthyCheck{}
+			err := json.NewDecoder(r.Body).Decode(&kc)
+			if err != nil {
+				w.WriteHeader(http.StatusBadRequest)
+				return
+			}
+		}
+		w.Header().Set("Content-Type", "application/json")
+		err := json.NewEncoder(w).Encode(kc)
+		if err != nil {
+			t.Error("failed to write khcheck:", err)
+		}
+	}))
+	defer server.Close()
+	khCheckClient = khcheckv1.NewForConfigOrDie(&rest.Config{Host: server.URL})
+
+	kh := &Kuberhealthy{}
+	if !kh.skipUnownedRun(c, false) {
+		t.Fatal("expected a scheduled run of a check that is not owned to be skipped")
+	}
+	if _, exists := kc.Annotations[external.KHRunNowAnnotationKey]; !exists {
+		t.Fatal("expected a skipped scheduled run to leave the pending on demand run alone")
+	}
+
+	if !kh.skipUnownedRun(c, true) {
+		t.Fatal("expected an on demand run of a check that is not owned to be skipped")
+	}
+	if pending := c.TakeRunRequest(); len(pending) != 0 {
+		t.Fatal("expected the skipped on demand run to be dropped, but", pending, "is still pending")
+	}
+	if _, exists := kc.Annotations[external.KHRunNowAnnotationKey]; exists {
+		t.Fatal("expected the run-now annotation of the skipped on demand run to be removed")
+	}
+}
+
+// expectMembersChanged fails the test if a membership does not see the members change in time
+func expectMembersChanged(t *testing.T, changed chan struct{}, description string) {
+	t.Helper()
+	select {
+	case <-changed:
+	case <-time.After(time.Second * 5):
+		t.Fatal("expected the members to change when", description)
+	}
+}
+
+// TestReconcileCheckMovingBetweenReplicas tests that a check that moves to a joining replica is stopped by its
+// previous owner and only started by its new owner after the previous owner has had time to notice the change
+func TestReconcileCheckMovingBetweenReplicas(t *testing.T) {
+	previousConfig, previousMembership := cfg, shardMembership
+	defer func() {
+		cfg, shardMembership = previousConfig, previousMembership
+	}()
+	cfg = &Config{}
+
+	ctx, cancel := context.WithCancel(context.Background())
+	defer cancel()
+
+	client := fake.NewSimpleClientset()
+	config := sharding.Config{Enabled: true, LeaseDuration: time.Second * 3, RenewPeriod: time.Second}
+	first, err := sharding.NewMembership(client, "kuberhealthy", "kuberhealthy-a", config)
+	if err != nil {
+		t.Fatal(err)
+	}
+	second, err := sharding.NewMembership(client, "kuberhealthy", "kuberhealthy-b", config)
+	if err != nil {
+		t.Fatal(err)
+	}
+
+	firstChanged := make(chan struct{}, 1)
+	go first.Run(ctx, firstChanged)
+	expectMembersChanged(t, firstChanged, "the first replica joins")
+
+	secondCtx, secondCancel := context.WithCancel(ctx)
+	defer secondCancel()
+	secondChanged := make(chan struct{}, 1)
+	go second.Run(secondCtx, secondChanged)
+	expectMembersChanged(t, secondChanged, "the second replica joins")
+
+	// find a check that moves to the second replica
+	var name string
+	for i := 0; i < 100 && len(name) == 0; i++ {
+		if second.Owns("kuberhealthy/check-" + strconv.Itoa(i)) {
+			name = "check-" + strconv.Itoa(i)
+		}
+	}
+	if len(name) == 0 {
+		t.Fatal("expected the second replica to own a check")
+	}
+	key := "kuberhealthy/" + name
+
+	newKuberhealthy := func() *Kuberhealthy {
+		kh := &Kuberhealthy{
+			runners:    make(map[string]*checkRunner),
+			checkStore: cache.NewStore(cache.MetaNamespaceKeyFunc),
+			checksCtx:  ctx,
+		}
+		err := kh.checkStore.Add(&khcheckv1.KuberhealthyCheck{
+			ObjectMeta: metav1.ObjectMeta{Name: name, Namespace: "kuberhealthy"},
+			Spec:       khcheckv1.CheckConfig{RunInterval: "5m"},
+		})
+		if err != nil {
+			t.Fatal("failed to add khcheck to the store:", err)
+		}
+		return kh
+	}
+
+	// the new owner waits for the previous owner to notice the change before starting the check
+	shardMembership = second
+	secondKH := newKuberhealthy()
+	secondKH.reconcileCheck(key)
+	runner, running := secondKH.runners[key]
+	if !running {
+		t.Fatal("expected the second replica to start the check it took over")
+	}
+	select {
+	case <-runner.done:
+		t.Fatal("expected the check taken over to wait before it starts")
+	case <-time.After(time.Millisecond * 100):
+	}
+	runner.cancel()
+	select {
+	case <-runner.done:
+	case <-time.After(time.Second * 5):
+		t.Fatal("expected a check waiting to start to stop when it is canceled")
+	}
+
+	// the previous owner stops running the check once it notices the second replica
+	expectMembersChanged(t, firstChanged, "the first replica notices the second")
+	shardMembership = first
+	firstKH := newKuberhealthy()
+	firstKH.reconcileCheck(key)
+	if len(firstKH.runners) != 0 {
+		t.Fatal("expected the first replica to not run a check owned by the second replica")
+	}
+
+	// the check moves back to the first replica right away when the second replica leaves
+	secondCancel()
+	expectMembersChanged(t, firstChanged, "the second replica leaves")
+	if !first.Owns(key) || first.HandoffDelay(key) != 0 {
+		t.Fatal("expected the first replica to take the check back without waiting")
+	}
+}
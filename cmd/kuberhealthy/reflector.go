@@ -83,15 +83,20 @@ func (sr *StateReflector) CurrentStatus() health.State {
 			continue
 		}
 
-		// parse check status from CRD and add it to the global status of errors. Skip blank errors
-		for _, e := range khState.Spec.Errors {
-			if len(strings.TrimSpace(e)) == 0 {
-				log.Warningln("Skipped an error that was blank when adding check details to current state.")
-				continue
+		// suspended and blocked checks are shown in the details, but do not change the global status
+		if !khState.Spec.Suspended && !khState.Spec.Blocked {
+			// parse check status from CRD and add it to the global status of errors. Skip blank errors
+			for _, e := range khState.Spec.Errors {
+				if len(strings.TrimSpace(e)) == 0 {
+					log.Warningln("Skipped an error that was blank when adding check details to current state.")
+					continue
+				}
+				state.AddError(e)
+				log.Debugln("Status page: Setting global OK state to false due to check details not being OK")
+				state.OK = false
 			}
-			state.AddError(e)
-			log.Debugln("Status page: Setting global OK state to false due to check details not being OK")
-			state.OK = false
+			state.AddWarning(khState.Spec.Warnings...)
+			state.AddSeverity(khState.Spec.GetSeverity())
 		}
 
 		khWorkload := determineKHWorkload(khState.Name, khState.Namespace)
@@ -107,6 +112,27 @@ func (sr *StateReflector) CurrentStatus() health.State {
 	return state
 }
 
+// WorkloadDetails returns the cached khstate details of the khcheck or khjob with the supplied namespace and name.  The
+// returned bool is false when there is no khstate for the workload in the cache.
+func (sr *StateReflector) WorkloadDetails(namespace string, name string) (khstatev1.WorkloadDetails, bool) {
+	if sr.store == nil {
+		log.Warningln("attempted to fetch WorkloadDetails from khStateReflector, but the store was nil")
+		return khstatev1.WorkloadDetails{}, false
+	}
+
+	item, exists, err := sr.store.GetByKey(namespace + "/" + sanitizeResourceName(name))
+	if err != nil || !exists {
+		return khstatev1.WorkloadDetails{}, false
+	}
+
+	khState, ok := item.(*khstatev1.KuberhealthyState)
+	if !ok {
+		log.Warningln("attempted to convert item from state cache reflector to a khstatev1.KuberhealthyState, but the type was invalid")
+		return khstatev1.WorkloadDetails{}, false
+	}
+	return khState.Spec, true
+}
+
 // determineKHWorkload uses the name and namespace of the kuberhealthy resource to determine whether its a khjob or khcheck
 // This function is necessary for the CurrentStatus() function as getting the KHWorkload from the state spec returns a blank kh workload.
 func determineKHWorkload(name string, namespace string) khstatev1.KHWorkload {
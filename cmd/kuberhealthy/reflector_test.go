@@ -91,3 +91,25 @@ func TestStop(t *testing.T) {
 		break
 	}
 }
+
+// TestWorkloadDetails ensures that khstate details are looked up from the reflector cache by workload name
+func TestWorkloadDetails(t *testing.T) {
+	sr := StateReflector{store: cache.NewStore(cache.MetaNamespaceKeyFunc)}
+	err := sr.store.Add(&khstatev1.KuberhealthyState{
+		ObjectMeta: metav1.ObjectMeta{Name: "bar", Namespace: "foo"},
+		Spec:       khstatev1.WorkloadDetails{OK: true, Node: "node-a"},
+	})
+	if err != nil {
+		t.Fatal(err)
+	}
+
+	details, exists := sr.WorkloadDetails("foo", "bar")
+	if !exists || !details.OK || details.Node != "node-a" {
+		t.Fatalf("expected cached details for foo/bar, got %+v (exists: %t)", details, exists)
+	}
+
+	_, exists = sr.WorkloadDetails("kuberhealthy", "bar")
+	if exists {
+		t.Fatal("expected no details for a workload in another namespace")
+	}
+}
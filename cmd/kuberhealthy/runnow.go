@@ -0,0 +1,161 @@
+package main
+
+import (
+	"errors"
+	"net/http"
+	"time"
+
+	"github.com/google/uuid"
+	log "github.com/sirupsen/logrus"
+	k8sErrors "k8s.io/apimachinery/pkg/api/errors"
+	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
+	"k8s.io/client-go/util/retry"
+
+	khcheckv1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khcheck/v1"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external"
+)
+
+// errCheckSuspended is returned when an on demand run is requested for a suspended khcheck
+var errCheckSuspended = errors.New("khcheck is suspended")
+
+// RunRequest is returned by the check API when an on demand run of a khcheck is requested
+type RunRequest struct {
+	Name      string `json:"name"`
+	Namespace string `json:"namespace"`
+	UUID      string `json:"uuid"` // the UUID the requested run will report with
+}
+
+// runCheckAPIHandler requests an on demand run of a khcheck.  The caller must present a bearer token for a user
+// that is allowed to update the khcheck.  The run is requested by setting the run-now annotation on the khcheck so
+// that the master pod picks it up no matter which kuberhealthy pod served the request.
+func (k *Kuberhealthy) runCheckAPIHandler(w http.ResponseWriter, r *http.Request) error {
+	log.Infoln("Client connected to check run API from", r.RemoteAddr, r.UserAgent())
+
+	namespace := r.PathValue("namespace")
+	name := r.PathValue("name")
+
+	user, err := authenticateRequest(r.Context(), kubernetesClient, r)
+	if err != nil {
+		writeAPIError(w, http.StatusUnauthorized, err.Error())
+		return nil
+	}
+
+	allowed, err := authorizeUser(r.Context(), kubernetesClient, user, "update", "khchecks", namespace, name)
+	if err != nil {
+		writeAPIError(w, http.StatusInternalServerError, err.Error())
+		return err
+	}
+	if !allowed {
+		writeAPIError(w, http.StatusForbidden, "user "+user.Username+" is not allowed to update khcheck "+namespace+"/"+name)
+		return nil
+	}
+
+	runUUID, err := requestCheckRun(namespace, name)
+	if err != nil {
+		if k8sErrors.IsNotFound(err) {
+			writeAPIError(w, http.StatusNotFound, "khcheck "+namespace+"/"+name+" not found")
+			return nil
+		}
+		if errors.Is(err, errCheckSuspended) {
+			writeAPIError(w, http.StatusConflict, "khcheck "+namespace+"/"+name+" is suspended")
+			return nil
+		}
+		writeAPIError(w, http.StatusInternalServerError, "failed to request run of khcheck: "+err.Error())
+		return err
+	}
+
+	log.Infoln("User", user.Username, "requested an on demand run of check", name, "in namespace", namespace, "with run uuid", runUUID)
+	return writeAPIResponse(w, http.StatusAccepted, RunRequest{Name: name, Namespace: namespace, UUID: runUUID})
+}
+
+// requestCheckRun sets the run-now annotation on a khcheck and returns the UUID the requested run will use.  When
+// a run has already been requested and has not started yet, the UUID of that run is returned instead.  Suspended
+// khchecks can not be run on demand.
+func requestCheckRun(namespace string, name string) (string, error) {
+	var runUUID string
+	err := retry.RetryOnConflict(retry.DefaultRetry, func() error {
+		kc, err := khCheckClient.KuberhealthyChecks(namespace).Get(name, metav1.GetOptions{})
+		if err != nil {
+			return err
+		}
+		if kc.Spec.IsSuspended(time.Now()) {
+			return errCheckSuspended
+		}
+
+		requested := kc.Annotations[external.KHRunNowAnnotationKey]
+		if isValidUUID(requested) {
+			runUUID = requested
+			return nil
+		}
+
+		runUUID = uuid.New().String()
+		if kc.Annotations == nil {
+			kc.Annotations = make(map[string]string)
+		}
+		kc.Annotations[external.KHRunNowAnnotationKey] = runUUID
+		_, err = khCheckClient.KuberhealthyChecks(namespace).Update(&kc)
+		return err
+	})
+	return runUUID, err
+}
+
+// acceptRunNowRequest hands a run requested with the run-now annotation on a khcheck to the running check.
+// Requests for checks that are not loaded yet are picked up when the check is loaded.
+func (k *Kuberhealthy) acceptRunNowRequest(kc khcheckv1.KuberhealthyCheck) {
+	_, exists := kc.Annotations[external.KHRunNowAnnotationKey]
+	if !exists {
+		return
+	}
+
+	c, err := k.getCheck(kc.Name, kc.Namespace)
+	if err != nil {
+		log.Debugln("Deferring on demand run of check", kc.Name, "in namespace", kc.Namespace, "until the check is loaded:", err)
+		return
+	}
+	requestRunFromAnnotation(kc, c)
+}
+
+// requestRunFromAnnotation requests an on demand run of the check if the run-now annotation is set on its khcheck.
+// The annotation is removed by the check when the run starts.
+func requestRunFromAnnotation(kc khcheckv1.KuberhealthyCheck, c *external.Checker) {
+	requested, exists := kc.Annotations[external.KHRunNowAnnotationKey]
+	if !exists {
+		return
+	}
+
+	runUUID := c.RequestRun(requested)
+	log.Infoln("On demand run of check", kc.Name, "in namespace", kc.Namespace, "requested with run uuid", runUUID)
+}
+
+// clearRunNowAnnotation removes the run-now annotation from the khcheck of a check once the run it requested has
+// started.  If the annotation has since been changed to request another run, that run is requested instead.
+func (k *Kuberhealthy) clearRunNowAnnotation(c *external.Checker, runUUID string) {
+	err := retry.RetryOnConflict(retry.DefaultRetry, func() error {
+		kc, err := k.getKHCheck(c.CheckNamespace(), c.Name())
+		if err != nil {
+			return err
+		}
+
+		requested, exists := kc.Annotations[external.KHRunNowAnnotationKey]
+		if !exists {
+			return nil
+		}
+		if requested != runUUID && isValidUUID(requested) {
+			requestRunFromAnnotation(kc, c)
+			return nil
+		}
+
+		delete(kc.Annotations, external.KHRunNowAnnotationKey)
+		_, err = khCheckClient.KuberhealthyChecks(kc.Namespace).Update(&kc)
+		return err
+	})
+	if err != nil {
+		log.Errorln("Error removing run-now annotation from check", c.Name(), "in namespace", c.CheckNamespace()+":", err)
+	}
+}
+
+// isValidUUID indicates if the supplied string is a valid UUID
+func isValidUUID(s string) bool {
+	_, err := uuid.Parse(s)
+	return err == nil
+}
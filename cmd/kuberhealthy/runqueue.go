@@ -0,0 +1,201 @@
+package main
+
+import (
+	"context"
+	"sync"
+	"time"
+
+	"github.com/prometheus/client_golang/prometheus"
+)
+
+// RunQueueConfig limits how many khcheck runs execute at the same time.  Runs over the limits wait in a queue until
+// a running check finishes.
+type RunQueueConfig struct {
+	MaxConcurrentRuns             int `yaml:"maxConcurrentRuns,omitempty"`             // the most check runs executing at once. 0 means no limit
+	MaxConcurrentRunsPerNamespace int `yaml:"maxConcurrentRunsPerNamespace,omitempty"` // the most check runs executing at once in a single namespace. 0 means no limit
+}
+
+// queuedRun is a check run waiting in the run queue
+type queuedRun struct {
+	namespace string
+	queued    time.Time
+	started   chan struct{} // closed when the run may start
+}
+
+// runQueueStats are the counters of a single namespace in the run queue
+type runQueueStats struct {
+	waiting          int
+	running          int
+	started          int64
+	waitSecondsTotal float64
+}
+
+// RunQueue is a queue in front of khcheck runs that starts them in the order they were queued while keeping the
+// number of runs executing at once within the configured limits.  A run that can not start because its namespace
+// is at its limit does not hold up runs from other namespaces queued behind it.
+type RunQueue struct {
+	mu      sync.Mutex
+	config  RunQueueConfig
+	running int
+	waiting []*queuedRun
+	stats   map[string]*runQueueStats // counters by namespace
+}
+
+// NewRunQueue creates a run queue with the supplied limits
+func NewRunQueue(config RunQueueConfig) *RunQueue {
+	return &RunQueue{
+		config: config,
+		stats:  make(map[string]*runQueueStats),
+	}
+}
+
+// SetConfig changes the limits of the queue.  Runs already executing are not stopped when the limits are lowered.
+func (q *RunQueue) SetConfig(config RunQueueConfig) {
+	q.mu.Lock()
+	defer q.mu.Unlock()
+	q.config = config
+	q.startWaiting()
+}
+
+// Acquire waits until a run in the supplied namespace may start and returns a func that must be called when the run
+// is done.  If the context ends first, the run is removed from the queue and the context's error is returned.
+func (q *RunQueue) Acquire(ctx context.Context, namespace string) (func(), error) {
+	run := &queuedRun{
+		namespace: namespace,
+		queued:    time.Now(),
+		started:   make(chan struct{}),
+	}
+
+	q.mu.Lock()
+	q.namespaceStats(namespace).waiting++
+	q.waiting = append(q.waiting, run)
+	q.startWaiting()
+	q.mu.Unlock()
+
+	release := func() {
+		q.mu.Lock()
+		defer q.mu.Unlock()
+		q.running--
+		q.namespaceStats(namespace).running--
+		q.startWaiting()
+	}
+
+	select {
+	case <-run.started:
+		return release, nil
+	case <-ctx.Done():
+	}
+
+	// the run may have been started while the context ended, in which case its slot is given back
+	q.mu.Lock()
+	select {
+	case <-run.started:
+		q.mu.Unlock()
+		release()
+		return nil, ctx.Err()
+	default:
+	}
+	for i, r := range q.waiting {
+		if r == run {
+			q.waiting = append(q.waiting[:i], q.waiting[i+1:]...)
+			break
+		}
+	}
+	q.namespaceStats(namespace).waiting--
+	q.mu.Unlock()
+	return nil, ctx.Err()
+}
+
+// startWaiting starts the waiting runs that fit within the limits, in the order they were queued.  The queue must be
+// locked by the caller.
+func (q *RunQueue) startWaiting() {
+	stillWaiting := q.waiting[:0]
+	for _, run := range q.waiting {
+		stats := q.namespaceStats(run.namespace)
+		if !q.canStart(stats) {
+			stillWaiting = append(stillWaiting, run)
+			continue
+		}
+
+		q.running++
+		stats.running++
+		stats.waiting--
+		stats.started++
+		stats.waitSecondsTotal += time.Since(run.queued).Seconds()
+		close(run.started)
+	}
+
+	// clear the tail so that started runs can be garbage collected
+	for i := len(stillWaiting); i < len(q.waiting); i++ {
+		q.waiting[i] = nil
+	}
+	q.waiting = stillWaiting
+}
+
+// canStart indicates if a run of a namespace with the supplied counters may start.  The queue must be locked by the
+// caller.
+func (q *RunQueue) canStart(stats *runQueueStats) bool {
+	if q.config.MaxConcurrentRuns > 0 && q.running >= q.config.MaxConcurrentRuns {
+		return false
+	}
+	if q.config.MaxConcurrentRunsPerNamespace > 0 && stats.running >= q.config.MaxConcurrentRunsPerNamespace {
+		return false
+	}
+	return true
+}
+
+// namespaceStats returns the counters of a namespace, creating them if needed.  The queue must be locked by the
+// caller.
+func (q *RunQueue) namespaceStats(namespace string) *runQueueStats {
+	stats, ok := q.stats[namespace]
+	if !ok {
+		stats = &runQueueStats{}
+		q.stats[namespace] = stats
+	}
+	return stats
+}
+
+// The descriptions of the run queue metrics
+var (
+	runQueueDepthDesc      = prometheus.NewDesc("kuberhealthy_run_queue_depth", "Shows the khcheck runs waiting in the run queue", []string{"namespace"}, nil)
+	runQueueOldestWaitDesc = prometheus.NewDesc("kuberhealthy_run_queue_oldest_wait_seconds", "Shows how long the longest waiting khcheck run has been in the run queue", []string{"namespace"}, nil)
+	runQueueRunningDesc    = prometheus.NewDesc("kuberhealthy_run_queue_running", "Shows the khcheck runs started by the run queue that are still executing", []string{"namespace"}, nil)
+	runQueueStartedDesc    = prometheus.NewDesc("kuberhealthy_run_queue_started_total", "Counts the khcheck runs started by the run queue", []string{"namespace"}, nil)
+	runQueueWaitedDesc     = prometheus.NewDesc("kuberhealthy_run_queue_wait_seconds_total", "Counts the seconds khcheck runs waited in the run queue before starting", []string{"namespace"}, nil)
+)
+
+// Describe sends the descriptions of the run queue metrics so the queue can be registered as a prometheus collector
+func (q *RunQueue) Describe(ch chan<- *prometheus.Desc) {
+	ch <- runQueueDepthDesc
+	ch <- runQueueOldestWaitDesc
+	ch <- runQueueRunningDesc
+	ch <- runQueueStartedDesc
+	ch <- runQueueWaitedDesc
+}
+
+// Collect sends the queue depth, running runs and wait time of the queue by namespace
+func (q *RunQueue) Collect(ch chan<- prometheus.Metric) {
+	q.mu.Lock()
+	defer q.mu.Unlock()
+
+	// runs are queued in order, so the first waiting run of each namespace has waited the longest
+	now := time.Now()
+	oldestQueued := make(map[string]time.Time)
+	for _, run := range q.waiting {
+		if _, ok := oldestQueued[run.namespace]; !ok {
+			oldestQueued[run.namespace] = run.queued
+		}
+	}
+
+	for namespace, stats := range q.stats {
+		var waited float64
+		if queued, ok := oldestQueued[namespace]; ok {
+			waited = now.Sub(queued).Seconds()
+		}
+		ch <- prometheus.MustNewConstMetric(runQueueDepthDesc, prometheus.GaugeValue, float64(stats.waiting), namespace)
+		ch <- prometheus.MustNewConstMetric(runQueueOldestWaitDesc, prometheus.GaugeValue, waited, namespace)
+		ch <- prometheus.MustNewConstMetric(runQueueRunningDesc, prometheus.GaugeValue, float64(stats.running), namespace)
+		ch <- prometheus.MustNewConstMetric(runQueueStartedDesc, prometheus.CounterValue, float64(stats.started), namespace)
+		ch <- prometheus.MustNewConstMetric(runQueueWaitedDesc, prometheus.CounterValue, stats.waitSecondsTotal, namespace)
+	}
+}
@@ -0,0 +1,130 @@
+package main
+
+import (
+	"context"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/metrics"
+)
+
+// acquireInBackground starts acquiring a run slot and returns a channel that receives the release func once the run
+// may start
+func acquireInBackground(ctx context.Context, q *RunQueue, namespace string) chan func() {
+	started := make(chan func(), 1)
+	go func() {
+		release, err := q.Acquire(ctx, namespace)
+		if err == nil {
+			started <- release
+		}
+	}()
+	return started
+}
+
+// expectStarted fails the test if a run does not start in time and returns its release func
+func expectStarted(t *testing.T, started chan func(), description string) func() {
+	t.Helper()
+	select {
+	case release := <-started:
+		return release
+	case <-time.After(time.Second * 5):
+		t.Fatal("expected", description, "to start")
+	}
+	return nil
+}
+
+// expectWaiting fails the test if a run starts
+func expectWaiting(t *testing.T, started chan func(), description string) {
+	t.Helper()
+	select {
+	case <-started:
+		t.Fatal("expected", description, "to wait in the queue")
+	case <-time.After(time.Millisecond * 100):
+	}
+}
+
+// TestRunQueueLimits tests that runs wait for the global and per namespace limits
+func TestRunQueueLimits(t *testing.T) {
+	ctx, cancel := context.WithCancel(context.Background())
+	defer cancel()
+
+	q := NewRunQueue(RunQueueConfig{MaxConcurrentRuns: 2, MaxConcurrentRunsPerNamespace: 1})
+
+	releaseFirst := expectStarted(t, acquireInBackground(ctx, q, "a"), "the first run in namespace a")
+
+	// a second run in the same namespace waits for the first, but does not hold up other namespaces
+	secondA := acquireInBackground(ctx, q, "a")
+	expectWaiting(t, secondA, "the second run in namespace a")
+	releaseB := expectStarted(t, acquireInBackground(ctx, q, "b"), "the first run in namespace b")
+
+	// the global limit is reached, so runs in new namespaces wait too
+	firstC := acquireInBackground(ctx, q, "c")
+	expectWaiting(t, firstC, "the first run in namespace c")
+
+	// the queue shows how long its waiting runs have been queued
+	output, err := metrics.GatherText(q)
+	if err != nil {
+		t.Fatal("failed to gather run queue metrics:", err)
+	}
+	if !strings.Contains(output, `kuberhealthy_run_queue_depth{namespace="c"} 1`) || strings.Contains(output, `kuberhealthy_run_queue_oldest_wait_seconds{namespace="c"} 0`+"\n") {
+		t.Fatalf("expected a waiting run in namespace c to be shown in the run queue metrics, got:\n%s", output)
+	}
+
+	// finishing the first run lets the second run in namespace a start ahead of namespace c, which was queued later
+	releaseFirst()
+	releaseSecondA := expectStarted(t, secondA, "the second run in namespace a")
+	expectWaiting(t, firstC, "the first run in namespace c")
+
+	releaseB()
+	releaseC := expectStarted(t, firstC, "the first run in namespace c")
+	releaseSecondA()
+	releaseC()
+
+	output, err = metrics.GatherText(q)
+	if err != nil {
+		t.Fatal("failed to gather run queue metrics:", err)
+	}
+	for _, expected := range []string{
+		`kuberhealthy_run_queue_depth{namespace="a"} 0`,
+		`kuberhealthy_run_queue_oldest_wait_seconds{namespace="c"} 0`,
+		`kuberhealthy_run_queue_running{namespace="c"} 0`,
+		`kuberhealthy_run_queue_started_total{namespace="a"} 2`,
+		`kuberhealthy_run_queue_wait_seconds_total{namespace="b"}`,
+	} {
+		if !strings.Contains(output, expected) {
+			t.Fatalf("expected run queue metrics to contain %s, got:\n%s", expected, output)
+		}
+	}
+}
+
+// TestRunQueueCancel tests that a run that stops waiting is removed from the queue
+func TestRunQueueCancel(t *testing.T) {
+	ctx, cancel := context.WithCancel(context.Background())
+	defer cancel()
+
+	q := NewRunQueue(RunQueueConfig{MaxConcurrentRuns: 1})
+	release := expectStarted(t, acquireInBackground(ctx, q, "a"), "the first run")
+
+	waitCtx, waitCancel := context.WithTimeout(ctx, time.Millisecond*50)
+	defer waitCancel()
+	_, err := q.Acquire(waitCtx, "a")
+	if err == nil {
+		t.Fatal("expected an error when the context ends while waiting in the queue")
+	}
+
+	q.mu.Lock()
+	waiting := len(q.waiting)
+	depth := q.stats["a"].waiting
+	q.mu.Unlock()
+	if waiting != 0 || depth != 0 {
+		t.Fatalf("expected the canceled run to be removed from the queue, found %d waiting and a depth of %d", waiting, depth)
+	}
+
+	// raising the limit starts waiting runs right away
+	second := acquireInBackground(ctx, q, "a")
+	expectWaiting(t, second, "the second run")
+	q.SetConfig(RunQueueConfig{MaxConcurrentRuns: 2})
+	expectStarted(t, second, "the second run")()
+	release()
+}
@@ -0,0 +1,142 @@
+package main
+
+import (
+	"context"
+	"crypto/tls"
+	"crypto/x509"
+	"errors"
+	"net/http"
+	"os"
+	"sync"
+	"time"
+
+	log "github.com/sirupsen/logrus"
+)
+
+// tlsReloadInterval is how often the certificate files of the web server are polled for changes
+const tlsReloadInterval = time.Second * 10
+
+// TLSConfig configures TLS for the Kuberhealthy web server.  TLS is enabled when a certificate and key are set.
+type TLSConfig struct {
+	CertFile          string `yaml:"certFile,omitempty"`          // PEM certificate served by the web server
+	KeyFile           string `yaml:"keyFile,omitempty"`           // PEM key of the certificate
+	ClientCAFile      string `yaml:"clientCAFile,omitempty"`      // PEM file of CAs trusted to sign the client certificates of checker pods
+	RequireClientCert bool   `yaml:"requireClientCert,omitempty"` // reject check reports sent without a verified client certificate
+}
+
+// enabled returns true when the web server should serve TLS
+func (c TLSConfig) enabled() bool {
+	return len(c.CertFile) > 0 && len(c.KeyFile) > 0
+}
+
+// validate returns an error when client certificates are required but can not be verified, so that check reports are
+// never accepted without the client certificate the configuration asks for
+func (c TLSConfig) validate() error {
+	if !c.RequireClientCert {
+		return nil
+	}
+	if !c.enabled() {
+		return errors.New("tls.requireClientCert is set but TLS is not enabled by setting tls.certFile and tls.keyFile")
+	}
+	if len(c.ClientCAFile) == 0 {
+		return errors.New("tls.requireClientCert is set without a tls.clientCAFile to verify client certificates with")
+	}
+	return nil
+}
+
+// certReloader serves the certificate and client CAs of the web server and reloads them when their files change, so
+// that rotated certificates are picked up without a restart
+type certReloader struct {
+	config    TLSConfig
+	mu        sync.RWMutex
+	cert      *tls.Certificate
+	clientCAs *x509.CertPool
+}
+
+// newCertReloader loads the configured certificate and client CAs and reloads them whenever one of their files
+// changes until the context is canceled
+func newCertReloader(ctx context.Context, config TLSConfig) (*certReloader, error) {
+	r := &certReloader{config: config}
+	err := r.reload()
+	if err != nil {
+		return nil, err
+	}
+
+	files := []string{config.CertFile, config.KeyFile}
+	if len(config.ClientCAFile) > 0 {
+		files = append(files, config.ClientCAFile)
+	}
+	for _, f := range files {
+		changes, err := watchConfig(ctx, f, tlsReloadInterval)
+		if err != nil {
+			return nil, err
+		}
+		go func(f string, changes chan string) {
+			for range changes {
+				log.Infoln("TLS file", f, "changed. Reloading web server certificates.")
+				err := r.reload()
+				if err != nil {
+					// certificates and keys are often not replaced at the same time, so we keep serving the
+					// current certificate until the files make a valid pair again
+					log.Errorln("Error reloading web server certificates. Continuing with the current certificates:", err)
+				}
+			}
+		}(f, changes)
+	}
+
+	return r, nil
+}
+
+// reload loads the certificate and client CAs from their files
+func (r *certReloader) reload() error {
+	cert, err := tls.LoadX509KeyPair(r.config.CertFile, r.config.KeyFile)
+	if err != nil {
+		return errors.New("failed to load web server certificate: " + err.Error())
+	}
+
+	var clientCAs *x509.CertPool
+	if len(r.config.ClientCAFile) > 0 {
+		pem, err := os.ReadFile(r.config.ClientCAFile)
+		if err != nil {
+			return errors.New("failed to read client CA file: " + err.Error())
+		}
+		clientCAs = x509.NewCertPool()
+		if !clientCAs.AppendCertsFromPEM(pem) {
+			return errors.New("no certificates found in client CA file " + r.config.ClientCAFile)
+		}
+	}
+
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	r.cert = &cert
+	r.clientCAs = clientCAs
+	return nil
+}
+
+// tlsConfig returns a TLS configuration that serves the current certificate.  When client CAs are configured, client
+// certificates are verified if they are presented.  Whether one is required is left to each handler, so that only
+// check reports have to be sent with a client certificate.
+func (r *certReloader) tlsConfig() *tls.Config {
+	return &tls.Config{
+		MinVersion: tls.VersionTLS12,
+		GetConfigForClient: func(*tls.ClientHelloInfo) (*tls.Config, error) {
+			r.mu.RLock()
+			defer r.mu.RUnlock()
+			config := &tls.Config{
+				MinVersion:   tls.VersionTLS12,
+				Certificates: []tls.Certificate{*r.cert},
+			}
+			if r.clientCAs != nil {
+				config.ClientCAs = r.clientCAs
+				config.ClientAuth = tls.VerifyClientCertIfGiven
+			}
+			return config, nil
+		},
+	}
+}
+
+// hasVerifiedClientCert returns true when the request was sent over TLS with a client certificate that was verified
+// against the client CAs
+func hasVerifiedClientCert(r *http.Request) bool {
+	return r.TLS != nil && len(r.TLS.VerifiedChains) > 0
+}
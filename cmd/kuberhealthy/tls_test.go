@@ -0,0 +1,172 @@
+package main
+
+import (
+	"bytes"
+	"context"
+	"crypto/ecdsa"
+	"crypto/elliptic"
+	"crypto/rand"
+	"crypto/tls"
+	"crypto/x509"
+	"crypto/x509/pkix"
+	"encoding/pem"
+	"math/big"
+	"net"
+	"net/http"
+	"net/http/httptest"
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+)
+
+// writeTestCert writes a self signed certificate and its key for 127.0.0.1 to the supplied files.  The certificate
+// can be used as a server certificate, a client certificate and the CA that verifies them.
+func writeTestCert(t *testing.T, certFile string, keyFile string, serial int64) tls.Certificate {
+	key, err := ecdsa.GenerateKey(elliptic.P256(), rand.Reader)
+	if err != nil {
+		t.Fatal("Error generating key:", err)
+	}
+	template := &x509.Certificate{
+		SerialNumber:          big.NewInt(serial),
+		Subject:               pkix.Name{CommonName: "kuberhealthy-test"},
+		NotBefore:             time.Now().Add(-time.Hour),
+		NotAfter:              time.Now().Add(time.Hour),
+		KeyUsage:              x509.KeyUsageDigitalSignature | x509.KeyUsageCertSign,
+		ExtKeyUsage:           []x509.ExtKeyUsage{x509.ExtKeyUsageServerAuth, x509.ExtKeyUsageClientAuth},
+		BasicConstraintsValid: true,
+		IsCA:                  true,
+		IPAddresses:           []net.IP{net.ParseIP("127.0.0.1")},
+	}
+	der, err := x509.CreateCertificate(rand.Reader, template, template, &key.PublicKey, key)
+	if err != nil {
+		t.Fatal("Error creating certificate:", err)
+	}
+	keyDER, err := x509.MarshalECPrivateKey(key)
+	if err != nil {
+		t.Fatal("Error marshaling key:", err)
+	}
+
+	certPEM := pem.EncodeToMemory(&pem.Block{Type: "CERTIFICATE", Bytes: der})
+	keyPEM := pem.EncodeToMemory(&pem.Block{Type: "EC PRIVATE KEY", Bytes: keyDER})
+	err = os.WriteFile(certFile, certPEM, 0600)
+	if err != nil {
+		t.Fatal(err)
+	}
+	err = os.WriteFile(keyFile, keyPEM, 0600)
+	if err != nil {
+		t.Fatal(err)
+	}
+
+	cert, err := tls.X509KeyPair(certPEM, keyPEM)
+	if err != nil {
+		t.Fatal(err)
+	}
+	return cert
+}
+
+// servedCert returns the certificate the reloader currently serves
+func servedCert(t *testing.T, r *certReloader) []byte {
+	config, err := r.tlsConfig().GetConfigForClient(&tls.ClientHelloInfo{})
+	if err != nil {
+		t.Fatal("Error getting TLS config:", err)
+	}
+	return config.Certificates[0].Certificate[0]
+}
+
+// TestCertReloader tests that rotated certificates are served after a reload and that a certificate that does not
+// match its key is not loaded
+func TestCertReloader(t *testing.T) {
+	dir := t.TempDir()
+	certFile := filepath.Join(dir, "tls.crt")
+	keyFile := filepath.Join(dir, "tls.key")
+	first := writeTestCert(t, certFile, keyFile, 1)
+
+	ctx, cancel := context.WithCancel(context.Background())
+	defer cancel()
+	r, err := newCertReloader(ctx, TLSConfig{CertFile: certFile, KeyFile: keyFile})
+	if err != nil {
+		t.Fatal("Error creating cert reloader:", err)
+	}
+	if !bytes.Equal(servedCert(t, r), first.Certificate[0]) {
+		t.Fatal("Expected the first certificate to be served")
+	}
+
+	// rotate the certificate
+	second := writeTestCert(t, certFile, keyFile, 2)
+	err = r.reload()
+	if err != nil {
+		t.Fatal("Error reloading certificates:", err)
+	}
+	if !bytes.Equal(servedCert(t, r), second.Certificate[0]) {
+		t.Fatal("Expected the rotated certificate to be served")
+	}
+
+	// a certificate written without its key is not loaded
+	writeTestCert(t, certFile, filepath.Join(dir, "other.key"), 3)
+	err = r.reload()
+	if err == nil {
+		t.Fatal("Expected an error loading a certificate that does not match its key")
+	}
+	if !bytes.Equal(servedCert(t, r), second.Certificate[0]) {
+		t.Fatal("Expected the rotated certificate to still be served")
+	}
+}
+
+// TestClientCertVerification tests that client certificates signed by the client CA are verified when presented
+func TestClientCertVerification(t *testing.T) {
+	dir := t.TempDir()
+	certFile := filepath.Join(dir, "tls.crt")
+	keyFile := filepath.Join(dir, "tls.key")
+	cert := writeTestCert(t, certFile, keyFile, 1)
+
+	ctx, cancel := context.WithCancel(context.Background())
+	defer cancel()
+	reloader, err := newCertReloader(ctx, TLSConfig{CertFile: certFile, KeyFile: keyFile, ClientCAFile: certFile})
+	if err != nil {
+		t.Fatal("Error creating cert reloader:", err)
+	}
+
+	server := httptest.NewUnstartedServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		if !hasVerifiedClientCert(r) {
+			w.WriteHeader(http.StatusUnauthorized)
+			return
+		}
+		w.WriteHeader(http.StatusOK)
+	}))
+	server.TLS = reloader.tlsConfig()
+	server.StartTLS()
+	defer server.Close()
+
+	rootCAs := x509.NewCertPool()
+	rootCAs.AddCert(mustParseCert(t, cert))
+
+	var testCases = []struct {
+		description  string
+		clientCerts  []tls.Certificate
+		expectedCode int
+	}{
+		{"client with a certificate", []tls.Certificate{cert}, http.StatusOK},
+		{"client without a certificate", nil, http.StatusUnauthorized},
+	}
+	for _, tc := range testCases {
+		client := &http.Client{Transport: &http.Transport{TLSClientConfig: &tls.Config{RootCAs: rootCAs, Certificates: tc.clientCerts}}}
+		resp, err := client.Get(server.URL)
+		if err != nil {
+			t.Fatal("Error making request as", tc.description+":", err)
+		}
+		resp.Body.Close()
+		if resp.StatusCode != tc.expectedCode {
+			t.Fatal("Expected status", tc.expectedCode, "for", tc.description, "but got", resp.StatusCode)
+		}
+	}
+}
+
+// mustParseCert parses the leaf of a certificate
+func mustParseCert(t *testing.T, cert tls.Certificate) *x509.Certificate {
+	leaf, err := x509.ParseCertificate(cert.Certificate[0])
+	if err != nil {
+		t.Fatal("Error parsing certificate:", err)
+	}
+	return leaf
+}
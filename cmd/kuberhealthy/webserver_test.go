@@ -14,6 +14,7 @@ import (
 	"k8s.io/client-go/kubernetes"
 	"k8s.io/client-go/rest"
 
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/health"
 )
 
@@ -147,3 +148,62 @@ func TestWebServerNotOK(t *testing.T) {
 	}
 
 }
+
+// TestVerifyReportSignature tests that check reports are only accepted with a signature from their run key, or
+// unsigned when unsigned reports are allowed
+func TestVerifyReportSignature(t *testing.T) {
+	body := []byte(`{"OK":true,"Errors":[]}`)
+	runKey, err := external.NewRunKey()
+	if err != nil {
+		t.Fatal("Error generating run key:", err)
+	}
+
+	var testCases = []struct {
+		description   string
+		runKey        string
+		signature     string
+		allowUnsigned bool
+		expectErr     bool
+	}{
+		{"signed report", runKey, external.SignReport(runKey, body), false, false},
+		{"unsigned report when unsigned reports are allowed", runKey, "", true, false},
+		{"unsigned report when unsigned reports are not allowed", runKey, "", false, true},
+		{"report signed with another key", runKey, external.SignReport("another-key", body), true, true},
+		{"signed report from a pod without a run key", "", external.SignReport(runKey, body), true, true},
+	}
+
+	for _, tc := range testCases {
+		err := verifyReportSignature(tc.runKey, tc.signature, body, tc.allowUnsigned)
+		if tc.expectErr && err == nil {
+			t.Fatal("Expected an error verifying", tc.description)
+		}
+		if !tc.expectErr && err != nil {
+			t.Fatal("Unexpected error verifying", tc.description+":", err)
+		}
+	}
+}
+
+// TestAllowsUnsignedReport tests that pods given a run key must sign their report unless they run a legacy image
+func TestAllowsUnsignedReport(t *testing.T) {
+	legacyImages := []string{"kuberhealthy/dns-resolution-check:v1.4.0"}
+
+	var testCases = []struct {
+		description   string
+		podReport     PodReportInfo
+		requireSigned bool
+		expected      bool
+	}{
+		{"pod with a run key", PodReportInfo{RunKey: "key", Images: []string{"kuberhealthy/dns-resolution-check:v1.5.0"}}, false, false},
+		{"pod with a run key running a legacy image", PodReportInfo{RunKey: "key", Images: []string{"kuberhealthy/dns-resolution-check:v1.4.0"}}, false, true},
+		{"pod without a run key", PodReportInfo{Images: []string{"kuberhealthy/dns-resolution-check:v1.5.0"}}, false, true},
+		{"pod without a run key when signing is required", PodReportInfo{}, true, false},
+		{"pod running a legacy image when signing is required", PodReportInfo{RunKey: "key", Images: legacyImages}, true, false},
+	}
+
+	for _, tc := range testCases {
+		allowed := allowsUnsignedReport(tc.podReport, tc.requireSigned, legacyImages)
+		if allowed != tc.expected {
+			t.Fatal("Expected unsigned reports to be allowed for", tc.description, "to be", tc.expected, "but got", allowed)
+		}
+	}
+}
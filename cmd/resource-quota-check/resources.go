@@ -17,6 +17,18 @@ type Job struct {
 	namespace string
 }
 
+// Finding is a problem found while examining the resource quotas of a namespace
+type Finding struct {
+	message string
+	warning bool // true when a quota has reached the threshold, rather than the quota being impossible to examine
+}
+
+// Findings holds the errors and threshold warnings found while examining resource quotas
+type Findings struct {
+	errors   []string
+	warnings []string
+}
+
 func runResourceQuotaCheck(ctx context.Context) {
 
 	// List all namespaces in the cluster.
@@ -31,9 +43,10 @@ func runResourceQuotaCheck(ctx context.Context) {
 	}
 
 	select {
-	case rqErrors := <-examineResourceQuotas(ctx, allNamespaces):
-		if len(rqErrors) != 0 {
-			log.Infoln("This check created", len(rqErrors), "errors and warnings.")
+	case findings := <-examineResourceQuotas(ctx, allNamespaces):
+		if len(findings.errors) != 0 {
+			rqErrors := append(findings.errors, findings.warnings...)
+			log.Infoln("This check created", len(findings.errors), "errors and", len(findings.warnings), "warnings.")
 			log.Debugln("Errors and warnings:")
 			for _, err := range rqErrors {
 				log.Debugln(err)
@@ -45,6 +58,20 @@ func runResourceQuotaCheck(ctx context.Context) {
 			}
 			return
 		}
+		// quotas that have reached the threshold are reported as a warning rather than failing the check
+		if len(findings.warnings) != 0 {
+			log.Infoln("This check created", len(findings.warnings), "warnings.")
+			log.Debugln("Warnings:")
+			for _, warning := range findings.warnings {
+				log.Debugln(warning)
+			}
+			log.Infoln("Reporting warnings to kuberhealthy.")
+			reportErr := kh.ReportWarning(findings.warnings)
+			if reportErr != nil {
+				log.Fatalln("error reporting warnings to kuberhealthy:", reportErr.Error())
+			}
+			return
+		}
 		log.Infoln("No errors or warnings were created during this check!")
 	case <-ctx.Done():
 		log.Infoln("Exiting and shutting down from interrupt.")
@@ -67,17 +94,17 @@ func runResourceQuotaCheck(ctx context.Context) {
 }
 
 // examineResourceQuotas looks at the resource quotas and makes reports on namespaces that meet or pass the threshold.
-func examineResourceQuotas(ctx context.Context, namespaceList *v1.NamespaceList) chan []string {
-	resultChan := make(chan []string)
+func examineResourceQuotas(ctx context.Context, namespaceList *v1.NamespaceList) chan Findings {
+	resultChan := make(chan Findings)
 
 	resourceQuotasJobChan := make(chan *Job, len(namespaceList.Items))
-	resourceQuotaErrorsChan := make(chan string, len(namespaceList.Items))
+	resourceQuotaErrorsChan := make(chan Finding, len(namespaceList.Items))
 
 	go fillJobChan(namespaceList, resourceQuotasJobChan)
 
-	go func(jobs chan *Job, results chan string) {
+	go func(jobs chan *Job, results chan Finding) {
 
-		errors := make([]string, 0)
+		findings := Findings{}
 		waitGroup := sync.WaitGroup{}
 
 		for job := range jobs {
@@ -93,10 +120,14 @@ func examineResourceQuotas(ctx context.Context, namespaceList *v1.NamespaceList)
 			close(results)
 		}(&waitGroup)
 
-		for err := range results {
-			errors = append(errors, err)
+		for finding := range results {
+			if finding.warning {
+				findings.warnings = append(findings.warnings, finding.message)
+				continue
+			}
+			findings.errors = append(findings.errors, finding.message)
 		}
-		resultChan <- errors
+		resultChan <- findings
 
 		return
 	}(resourceQuotasJobChan, resourceQuotaErrorsChan)
@@ -104,7 +135,7 @@ func examineResourceQuotas(ctx context.Context, namespaceList *v1.NamespaceList)
 	return resultChan
 }
 
-// createWorkerForNamespaceResourceQuotaCheck looks at the resource quotas for a given namespace and creates error messages
+// createWorkerForNamespaceResourceQuotaCheck looks at the resource quotas for a given namespace and creates warning messages
 // if usage is over a threshold.
 /*
 if blacklist is specified, and whitelist is not, then we simply operate on a blacklist
@@ -112,7 +143,7 @@ if blacklist is specified, and whitelist is also specified, then we operate on t
 if blacklist is not specified, but whitelist is, then we operate on a whitelist
 if neither a blacklist or whitelist is specified, then all namespaces are targeted
 */
-func createWorkerForNamespaceResourceQuotaCheck(ctx context.Context, namespace string, quotasChan chan string, wg *sync.WaitGroup) {
+func createWorkerForNamespaceResourceQuotaCheck(ctx context.Context, namespace string, quotasChan chan Finding, wg *sync.WaitGroup) {
 	defer wg.Done()
 	defer log.Debugln("worker for", namespace, "namespace is done!")
 
@@ -134,13 +165,13 @@ func createWorkerForNamespaceResourceQuotaCheck(ctx context.Context, namespace s
 	examineResouceQuotasForNamespace(ctx, namespace, quotasChan)
 }
 
-// examineResouceQuotasForNamespace looks at resource quotas and sends error messages on threshold violations.
-func examineResouceQuotasForNamespace(ctx context.Context, namespace string, c chan<- string) {
+// examineResouceQuotasForNamespace looks at resource quotas and sends warning messages on threshold violations.
+func examineResouceQuotasForNamespace(ctx context.Context, namespace string, c chan<- Finding) {
 	log.Infoln("Looking at resource quotas for", namespace, "namespace.")
 	quotas, err := client.CoreV1().ResourceQuotas(namespace).List(ctx, metav1.ListOptions{})
 	if err != nil {
 		err = fmt.Errorf("error occurred listing resource quotas for %s namespace %v", namespace, err)
-		c <- err.Error()
+		c <- Finding{message: err.Error()}
 		return
 	}
 	// Check if usage is at certain a threshold (percentage) of the limit.
@@ -154,12 +185,12 @@ func examineResouceQuotasForNamespace(ctx context.Context, namespace string, c c
 		if percentCPUUsed >= threshold {
 			err := fmt.Errorf("cpu for %s namespace has reached threshold of %4.2f: USED: %d LIMIT: %d PERCENT_USED: %6.3f",
 				namespace, threshold, status.Cpu().MilliValue(), limits.Cpu().MilliValue(), percentCPUUsed)
-			c <- err.Error()
+			c <- Finding{message: err.Error(), warning: true}
 		}
 		if percentMemoryUsed >= threshold {
 			err := fmt.Errorf("memory for %s namespace has reached threshold of %4.2f: USED: %d LIMIT: %d PERCENT_USED: %6.3f",
 				namespace, threshold, status.Memory().MilliValue(), limits.Memory().MilliValue(), percentMemoryUsed)
-			c <- err.Error()
+			c <- Finding{message: err.Error(), warning: true}
 		}
 	}
 }
@@ -117,8 +117,11 @@ func (sec *Checker) runExpiry(ctx context.Context, cancel context.CancelFunc, cl
 	doneChan := make(chan error)
 	runTimeout := time.After(checkTimeout)
 
+	// warnings found by the check are reported without failing the check
+	var warnings []string
 	go func(doneChan chan error) {
-		err := sec.doChecks()
+		var err error
+		warnings, err = sec.doChecks()
 		doneChan <- err
 	}(doneChan)
 
@@ -135,28 +138,34 @@ func (sec *Checker) runExpiry(ctx context.Context, cancel context.CancelFunc, cl
 		if err != nil {
 			return reportKHFailure(err.Error())
 		}
+		if len(warnings) > 0 {
+			return reportKHWarning(warnings)
+		}
 		return reportKHSuccess()
 	}
 }
 
-func (sec *Checker) doChecks() error {
+// doChecks checks the certificate expiration of the domain.  An expired certificate is returned as an error, while
+// a certificate expiring within the configured number of days is returned as a warning.
+func (sec *Checker) doChecks() ([]string, error) {
 	certExpired, expirePending, err := ssl_util.CertExpiry(domainName, portNum, daysToExpire, insecureBool)
 	if err != nil {
 		log.Error("Unable to perform SSL expiration check")
-		return err
+		return nil, err
 	}
 
 	if certExpired {
 		err := fmt.Errorf("Certificate for domain " + domainName + " is expired")
-		return err
+		return nil, err
 	}
 
 	if expirePending {
-		err := fmt.Errorf("Certificate for domain " + domainName + " is expiring in less than " + daysToExpire + " days")
-		return err
+		warning := "Certificate for domain " + domainName + " is expiring in less than " + daysToExpire + " days"
+		log.Warn(warning)
+		return []string{warning}, nil
 	}
 
-	return err
+	return nil, err
 }
 
 // reportKHSuccess reports success to Kuberhealthy servers and verifies the report successfully went through
@@ -170,6 +179,17 @@ func reportKHSuccess() error {
 	return err
 }
 
+// reportKHWarning reports a warning to Kuberhealthy servers and verifies the report successfully went through
+func reportKHWarning(warningMessages []string) error {
+	err := checkclient.ReportWarning(warningMessages)
+	if err != nil {
+		log.Error("Error reporting warning status to Kuberhealthy servers:", err)
+		return err
+	}
+	log.Info("Successfully reported warning status to Kuberhealthy servers")
+	return err
+}
+
 // reportKHFailure reports failure to Kuberhealthy servers and verifies the report successfully went through
 func reportKHFailure(errorMessage string) error {
 	err := checkclient.ReportFailure([]string{errorMessage})
@@ -1,6 +1,8 @@
 package v1
 
 import (
+	"time"
+
 	apiv1 "k8s.io/api/core/v1"
 	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
 )
@@ -30,13 +32,55 @@ type KuberhealthyCheck struct {
 // endpoint.
 // +k8s:openapi-gen=true
 type CheckConfig struct {
-	RunInterval string        `json:"runInterval" yaml:"runInterval"` // the interval at which the check runs
-	Timeout     string        `json:"timeout" yaml:"timeout"`         // the maximum time the pod is allowed to run before a failure is assumed
-	PodSpec     apiv1.PodSpec `json:"podSpec" yaml:"podSpec"`         // a spec for the external checker
+	// +optional
+	RunInterval string `json:"runInterval" yaml:"runInterval"` // the interval at which the check runs
+	// +optional
+	Schedule string `json:"schedule,omitempty" yaml:"schedule,omitempty"` // a cron expression for when the check runs. takes the place of runInterval when set
+	// +optional
+	Jitter  string        `json:"jitter,omitempty" yaml:"jitter,omitempty"` // the maximum random delay added before each run of the check
+	Timeout string        `json:"timeout" yaml:"timeout"`                   // the maximum time the pod is allowed to run before a failure is assumed
+	PodSpec apiv1.PodSpec `json:"podSpec" yaml:"podSpec"`                   // a spec for the external checker
 	// +optional
 	ExtraAnnotations map[string]string `json:"extraAnnotations" yaml:"extraAnnotations"` // a map of extra annotations that will be applied to the pod
 	// +optional
 	ExtraLabels map[string]string `json:"extraLabels" yaml:"extraLabels"` // a map of extra labels that will be applied to the pod
+	// +optional
+	FailureThreshold int `json:"failureThreshold,omitempty" yaml:"failureThreshold,omitempty"` // the number of consecutive failed runs before the check is shown as failing (default: 1)
+	// +optional
+	SuccessThreshold int `json:"successThreshold,omitempty" yaml:"successThreshold,omitempty"` // the number of consecutive successful runs before a failing check is shown as OK (default: 1)
+	// +optional
+	Suspend bool `json:"suspend,omitempty" yaml:"suspend,omitempty"` // stops the check from running and shows it as suspended until it is resumed
+	// +optional
+	// +nullable
+	SuspendUntil *metav1.Time `json:"suspendUntil,omitempty" yaml:"suspendUntil,omitempty"` // suspends the check until this time, after which it resumes on its own
+	// +optional
+	DependsOn []string `json:"dependsOn,omitempty" yaml:"dependsOn,omitempty"` // khchecks that must be passing for this check to run, as name or namespace/name
+	// +optional
+	Retries int `json:"retries,omitempty" yaml:"retries,omitempty"` // the number of times a run that fails to execute is retried before it is recorded as failed
+	// +optional
+	RetryBackoff string `json:"retryBackoff,omitempty" yaml:"retryBackoff,omitempty"` // the wait before the first retry of a run, doubled after each retry (default: 10s)
+	// +optional
+	// +kubebuilder:validation:Enum=Pod;Job
+	RunAs string `json:"runAs,omitempty" yaml:"runAs,omitempty"` // how the checker pod is run, as a bare Pod (default) or wrapped in a batch/v1 Job
+	// +optional
+	TTLSecondsAfterFinished *int32 `json:"ttlSecondsAfterFinished,omitempty" yaml:"ttlSecondsAfterFinished,omitempty"` // how long a finished Job is kept before it is garbage collected when runAs is Job
+	// +optional
+	BackoffLimit *int32 `json:"backoffLimit,omitempty" yaml:"backoffLimit,omitempty"` // how many times the Job controller retries a failed checker pod within the timeout when runAs is Job
+}
+
+// RunAsPod runs the checker pod of a check as a bare pod
+const RunAsPod = "Pod"
+
+// RunAsJob runs the checker pod of a check in a batch/v1 Job
+const RunAsJob = "Job"
+
+// IsSuspended indicates if a check with this config is suspended at the supplied time.  When suspendUntil is set,
+// the check is suspended until that time.  Otherwise, the check is suspended while suspend is true.
+func (in *CheckConfig) IsSuspended(now time.Time) bool {
+	if in.SuspendUntil != nil {
+		return now.Before(in.SuspendUntil.Time)
+	}
+	return in.Suspend
 }
 
 // +k8s:deepcopy-gen:interfaces=k8s.io/apimachinery/pkg/runtime.Object
@@ -95,6 +95,66 @@ func (in *WorkloadDetails) DeepCopyInto(out *WorkloadDetails) {
 		copy(*out, *in)
 	}
 	in.LastRun.DeepCopyInto(out.LastRun)
+	if in.Warnings != nil {
+		in, out := &in.Warnings, &out.Warnings
+		*out = make([]string, len(*in))
+		copy(*out, *in)
+	}
+	if in.Metrics != nil {
+		in, out := &in.Metrics, &out.Metrics
+		*out = make([]Metric, len(*in))
+		for i := range *in {
+			(*in)[i].DeepCopyInto(&(*out)[i])
+		}
+	}
+	if in.LastRunResult != nil {
+		in, out := &in.LastRunResult, &out.LastRunResult
+		*out = new(RunResult)
+		(*in).DeepCopyInto(*out)
+	}
+	if in.History != nil {
+		in, out := &in.History, &out.History
+		*out = make([]RunHistoryEntry, len(*in))
+		for i := range *in {
+			(*in)[i].DeepCopyInto(&(*out)[i])
+		}
+	}
+	return
+}
+
+// DeepCopyInto is an autogenerated deepcopy function, copying the receiver, writing into out. in must be non-nil.
+func (in *RunHistoryEntry) DeepCopyInto(out *RunHistoryEntry) {
+	*out = *in
+	in.Time.DeepCopyInto(&out.Time)
+	if in.Errors != nil {
+		in, out := &in.Errors, &out.Errors
+		*out = make([]string, len(*in))
+		copy(*out, *in)
+	}
+	return
+}
+
+// DeepCopyInto is an autogenerated deepcopy function, copying the receiver, writing into out. in must be non-nil.
+func (in *RunResult) DeepCopyInto(out *RunResult) {
+	*out = *in
+	if in.Errors != nil {
+		in, out := &in.Errors, &out.Errors
+		*out = make([]string, len(*in))
+		copy(*out, *in)
+	}
+	return
+}
+
+// DeepCopyInto is an autogenerated deepcopy function, copying the receiver, writing into out. in must be non-nil.
+func (in *Metric) DeepCopyInto(out *Metric) {
+	*out = *in
+	if in.Labels != nil {
+		in, out := &in.Labels, &out.Labels
+		*out = make(map[string]string, len(*in))
+		for key, val := range *in {
+			(*out)[key] = val
+		}
+	}
 	return
 }
 
@@ -128,6 +188,18 @@ func NewWorkloadDetails(workloadType KHWorkload) WorkloadDetails {
 	}
 }
 
+// GetSeverity returns the severity of the khWorkload's last run.  khstates written before severity was tracked
+// only have an OK state, so the severity is derived from OK when it is blank.
+func (wd *WorkloadDetails) GetSeverity() Severity {
+	if !wd.OK {
+		return SeverityFailing
+	}
+	if wd.Severity == SeverityWarning {
+		return SeverityWarning
+	}
+	return SeverityOK
+}
+
 // GetKHWorkload returns the workload for the WorkloadDetails struct
 func (wd *WorkloadDetails) GetKHWorkload() KHWorkload {
 	// failsafe if the workload is empty
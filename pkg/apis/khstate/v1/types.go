@@ -39,10 +39,79 @@ type WorkloadDetails struct {
 	LastRun          *metav1.Time `json:"LastRun,omitempty" yaml:"LastRun,omitempty"` // the time the khWorkload was last run
 	AuthoritativePod string       `json:"AuthoritativePod" yaml:"AuthoritativePod"`   // the main kuberhealthy pod creating and updating the khstate
 	CurrentUUID      string       `json:"uuid" yaml:"uuid"`                           // the UUID that is authorized to report statuses into the kuberhealthy endpoint
+	// +optional
+	Warnings []string `json:"Warnings,omitempty" yaml:"Warnings,omitempty"` // the list of non-fatal warnings reported from the khWorkload run
+	// +optional
+	Metrics []Metric `json:"Metrics,omitempty" yaml:"Metrics,omitempty"` // the numeric measurements reported from the khWorkload run
+	// +optional
+	Severity Severity `json:"Severity,omitempty" yaml:"Severity,omitempty"` // the severity of the khWorkload run: OK, Warning or Failing
+	// +optional
+	// +nullable
+	LastRunResult *RunResult `json:"LastRunResult,omitempty" yaml:"LastRunResult,omitempty"` // the result of the latest run before failure and success thresholds are applied
+	// +optional
+	ConsecutiveFailures int `json:"ConsecutiveFailures,omitempty" yaml:"ConsecutiveFailures,omitempty"` // the number of consecutive runs that have failed
+	// +optional
+	ConsecutiveSuccesses int `json:"ConsecutiveSuccesses,omitempty" yaml:"ConsecutiveSuccesses,omitempty"` // the number of consecutive runs that have succeeded
+	// +optional
+	History []RunHistoryEntry `json:"History,omitempty" yaml:"History,omitempty"` // the results of past runs, oldest first
+	// +optional
+	TotalRuns int64 `json:"TotalRuns,omitempty" yaml:"TotalRuns,omitempty"` // the number of runs recorded for the khWorkload
+	// +optional
+	TotalFailures int64 `json:"TotalFailures,omitempty" yaml:"TotalFailures,omitempty"` // the number of recorded runs that failed
+	// +optional
+	CountedUUID string `json:"CountedUUID,omitempty" yaml:"CountedUUID,omitempty"` // the UUID of the last run counted in TotalRuns
+	// +optional
+	Suspended bool `json:"Suspended,omitempty" yaml:"Suspended,omitempty"` // true while the khcheck is suspended and not running
+	// +optional
+	Blocked bool `json:"Blocked,omitempty" yaml:"Blocked,omitempty"` // true while the khcheck is not running because one of its dependencies is failing
+	// +optional
+	BlockedReason string `json:"BlockedReason,omitempty" yaml:"BlockedReason,omitempty"` // why the khcheck is blocked by its dependencies
+	// +optional
+	Attempts int `json:"Attempts,omitempty" yaml:"Attempts,omitempty"` // the number of attempts the latest khcheck run took, including retries of runs that failed to execute
 	// +nullable
 	khWorkload *KHWorkload `json:"khWorkload,omitempty" yaml:"khWorkload,omitempty"`
 }
 
+// Metric is a single numeric measurement reported by a khWorkload run
+type Metric struct {
+	Name  string  `json:"Name" yaml:"Name"`   // the name of the measurement
+	Value float64 `json:"Value" yaml:"Value"` // the measured value
+	// +optional
+	Unit string `json:"Unit,omitempty" yaml:"Unit,omitempty"` // the unit of the measured value
+	// +optional
+	Labels map[string]string `json:"Labels,omitempty" yaml:"Labels,omitempty"` // extra labels describing the measurement
+}
+
+// RunResult is the result reported by a single khWorkload run.  The OK state shown for a khcheck only follows
+// its run results once the check's failure or success threshold of consecutive runs is reached.
+type RunResult struct {
+	OK       bool     `json:"OK" yaml:"OK"`                                 // the OK state reported by the run
+	Errors   []string `json:"Errors,omitempty" yaml:"Errors,omitempty"`     // the errors reported by the run
+	Severity Severity `json:"Severity,omitempty" yaml:"Severity,omitempty"` // the severity reported by the run
+}
+
+// RunHistoryEntry records the result of a single past khWorkload run
+type RunHistoryEntry struct {
+	Time        metav1.Time `json:"Time" yaml:"Time"`                         // the time the run was first recorded
+	OK          bool        `json:"OK" yaml:"OK"`                             // the OK state reported by the run
+	Errors      []string    `json:"Errors,omitempty" yaml:"Errors,omitempty"` // the errors reported by the run
+	RunDuration string      `json:"RunDuration" yaml:"RunDuration"`           // the time it took for the run to complete
+	Node        string      `json:"Node" yaml:"Node"`                         // the node the run happened on
+	UUID        string      `json:"uuid" yaml:"uuid"`                         // the UUID of the run
+}
+
+// Severity describes how healthy a khWorkload was on its last run.  A Warning means the khWorkload
+// is degraded but still OK, while Failing means the khWorkload is not OK.
+// +kubebuilder:validation:Enum=OK;Warning;Failing
+type Severity string
+
+// The severities a khWorkload run can be in
+const (
+	SeverityOK      Severity = "OK"
+	SeverityWarning Severity = "Warning"
+	SeverityFailing Severity = "Failing"
+)
+
 // KHWorkload is used to describe the different types of kuberhealthy workloads: KhCheck or KHJob
 type KHWorkload string
 
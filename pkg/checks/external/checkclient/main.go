@@ -56,6 +56,30 @@ func ReportFailure(errorMessages []string) error {
 	return sendReport(newReport)
 }
 
+// ReportWarning reports that the external checker has found problems that are
+// not severe enough to fail the check, such as a quota nearing its limit.  The
+// check stays OK, but is shown in a degraded Warning state with the supplied
+// warning messages.
+func ReportWarning(warningMessages []string) error {
+	writeLog("DEBUG: Reporting WARNING")
+
+	// make a new report with warnings
+	newReport := status.NewWarningReport(warningMessages)
+
+	// send it
+	return sendReport(newReport)
+}
+
+// SendReport sends a fully formed report to the Kuberhealthy service.  Use
+// this instead of ReportSuccess or ReportFailure when the report carries
+// warnings or metrics gathered during the check run.
+func SendReport(report status.Report) error {
+	writeLog("DEBUG: Reporting with ok state of:", report.OK)
+
+	// send it
+	return sendReport(report)
+}
+
 // writeLog writes a log entry if debugging is enabled
 func writeLog(i ...interface{}) {
 	if Debug {
@@ -69,6 +93,7 @@ func sendReport(s status.Report) error {
 
 	writeLog("DEBUG: Sending report with error length of:", len(s.Errors))
 	writeLog("DEBUG: Sending report with ok state of:", s.OK)
+	writeLog("DEBUG: Sending report with metric length of:", len(s.Metrics))
 
 	// marshal the request body
 	b, err := json.Marshal(s)
@@ -99,6 +124,13 @@ func sendReport(s status.Report) error {
 	req.Header.Set("kh-run-uuid", uuid)
 	req.Header.Set("Content-Type", "application/json")
 
+	// sign the report with the key of this run so kuberhealthy can verify it came from this pod.  Checks started by
+	// versions of kuberhealthy that do not give out run keys send their reports unsigned.
+	runKey := getKuberhealthyRunKey()
+	if len(runKey) > 0 {
+		req.Header.Set(external.ReportSignatureHeader, external.SignReport(runKey, b))
+	}
+
 	exponentialBackOff := backoff.NewExponentialBackOff()
 	exponentialBackOff.MaxElapsedTime = maxElapsedTime
 
@@ -113,8 +145,8 @@ func sendReport(s status.Report) error {
 		if err != nil {
 			return err
 		}
-		// retry on status codes that do not return a 200 or 400
-		if !(resp.StatusCode == http.StatusOK || resp.StatusCode == http.StatusBadRequest) {
+		// retry on status codes that do not return a 200, 400 or 401
+		if !(resp.StatusCode == http.StatusOK || resp.StatusCode == http.StatusBadRequest || resp.StatusCode == http.StatusUnauthorized) {
 			writeLog("ERROR: got a bad status code from kuberhealthy:", resp.StatusCode, resp.Status)
 			return fmt.Errorf("bad status code from kuberhealthy status reporting url: [%d] %s ", resp.StatusCode, resp.Status)
 		}
@@ -160,6 +192,16 @@ func getKuberhealthyRunUUID() (string, error) {
 	return khRunUUID, nil
 }
 
+// getKuberhealthyRunKey fetches the key that reports of this run are signed with from the environment variable.  The
+// key is blank when the check was started by a version of kuberhealthy that does not sign reports.
+func getKuberhealthyRunKey() string {
+	khRunKey := os.Getenv(external.KHRunKey)
+	if len(khRunKey) < 1 {
+		writeLog("WARNING: kuberhealthy run key from environment variable", external.KHRunKey, "was blank. Sending report unsigned.")
+	}
+	return khRunKey
+}
+
 // GetDeadline fetches the KH_CHECK_RUN_DEADLINE environment variable and returns it.
 // Checks are given up to the deadline to complete their check runs.
 func GetDeadline() (time.Time, error) {
@@ -1,11 +1,15 @@
 package checkclient
 
 import (
+	"io"
+	"net/http"
+	"net/http/httptest"
 	"os"
 	"testing"
 	"time"
 
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external/status"
 )
 
 // TestGetKuberhealthyURL ensures that KH_REPORTING_URL env var can be fetched
@@ -103,4 +107,43 @@ func TestGetDeadline(t *testing.T) {
 	}
 }
 
-//TODO: TestSendReport
+// TestSendReport ensures that reports are sent with the run UUID and signed with the run key
+func TestSendReport(t *testing.T) {
+	runKey, err := external.NewRunKey()
+	if err != nil {
+		t.Fatal("Error generating run key:", err)
+	}
+
+	var verified bool
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		body, err := io.ReadAll(r.Body)
+		if err != nil {
+			w.WriteHeader(http.StatusBadRequest)
+			return
+		}
+		if r.Header.Get("kh-run-uuid") != "some-random-uuid" {
+			w.WriteHeader(http.StatusBadRequest)
+			return
+		}
+		verified = external.VerifyReportSignature(runKey, body, r.Header.Get(external.ReportSignatureHeader))
+		if !verified {
+			w.WriteHeader(http.StatusUnauthorized)
+			return
+		}
+		w.WriteHeader(http.StatusOK)
+	}))
+	defer server.Close()
+
+	os.Setenv(external.KHReportingURL, server.URL)
+	os.Setenv(external.KHRunUUID, "some-random-uuid")
+	os.Setenv(external.KHRunKey, runKey)
+	defer os.Unsetenv(external.KHRunKey)
+
+	err = sendReport(status.NewReport([]string{}))
+	if err != nil {
+		t.Fatal("Error sending report:", err)
+	}
+	if !verified {
+		t.Fatal("Expected the report to be signed with the run key")
+	}
+}
@@ -0,0 +1,134 @@
+package external
+
+import (
+	"context"
+	"math"
+
+	batchv1 "k8s.io/api/batch/v1"
+	apiv1 "k8s.io/api/core/v1"
+	k8sErrors "k8s.io/apimachinery/pkg/api/errors"
+	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
+
+	khcheckv1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khcheck/v1"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external/util"
+)
+
+// defaultJobTTLSeconds is how long a finished checker job is kept before it is garbage collected when the check
+// does not specify ttlSecondsAfterFinished
+const defaultJobTTLSeconds int32 = 600
+
+// defaultJobBackoffLimit is how many times the job controller retries a failed checker pod when the check does not
+// specify backoffLimit
+const defaultJobBackoffLimit int32 = 2
+
+// runsAsJob indicates if the checker pod of this check is run in a Job instead of as a bare pod
+func (ext *Checker) runsAsJob() bool {
+	return ext.RunAs == khcheckv1.RunAsJob
+}
+
+// createJob prepares and creates a Job that runs the checker pod using the kubernetes API
+func (ext *Checker) createJob(ctx context.Context) (*batchv1.Job, error) {
+	ext.log("Creating external checker job named", ext.podName())
+	p, err := ext.checkerPod()
+	if err != nil {
+		return nil, err
+	}
+
+	return ext.KubeClient.BatchV1().Jobs(ext.Namespace).Create(ctx, ext.checkerJob(p), metav1.CreateOptions{})
+}
+
+// checkerJob wraps a checker pod in a Job.  The Job carries the name, labels and owner reference of the pod so it
+// can be found and cleaned up the same way, while the pod template keeps the labels that the run is watched by.
+// The Job controller retries failed checker pods up to the backoff limit and enforces the check timeout with
+// activeDeadlineSeconds, and the TTL controller garbage collects the finished Job and its pods.  Retried pods run
+// with the same run UUID, so the run still completes with the first report that comes in.
+func (ext *Checker) checkerJob(p *apiv1.Pod) *batchv1.Job {
+	backoffLimit := ext.JobBackoffLimit
+	activeDeadlineSeconds := int64(math.Ceil(ext.RunTimeout.Seconds()))
+	ttlSecondsAfterFinished := ext.JobTTLSeconds
+
+	job := &batchv1.Job{}
+	job.Name = p.Name
+	job.Namespace = p.Namespace
+	job.Labels = p.Labels
+	job.Annotations = p.Annotations
+	job.OwnerReferences = p.OwnerReferences
+	job.Spec.BackoffLimit = &backoffLimit
+	job.Spec.ActiveDeadlineSeconds = &activeDeadlineSeconds
+	job.Spec.TTLSecondsAfterFinished = &ttlSecondsAfterFinished
+	job.Spec.Template.Labels = p.Labels
+	job.Spec.Template.Annotations = p.Annotations
+	job.Spec.Template.Spec = p.Spec
+
+	return job
+}
+
+// getCheckerPod fetches the checker pod of the current run.  Pods of a Job are named by the Job controller, so they
+// are looked up by the run id label instead of by name.  The most recently created pod is returned when the Job has
+// retried its pod.  A not found error is returned when there is no pod.
+func (ext *Checker) getCheckerPod(ctx context.Context) (*apiv1.Pod, error) {
+	podClient := ext.KubeClient.CoreV1().Pods(ext.Namespace)
+	if !ext.runsAsJob() {
+		return podClient.Get(ctx, ext.podName(), metav1.GetOptions{})
+	}
+
+	pods, err := podClient.List(ctx, metav1.ListOptions{
+		LabelSelector: kuberhealthyRunIDLabel + "=" + ext.currentCheckUUID,
+	})
+	if err != nil {
+		return nil, err
+	}
+	if len(pods.Items) == 0 {
+		return nil, k8sErrors.NewNotFound(apiv1.Resource("pods"), ext.podName())
+	}
+	newest := pods.Items[0]
+	for _, p := range pods.Items[1:] {
+		if p.CreationTimestamp.After(newest.CreationTimestamp.Time) {
+			newest = p
+		}
+	}
+	return &newest, nil
+}
+
+// checkerPodExists indicates if a checker pod of the current run has not finished yet
+func (ext *Checker) checkerPodExists(ctx context.Context) (bool, error) {
+	if !ext.runsAsJob() {
+		return util.PodNameExists(ext.KubeClient, ext.podName(), ext.Namespace)
+	}
+
+	pods, err := ext.KubeClient.CoreV1().Pods(ext.Namespace).List(ctx, metav1.ListOptions{
+		LabelSelector: kuberhealthyRunIDLabel + "=" + ext.currentCheckUUID,
+	})
+	if err != nil {
+		return false, err
+	}
+	for _, p := range pods.Items {
+		if p.Status.Phase != apiv1.PodSucceeded && p.Status.Phase != apiv1.PodFailed {
+			return true, nil
+		}
+	}
+	return false, nil
+}
+
+// killCheckerPod deletes the checker pod of the current run.  When the check runs as a Job, the Job is deleted so
+// that the Job controller does not replace the pod, and the garbage collector removes its pods in the background.
+func (ext *Checker) killCheckerPod(ctx context.Context, gracePeriod int64) error {
+	if ext.runsAsJob() {
+		return ext.deleteCheckerJob(ctx, ext.podName(), gracePeriod)
+	}
+	return util.PodKill(ext.KubeClient, ext.podName(), ext.Namespace, gracePeriod)
+}
+
+// deleteCheckerJob deletes a checker Job and leaves the removal of its pods to the garbage collector.  A Job that no
+// longer exists is not an error.
+func (ext *Checker) deleteCheckerJob(ctx context.Context, name string, gracePeriod int64) error {
+	propagationPolicy := metav1.DeletePropagationBackground
+	err := ext.KubeClient.BatchV1().Jobs(ext.Namespace).Delete(ctx, name, metav1.DeleteOptions{
+		GracePeriodSeconds: &gracePeriod,
+		PropagationPolicy:  &propagationPolicy,
+	})
+	if err != nil && !k8sErrors.IsNotFound(err) {
+		return err
+	}
+	return nil
+}
@@ -0,0 +1,79 @@
+package external
+
+import (
+	"testing"
+	"time"
+
+	apiv1 "k8s.io/api/core/v1"
+	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
+
+	khcheckv1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khcheck/v1"
+)
+
+// TestCheckerJob tests that the checker pod is wrapped in a Job with the deadline and TTL of the check
+func TestCheckerJob(t *testing.T) {
+	t.Parallel()
+
+	ttl := int32(120)
+	backoffLimit := int32(1)
+	checkConfig := &khcheckv1.KuberhealthyCheck{
+		ObjectMeta: metav1.ObjectMeta{
+			Name:      "job-check",
+			Namespace: "foo",
+		},
+		Spec: khcheckv1.CheckConfig{
+			RunAs:                   khcheckv1.RunAsJob,
+			TTLSecondsAfterFinished: &ttl,
+			BackoffLimit:            &backoffLimit,
+			PodSpec: apiv1.PodSpec{
+				Containers: []apiv1.Container{{Name: "check", Image: "check:latest"}},
+			},
+		},
+	}
+	c := NewCheck(nil, checkConfig, nil, nil, DefaultKuberhealthyReportingURL)
+	c.RunTimeout = time.Second*134 + time.Millisecond*500
+	c.currentCheckUUID = "run-uuid"
+	c.checkPodName = "job-check-1"
+	if !c.runsAsJob() {
+		t.Fatal("expected a check with runAs Job to run as a job")
+	}
+
+	p, err := c.checkerPod()
+	if err != nil {
+		t.Fatal("error preparing checker pod:", err)
+	}
+	job := c.checkerJob(p)
+
+	if job.Name != "job-check-1" || job.Namespace != "foo" {
+		t.Fatalf("expected job foo/job-check-1, got %s/%s", job.Namespace, job.Name)
+	}
+	if *job.Spec.ActiveDeadlineSeconds != 135 {
+		t.Fatalf("expected an active deadline of 135 seconds, got %d", *job.Spec.ActiveDeadlineSeconds)
+	}
+	if *job.Spec.TTLSecondsAfterFinished != ttl {
+		t.Fatalf("expected a ttl of %d seconds, got %d", ttl, *job.Spec.TTLSecondsAfterFinished)
+	}
+	if *job.Spec.BackoffLimit != backoffLimit {
+		t.Fatalf("expected a backoff limit of %d, got %d", backoffLimit, *job.Spec.BackoffLimit)
+	}
+	if job.Spec.Template.Labels[kuberhealthyRunIDLabel] != "run-uuid" {
+		t.Fatal("expected the pod template to be labeled with the run id")
+	}
+	if job.Spec.Template.Annotations[KHCheckNameAnnotationKey] != "job-check" {
+		t.Fatal("expected the pod template to be annotated with the check name")
+	}
+	if len(job.Spec.Template.Spec.Containers) != 1 {
+		t.Fatal("expected the pod template to have the checker pod spec")
+	}
+
+	// checks that do not set a ttl or backoff limit use the defaults
+	checkConfig.Spec.TTLSecondsAfterFinished = nil
+	checkConfig.Spec.BackoffLimit = nil
+	c = NewCheck(nil, checkConfig, nil, nil, DefaultKuberhealthyReportingURL)
+	if c.JobTTLSeconds != defaultJobTTLSeconds {
+		t.Fatalf("expected the default ttl of %d seconds, got %d", defaultJobTTLSeconds, c.JobTTLSeconds)
+	}
+	if c.JobBackoffLimit != defaultJobBackoffLimit {
+		t.Fatalf("expected the default backoff limit of %d, got %d", defaultJobBackoffLimit, c.JobBackoffLimit)
+	}
+}
@@ -16,6 +16,7 @@ import (
 	k8sErrors "k8s.io/apimachinery/pkg/api/errors"
 
 	"github.com/google/uuid"
+	"github.com/gorhill/cronexpr"
 	log "github.com/sirupsen/logrus"
 	"k8s.io/apimachinery/pkg/watch"
 	"k8s.io/client-go/kubernetes"
@@ -28,6 +29,7 @@ import (
 	khjobv1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khjob/v1"
 	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/checks/external/util"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/tracing"
 )
 
 // KHReportingURL is the environment variable used to tell external checks where to send their status updates
@@ -37,12 +39,20 @@ const KHReportingURL = "KH_REPORTING_URL"
 // can be de-duplicated on the server side.
 const KHRunUUID = "KH_RUN_UUID"
 
+// KHRunKey is the environment variable used to give external checks a key that is unique to their run.  Checks sign
+// their reports with it so that Kuberhealthy can verify that a report came from the pod started for the run.
+const KHRunKey = "KH_RUN_KEY"
+
 // KHDeadline is the environment variable name for when checks must finish their runs by in unixtime
 const KHDeadline = "KH_CHECK_RUN_DEADLINE"
 
 // KHCheckNameAnnotationKey is the annotation which holds the check's name for later validation when the pod calls in
 const KHCheckNameAnnotationKey = "comcast.github.io/check-name"
 
+// KHRunNowAnnotationKey is the annotation on a khcheck that requests an on demand run of the check.  Its value is
+// used as the UUID of the requested run when it is a valid UUID.
+const KHRunNowAnnotationKey = "comcast.github.io/run-now"
+
 // KHPodNamespace is the namespace variable used to tell external checks their namespace to perform
 // checks in.
 const KHPodNamespace = "KH_POD_NAMESPACE"
@@ -86,8 +96,20 @@ var kuberhealthyNamespace = "kuberhealthy"
 type Checker struct {
 	CheckName                string // the name of this checker
 	Namespace                string
-	RunInterval              time.Duration // how often this check runs a loop
-	RunTimeout               time.Duration // time check must run completely within
+	RunInterval              time.Duration        // how often this check runs a loop
+	RunSchedule              *cronexpr.Expression // an optional cron schedule that takes the place of RunInterval
+	RunJitter                time.Duration        // the maximum random delay added before each run
+	RunTimeout               time.Duration        // time check must run completely within
+	FailureThreshold         int                  // consecutive failed runs before the check is shown as failing
+	SuccessThreshold         int                  // consecutive successful runs before a failing check is shown as OK
+	Suspend                  bool                 // stops the check from running until it is resumed
+	SuspendUntil             time.Time            // suspends the check until this time when set
+	DependsOn                []string             // the namespace/name of khchecks that must be passing for this check to run
+	Retries                  int                  // how many times a run that fails to execute is retried
+	RetryBackoff             time.Duration        // the wait before the first retry of a run, doubled after each retry
+	RunAs                    string               // runs the checker pod as a bare Pod or wrapped in a Job
+	JobTTLSeconds            int32                // how long a finished checker Job is kept when RunAs is Job
+	JobBackoffLimit          int32                // how many times a failed checker pod is retried by the Job controller when RunAs is Job
 	KubeClient               *kubernetes.Clientset
 	KHJobClient              *khjobv1.KHJobV1Client
 	KHCheckClient            *khcheckv1.KHCheckV1Client
@@ -100,13 +122,18 @@ type Checker struct {
 	ExtraLabels              map[string]string
 	Node                     string             // the node the checker pod runs on
 	currentCheckUUID         string             // the UUID of the current external checker running
+	currentRunKey            string             // the key the checker pod of the current run signs its report with
 	Debug                    bool               // indicates we should run in debug mode - run once and stop
 	shutdownCTXFunc          context.CancelFunc // used to cancel things in-flight when shutting down gracefully
 	shutdownCTX              context.Context    // a context used for shutting down the check gracefully
 	wg                       sync.WaitGroup     // used to track background workers and processes
 	hostname                 string             // hostname cache
 	checkPodName             string             // the current unique checker pod name
+	runRequestChan           chan struct{}      // notified when an on demand run is requested
+	runRequestMu             sync.Mutex         // protects requestedRunUUID
+	requestedRunUUID         string             // the UUID of the pending on demand run, if any
 	KHWorkload               khstatev1.KHWorkload
+	Tracer                   *tracing.Tracer // records a trace of each run when set
 }
 
 func init() {
@@ -128,6 +155,23 @@ func NewCheck(client *kubernetes.Clientset, checkConfig *khcheckv1.KuberhealthyC
 		checkConfig.Namespace = "kuberhealthy"
 	}
 
+	var suspendUntil time.Time
+	if checkConfig.Spec.SuspendUntil != nil {
+		suspendUntil = checkConfig.Spec.SuspendUntil.Time
+	}
+
+	// finished checker jobs are kept for a default amount of time unless the check specifies otherwise
+	jobTTLSeconds := defaultJobTTLSeconds
+	if checkConfig.Spec.TTLSecondsAfterFinished != nil {
+		jobTTLSeconds = *checkConfig.Spec.TTLSecondsAfterFinished
+	}
+
+	// failed checker pods of a job are retried a default number of times unless the check specifies otherwise
+	jobBackoffLimit := defaultJobBackoffLimit
+	if checkConfig.Spec.BackoffLimit != nil {
+		jobBackoffLimit = *checkConfig.Spec.BackoffLimit
+	}
+
 	// build the checker object
 	log.Debugf("Creating external check from check config: %+v \n", checkConfig)
 	return &Checker{
@@ -141,9 +185,32 @@ func NewCheck(client *kubernetes.Clientset, checkConfig *khcheckv1.KuberhealthyC
 		ExtraLabels:              make(map[string]string),
 		OriginalPodSpec:          checkConfig.Spec.PodSpec,
 		PodSpec:                  checkConfig.Spec.PodSpec,
+		FailureThreshold:         checkConfig.Spec.FailureThreshold,
+		SuccessThreshold:         checkConfig.Spec.SuccessThreshold,
+		Suspend:                  checkConfig.Spec.Suspend,
+		SuspendUntil:             suspendUntil,
+		DependsOn:                DependencyKeys(checkConfig),
+		Retries:                  checkConfig.Spec.Retries,
+		RunAs:                    checkConfig.Spec.RunAs,
+		JobTTLSeconds:            jobTTLSeconds,
+		JobBackoffLimit:          jobBackoffLimit,
 		KubeClient:               client,
 		KHWorkload:               khstatev1.KHCheck,
+		runRequestChan:           make(chan struct{}, 1),
+	}
+}
+
+// DependencyKeys returns the namespace/name of the khchecks that a khcheck depends on.  Dependencies without a
+// namespace are in the namespace of the check.
+func DependencyKeys(checkConfig *khcheckv1.KuberhealthyCheck) []string {
+	dependsOn := make([]string, 0, len(checkConfig.Spec.DependsOn))
+	for _, dependency := range checkConfig.Spec.DependsOn {
+		if !strings.Contains(dependency, "/") {
+			dependency = checkConfig.Namespace + "/" + dependency
+		}
+		dependsOn = append(dependsOn, dependency)
 	}
+	return dependsOn
 }
 
 func NewJob(client *kubernetes.Clientset, jobConfig *khjobv1.KuberhealthyJob, khJobClient *khjobv1.KHJobV1Client, khStateClient *khstatev1.KHStateV1Client, reportingURL string) *Checker {
@@ -243,11 +310,18 @@ func (ext *Checker) Timeout() time.Duration {
 // the RunInterval and is executed by the Kuberhealthy checker
 func (ext *Checker) Run(ctx context.Context, client *kubernetes.Clientset) error {
 
+	// generate a new UUID for each run
+	return ext.RunWithUUID(ctx, client, uuid.New().String())
+}
+
+// RunWithUUID executes the checker like Run, but uses the supplied UUID for the run.  This is used for on demand
+// runs so that the requester can look up the result of the run by the UUID it was handed.
+func (ext *Checker) RunWithUUID(ctx context.Context, client *kubernetes.Clientset, runUUID string) error {
+
 	// store the client in the checker
 	ext.KubeClient = client
 
-	// generate a new UUID for each run
-	err := ext.setNewCheckUUID()
+	err := ext.setCheckUUID(runUUID)
 	if err != nil {
 		return err
 	}
@@ -305,10 +379,16 @@ func (ext *Checker) cleanup(ctx context.Context) {
 
 	// evict all checker pods not status=Failed or status=Succeeded. Check for existence of pods afterwards and if eviction failed, forcefully attempt to kill the pods
 	wg := sync.WaitGroup{}
+	jobs := make(map[string]bool)
 	for _, p := range podList.Items {
 		ext.log("finding pods that are not in status.phase=Failed or status.phase=Succeeded")
 		ext.log("pod:", p.Name, "is in status:", p.Status.Phase)
 		if p.Status.Phase == apiv1.PodPending || p.Status.Phase == apiv1.PodUnknown || p.Status.Phase == apiv1.PodRunning {
+			// the job controller would replace an evicted pod of a job, so the job is deleted instead
+			if owner := metav1.GetControllerOf(&p); owner != nil && owner.Kind == "Job" {
+				jobs[owner.Name] = true
+				continue
+			}
 			wg.Add(1)
 			go func(p apiv1.Pod) {
 				defer wg.Done()
@@ -320,6 +400,17 @@ func (ext *Checker) cleanup(ctx context.Context) {
 			}(p)
 		}
 	}
+	for jobName := range jobs {
+		wg.Add(1)
+		go func(jobName string) {
+			defer wg.Done()
+			ext.log("deleting job", jobName, "from namespace", ext.Namespace)
+			err := ext.deleteCheckerJob(ctx, jobName, 30)
+			if err != nil {
+				ext.log("error deleting job", jobName+":", err)
+			}
+		}(jobName)
+	}
 	wg.Wait()
 }
 
@@ -541,15 +632,41 @@ func (ext *Checker) newError(s string) error {
 
 // RunOnce runs one check loop.  This creates a checker pod and ensures it starts,
 // then ensures it changes to Running properly
-func (ext *Checker) RunOnce(ctx context.Context) error {
+func (ext *Checker) RunOnce(ctx context.Context) (err error) {
+
+	// trace the run, with a child span for each phase of the run.  a phase ends when the next one starts.
+	ctx, runSpan := ext.Tracer.Start(ctx, "khcheck run")
+	defer func() {
+		runSpan.RecordError(err)
+		runSpan.End()
+	}()
+	var phaseSpan *tracing.Span
+	startPhase := func(name string) {
+		phaseSpan.End()
+		_, phaseSpan = ext.Tracer.Start(ctx, name)
+	}
 
 	// create a context for this run
 	ext.shutdownCTX, ext.shutdownCTXFunc = context.WithCancel(ctx)
 	defer ext.shutdownCTXFunc()
-	defer ext.cleanup(ctx)
+	defer func() {
+		phaseSpan.RecordError(err)
+		phaseSpan.End()
+		_, cleanupSpan := ext.Tracer.Start(ctx, "cleanup")
+		ext.cleanup(ctx)
+		cleanupSpan.End()
+	}()
 
 	// regenerate the checker pod name with a new timestamp
 	ext.regeneratePodName()
+	runSpan.SetAttribute("kuberhealthy.check", ext.CheckName)
+	runSpan.SetAttribute("kuberhealthy.namespace", ext.Namespace)
+	runSpan.SetAttribute("kuberhealthy.run_uuid", ext.currentCheckUUID)
+	if ext.runsAsJob() {
+		runSpan.SetAttribute("kuberhealthy.job", ext.podName())
+	} else {
+		runSpan.SetAttribute("kuberhealthy.pod", ext.podName())
+	}
 
 	// fetch the currently known lastReportTime for this check.  We will use this to know when the pod has
 	// fully reported back with a status before exiting
@@ -612,16 +729,27 @@ func (ext *Checker) RunOnce(ctx context.Context) error {
 	defer podShutdownWatchCtxCancel()
 
 	// Spawn kubernetes pod to run our external check
+	startPhase("create pod")
 	ext.log("creating pod for external check:", ext.CheckName)
 	ext.log("checker pod annotations and labels:", ext.ExtraAnnotations, ext.ExtraLabels)
-	createdPod, err := ext.createPod(ctx)
-	if err != nil {
-		ext.log("error creating pod")
-		return ext.newError("failed to create pod for checker: " + err.Error())
+	if ext.runsAsJob() {
+		createdJob, err := ext.createJob(ctx)
+		if err != nil {
+			ext.log("error creating job")
+			return ext.newError("failed to create job for checker: " + err.Error())
+		}
+		ext.log("Check", ext.Name(), "created job", createdJob.Name, "in namespace", createdJob.Namespace)
+	} else {
+		createdPod, err := ext.createPod(ctx)
+		if err != nil {
+			ext.log("error creating pod")
+			return ext.newError("failed to create pod for checker: " + err.Error())
+		}
+		ext.log("Check", ext.Name(), "created pod", createdPod.Name, "in namespace", createdPod.Namespace)
 	}
-	ext.log("Check", ext.Name(), "created pod", createdPod.Name, "in namespace", createdPod.Namespace)
 
 	// watch for pod to start with a timeout (include time for a new node to be created)
+	startPhase("wait for pod start")
 	select {
 	case <-timeoutChan: // were out of time
 		ext.log("timed out waiting for pod to startup")
@@ -643,12 +771,23 @@ func (ext *Checker) RunOnce(ctx context.Context) error {
 		}
 		// flag the pod as running until this run ends
 		ext.log("External check pod is running:", ext.podName())
+
+		// the pod of a job is named by the job controller, so it is only known once it has started
+		if ext.runsAsJob() {
+			p, err := ext.getCheckerPod(ctx)
+			if err != nil {
+				ext.log("error looking up the checker pod of job", ext.podName()+":", err)
+			} else {
+				runSpan.SetAttribute("kuberhealthy.pod", p.Name)
+			}
+		}
 	case <-ext.shutdownCTX.Done(): // shutdown signal
 		ext.log("shutting down check. aborting watch for pod to start")
 		return nil
 	}
 
 	// validate that the pod was able to update its khstate
+	startPhase("wait for report")
 	ext.log("Waiting for pod status to be reported from pod", ext.podName(), "in namespace", ext.Namespace)
 	select {
 	case <-timeoutChan: // out of time
@@ -680,6 +819,7 @@ func (ext *Checker) RunOnce(ctx context.Context) error {
 	podShutdownWatchCtxCancel()
 
 	// validate that the pod stopped running properly (wait for the pod to exit)
+	startPhase("wait for pod exit")
 	select {
 	case <-timeoutChan: // out of time
 		errorMessage := "timed out waiting for pod to exit"
@@ -821,9 +961,6 @@ func (ext *Checker) waitForAllPodsToClear(ctx context.Context) chan error {
 	// make the output channel we will return and close it whenever we are done
 	outChan := make(chan error, 2)
 
-	// setup a pod watching client for our current KH pod
-	podClient := ext.KubeClient.CoreV1().Pods(ext.Namespace)
-
 	ext.wg.Add(1)
 	go func() {
 
@@ -844,8 +981,8 @@ func (ext *Checker) waitForAllPodsToClear(ctx context.Context) chan error {
 			default:
 			}
 
-			// fetch the pod by name
-			p, err := podClient.Get(ctx, ext.podName(), metav1.GetOptions{})
+			// fetch the pod
+			p, err := ext.getCheckerPod(ctx)
 
 			// if we got a "not found" message, then we are done.  This is the happy path.
 			if err != nil {
@@ -1060,6 +1197,16 @@ func (ext *Checker) validatePodSpec() error {
 // createPod prepares and creates the checker pod using the kubernetes API
 func (ext *Checker) createPod(ctx context.Context) (*apiv1.Pod, error) {
 	ext.log("Creating external checker pod named", ext.podName())
+	p, err := ext.checkerPod()
+	if err != nil {
+		return nil, err
+	}
+
+	return ext.KubeClient.CoreV1().Pods(ext.Namespace).Create(ctx, p, metav1.CreateOptions{})
+}
+
+// checkerPod prepares the checker pod with the labels, annotations and owner reference of this check
+func (ext *Checker) checkerPod() (*apiv1.Pod, error) {
 	p := &apiv1.Pod{}
 	p.Annotations = make(map[string]string)
 	p.Labels = make(map[string]string)
@@ -1084,7 +1231,7 @@ func (ext *Checker) createPod(ctx context.Context) (*apiv1.Pod, error) {
 		p.OwnerReferences = ownerRef
 	}
 
-	return ext.KubeClient.CoreV1().Pods(ext.Namespace).Create(ctx, p, metav1.CreateOptions{})
+	return p, nil
 }
 
 // configureUserPodSpec configures a user-specified pod spec with
@@ -1108,6 +1255,10 @@ func (ext *Checker) configureUserPodSpec(deadline time.Time) error {
 			Name:  KHRunUUID,
 			Value: ext.currentCheckUUID,
 		},
+		{
+			Name:  KHRunKey,
+			Value: ext.currentRunKey,
+		},
 		{
 			Name:  KHDeadline,
 			Value: strconv.FormatInt(deadline.Unix(), 10),
@@ -1124,7 +1275,7 @@ func (ext *Checker) configureUserPodSpec(deadline time.Time) error {
 
 	// apply overwrite env vars on every container in the pod
 	for i := range ext.PodSpec.Containers {
-		ext.PodSpec.Containers[i].Env = resetInjectedContainerEnvVars(ext.PodSpec.Containers[i].Env, []string{KHReportingURL, KHRunUUID, KHPodNamespace, KHDeadline})
+		ext.PodSpec.Containers[i].Env = resetInjectedContainerEnvVars(ext.PodSpec.Containers[i].Env, []string{KHReportingURL, KHRunUUID, KHRunKey, KHPodNamespace, KHDeadline})
 		ext.PodSpec.Containers[i].Env = append(ext.PodSpec.Containers[i].Env, overwriteEnvVars...)
 	}
 
@@ -1173,11 +1324,17 @@ func (ext *Checker) addKuberhealthyLabels(pod *apiv1.Pod) {
 
 }
 
-// createCheckUUID creates a UUID that represents a single run of the external check
-func (ext *Checker) setNewCheckUUID() error {
-	uniqueID := uuid.New()
-	ext.currentCheckUUID = uniqueID.String()
-	log.Debugln("Generated new UUID for external check:", ext.currentCheckUUID)
+// setCheckUUID sets the UUID that represents a single run of the external check
+func (ext *Checker) setCheckUUID(runUUID string) error {
+	ext.currentCheckUUID = runUUID
+	log.Debugln("Using UUID for external check run:", ext.currentCheckUUID)
+
+	// make a new key for the checker pod of this run to sign its report with
+	runKey, err := NewRunKey()
+	if err != nil {
+		return errors.New("failed to generate run key: " + err.Error())
+	}
+	ext.currentRunKey = runKey
 
 	// set whitelist in check configuration CRD so only this
 	// currently running pod can report-in with a status update
@@ -1195,7 +1352,7 @@ func (ext *Checker) waitForShutdown(ctx context.Context) chan error {
 	go func() {
 		for {
 			time.Sleep(time.Second * 5)
-			exists, err := util.PodNameExists(ext.KubeClient, ext.checkPodName, ext.Namespace)
+			exists, err := ext.checkerPodExists(ctx)
 			if err != nil {
 				ext.log("shutdown completed with error: ", err)
 				doneChan <- err
@@ -1244,7 +1401,7 @@ func (ext *Checker) Shutdown() error {
 		ext.log("Check using pod " + ext.podName() + " successfully shutdown.")
 	case <-time.After(defaultShutdownGracePeriod):
 		ext.log("Reached timeout:", defaultShutdownGracePeriod, "trying to shutdown pod:", ext.podName(), "Killing pod forcefully.")
-		err := util.PodKill(ext.KubeClient, ext.podName(), ext.Namespace, 0)
+		err := ext.killCheckerPod(ctx, 0)
 		if err != nil {
 			ext.log("Error force killing pod: ", ext.podName(), " Error:", err)
 			return err
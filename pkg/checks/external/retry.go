@@ -0,0 +1,118 @@
+package external
+
+import (
+	"context"
+	"errors"
+	"time"
+
+	log "github.com/sirupsen/logrus"
+	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
+	"k8s.io/client-go/kubernetes"
+)
+
+// DefaultRetryBackoff is the wait before the first retry of a run when a check does not specify a retryBackoff
+const DefaultRetryBackoff = time.Second * 10
+
+// maxRetryBackoff is the longest wait between two attempts of a run
+const maxRetryBackoff = time.Minute * 5
+
+// RunSlotFunc waits until a run attempt may start and returns a func that must be called when the attempt is done
+type RunSlotFunc func(ctx context.Context) (func(), error)
+
+// RunWithRetries runs the checker like RunWithUUID, but retries a run that fails to execute up to Retries more
+// times before returning its error.  A run is not retried once its checker pod has reported in, because the result
+// of the check was already recorded, or when its pod was removed expectedly.  Every attempt uses the same run UUID
+// so that an on demand run can still be found by the UUID handed to its requester.  Each attempt waits for its own
+// slot from acquire and gives it back when it ends, so that a run waiting to be retried does not hold a slot.  The
+// number of attempts made is returned along with the error of the last attempt, or with the error of acquire when
+// it fails.
+func (ext *Checker) RunWithRetries(ctx context.Context, client *kubernetes.Clientset, runUUID string, acquire RunSlotFunc) (int, error) {
+	attempts := 0
+	for {
+		release, err := acquire(ctx)
+		if err != nil {
+			return attempts, err
+		}
+		attempts++
+
+		// remember when the check last reported in so we know if this attempt reported before it failed
+		lastReportTime, err := ext.getCheckLastUpdateTime()
+		if err != nil {
+			ext.log("error fetching last report time before run attempt", attempts, err)
+		}
+
+		err = ext.RunWithUUID(ctx, client, runUUID)
+		release()
+		if !ext.retryable(attempts, err) {
+			return attempts, err
+		}
+		reported, reportErr := ext.podHasReportedInAfterTime(lastReportTime)
+		if reportErr == nil && reported {
+			ext.log("not retrying run attempt", attempts, "because the checker pod reported in before it failed:", err)
+			return attempts, err
+		}
+
+		backoff := ext.retryBackoff(attempts)
+		log.Warningln("Run attempt", attempts, "of", ext.Retries+1, "of check", ext.Name(), "in namespace", ext.CheckNamespace(), "failed. Retrying in", backoff.String()+":", err)
+		select {
+		case <-ctx.Done():
+			return attempts, err
+		case <-time.After(backoff):
+		}
+
+		// the next attempt watches pods by the same run UUID, so the pods left by this attempt are removed first
+		ext.clearRunPods(ctx, runUUID)
+	}
+}
+
+// retryable indicates if a run attempt that ended with err should be retried
+func (ext *Checker) retryable(attempts int, err error) bool {
+	if err == nil || attempts > ext.Retries {
+		return false
+	}
+	return !errors.Is(err, ErrPodRemovedExpectedly)
+}
+
+// retryBackoff returns the wait after the supplied number of failed attempts.  The wait starts at RetryBackoff and
+// doubles after each attempt, up to maxRetryBackoff.
+func (ext *Checker) retryBackoff(attempts int) time.Duration {
+	backoff := ext.RetryBackoff
+	if backoff <= 0 {
+		backoff = DefaultRetryBackoff
+	}
+	for i := 1; i < attempts && backoff < maxRetryBackoff; i++ {
+		backoff *= 2
+	}
+	if backoff > maxRetryBackoff {
+		backoff = maxRetryBackoff
+	}
+	return backoff
+}
+
+// clearRunPods deletes the checker pods, and the jobs of checks that run as a Job, left by a run attempt
+func (ext *Checker) clearRunPods(ctx context.Context, runUUID string) {
+	listOptions := metav1.ListOptions{
+		LabelSelector: kuberhealthyRunIDLabel + "=" + runUUID,
+	}
+
+	if ext.runsAsJob() {
+		jobClient := ext.KubeClient.BatchV1().Jobs(ext.Namespace)
+		jobs, err := jobClient.List(ctx, listOptions)
+		if err != nil {
+			ext.log("error listing jobs of failed run attempt:", err)
+		} else {
+			propagationBackground := metav1.DeletePropagationBackground
+			for _, j := range jobs.Items {
+				err = jobClient.Delete(ctx, j.Name, metav1.DeleteOptions{PropagationPolicy: &propagationBackground})
+				if err != nil {
+					ext.log("error deleting job", j.Name, "of failed run attempt:", err)
+				}
+			}
+		}
+	}
+
+	err := ext.KubeClient.CoreV1().Pods(ext.Namespace).DeleteCollection(ctx, metav1.DeleteOptions{}, listOptions)
+	if err != nil {
+		ext.log("error deleting pods of failed run attempt:", err)
+	}
+}
@@ -0,0 +1,84 @@
+package external
+
+import (
+	"context"
+	"errors"
+	"testing"
+	"time"
+)
+
+// TestRetryable tests which failed run attempts are retried
+func TestRetryable(t *testing.T) {
+	t.Parallel()
+
+	c := &Checker{Retries: 2}
+	runErr := errors.New("failed to see pod running within timeout")
+
+	var testCases = []struct {
+		description string
+		attempts    int
+		err         error
+		expected    bool
+	}{
+		{"a successful run is not retried", 1, nil, false},
+		{"a failed first attempt is retried", 1, runErr, true},
+		{"a failed last retry is retried", 2, runErr, true},
+		{"a run is not retried after all retries are used", 3, runErr, false},
+		{"a run with a pod removed expectedly is not retried", 1, ErrPodRemovedExpectedly, false},
+	}
+
+	for _, tc := range testCases {
+		if retry := c.retryable(tc.attempts, tc.err); retry != tc.expected {
+			t.Fatalf("%s: expected retryable to be %t, got %t", tc.description, tc.expected, retry)
+		}
+	}
+
+	c.Retries = 0
+	if c.retryable(1, runErr) {
+		t.Fatal("expected a check without retries to not retry failed runs")
+	}
+}
+
+// TestRetryBackoff tests that the wait between attempts doubles up to the maximum
+func TestRetryBackoff(t *testing.T) {
+	t.Parallel()
+
+	c := &Checker{RetryBackoff: time.Second * 30}
+
+	var testCases = []struct {
+		attempts int
+		expected time.Duration
+	}{
+		{1, time.Second * 30},
+		{2, time.Minute},
+		{3, time.Minute * 2},
+		{4, time.Minute * 4},
+		{5, maxRetryBackoff},
+		{50, maxRetryBackoff},
+	}
+
+	for _, tc := range testCases {
+		if backoff := c.retryBackoff(tc.attempts); backoff != tc.expected {
+			t.Fatalf("expected a backoff of %s after %d attempts, got %s", tc.expected, tc.attempts, backoff)
+		}
+	}
+
+	c.RetryBackoff = 0
+	if backoff := c.retryBackoff(1); backoff != DefaultRetryBackoff {
+		t.Fatalf("expected the default backoff of %s, got %s", DefaultRetryBackoff, backoff)
+	}
+}
+
+// TestRunWithRetriesAcquireError tests that no attempt is made when a run slot can not be acquired
+func TestRunWithRetriesAcquireError(t *testing.T) {
+	t.Parallel()
+
+	c := &Checker{Retries: 2}
+	acquireErr := errors.New("context canceled")
+	attempts, err := c.RunWithRetries(context.Background(), nil, "run-uuid", func(ctx context.Context) (func(), error) {
+		return nil, acquireErr
+	})
+	if attempts != 0 || err != acquireErr {
+		t.Fatalf("expected no attempts and the acquire error, got %d attempts and %v", attempts, err)
+	}
+}
@@ -0,0 +1,48 @@
+package external
+
+import (
+	"github.com/google/uuid"
+)
+
+// RequestRun requests an on demand run of the check with the supplied UUID and returns the UUID the run will
+// use.  A new UUID is generated when the supplied one is not a valid UUID.  Only one on demand run is pending at a
+// time, so when a run has already been requested and not yet started, the UUID of that run is returned instead.
+func (ext *Checker) RequestRun(runUUID string) string {
+	ext.runRequestMu.Lock()
+	defer ext.runRequestMu.Unlock()
+
+	if len(ext.requestedRunUUID) > 0 {
+		return ext.requestedRunUUID
+	}
+
+	_, err := uuid.Parse(runUUID)
+	if err != nil {
+		runUUID = uuid.New().String()
+	}
+	ext.requestedRunUUID = runUUID
+
+	// notify the runner without blocking.  the channel is buffered, so a notification is only dropped when one
+	// is already waiting to be picked up.
+	select {
+	case ext.runRequestChan <- struct{}{}:
+	default:
+	}
+
+	return runUUID
+}
+
+// RunRequests returns a channel that is notified when an on demand run of the check has been requested
+func (ext *Checker) RunRequests() <-chan struct{} {
+	return ext.runRequestChan
+}
+
+// TakeRunRequest returns the UUID of the pending on demand run and clears it so that another run can be
+// requested.  A blank string is returned when no run is pending.
+func (ext *Checker) TakeRunRequest() string {
+	ext.runRequestMu.Lock()
+	defer ext.runRequestMu.Unlock()
+
+	runUUID := ext.requestedRunUUID
+	ext.requestedRunUUID = ""
+	return runUUID
+}
@@ -0,0 +1,48 @@
+package external
+
+import (
+	"testing"
+
+	"github.com/google/uuid"
+)
+
+// TestRequestRun tests that on demand run requests are coalesced until the pending run is taken
+func TestRequestRun(t *testing.T) {
+	t.Parallel()
+
+	c := &Checker{runRequestChan: make(chan struct{}, 1)}
+
+	requested := uuid.New().String()
+	if runUUID := c.RequestRun(requested); runUUID != requested {
+		t.Fatalf("expected the requested uuid %s to be used for the run, got %s", requested, runUUID)
+	}
+	select {
+	case <-c.RunRequests():
+	default:
+		t.Fatal("expected the run request channel to be notified")
+	}
+
+	// a second request while one is pending should get the pending run's uuid
+	if runUUID := c.RequestRun(uuid.New().String()); runUUID != requested {
+		t.Fatalf("expected a second request to be given the pending uuid %s, got %s", requested, runUUID)
+	}
+	select {
+	case <-c.RunRequests():
+		t.Fatal("expected no notification for a request that was coalesced with a pending run")
+	default:
+	}
+
+	if runUUID := c.TakeRunRequest(); runUUID != requested {
+		t.Fatalf("expected to take the pending uuid %s, got %s", requested, runUUID)
+	}
+	if runUUID := c.TakeRunRequest(); runUUID != "" {
+		t.Fatalf("expected no pending run after it was taken, got %s", runUUID)
+	}
+
+	// invalid uuids are replaced with a generated one
+	runUUID := c.RequestRun("now")
+	_, err := uuid.Parse(runUUID)
+	if err != nil {
+		t.Fatalf("expected a generated uuid for an invalid requested uuid, got %s", runUUID)
+	}
+}
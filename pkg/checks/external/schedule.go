@@ -0,0 +1,78 @@
+package external
+
+import (
+	"errors"
+	"math/rand"
+	"time"
+
+	"github.com/gorhill/cronexpr"
+	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
+
+	khcheckv1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khcheck/v1"
+)
+
+// ParseSchedule parses the cron schedule of a check.  Schedules that never match, such as one for the 30th of
+// February, are rejected as they would never run the check.
+func ParseSchedule(schedule string, now time.Time) (*cronexpr.Expression, error) {
+	expression, err := cronexpr.Parse(schedule)
+	if err != nil {
+		return nil, err
+	}
+	if expression.Next(now).IsZero() {
+		return nil, errors.New("schedule " + schedule + " never matches")
+	}
+	return expression, nil
+}
+
+// NextRunTime calculates when this check is next scheduled to run.  Checks with a cron schedule run at the next
+// scheduled time after now.  Otherwise, checks run one RunInterval after their last scheduled run, or right away if
+// that time has already passed.  A zero lastScheduledRun indicates the check has not run yet.  Checks that are
+// suspended until a time do not run before that time.  A zero time is returned when the check has no upcoming run.
+// Jitter is not included, so the result can be fed back in as lastScheduledRun without runs drifting.
+func (ext *Checker) NextRunTime(lastScheduledRun time.Time, now time.Time) time.Time {
+
+	next := lastScheduledRun.Add(ext.RunInterval)
+	if ext.RunSchedule != nil {
+		next = ext.RunSchedule.Next(now)
+	}
+
+	// resume suspended checks as soon as their suspension ends
+	if ext.SuspendUntil.After(next) {
+		next = ext.SuspendUntil
+		if ext.RunSchedule != nil {
+			next = ext.RunSchedule.Next(ext.SuspendUntil)
+		}
+	}
+
+	// cron schedules return a zero time when they never match again
+	if ext.RunSchedule != nil && next.IsZero() {
+		return next
+	}
+
+	// never schedule a run in the past
+	if next.Before(now) {
+		next = now
+	}
+
+	return next
+}
+
+// Jitter returns a random delay of up to RunJitter to add to the scheduled run time so that many checks do not all
+// start at once
+func (ext *Checker) Jitter() time.Duration {
+	if ext.RunJitter <= 0 {
+		return 0
+	}
+	return time.Duration(rand.Int63n(int64(ext.RunJitter)))
+}
+
+// Suspended indicates if the check is suspended at the supplied time.  The khcheck spec decides how Suspend and
+// SuspendUntil take precedence over each other, so that the check and its khcheck always agree.
+func (ext *Checker) Suspended(now time.Time) bool {
+	spec := khcheckv1.CheckConfig{Suspend: ext.Suspend}
+	if !ext.SuspendUntil.IsZero() {
+		suspendUntil := metav1.NewTime(ext.SuspendUntil)
+		spec.SuspendUntil = &suspendUntil
+	}
+	return spec.IsSuspended(now)
+}
@@ -0,0 +1,134 @@
+package external
+
+import (
+	"testing"
+	"time"
+
+	"github.com/gorhill/cronexpr"
+)
+
+// TestNextRunTime tests run time calculation for interval and cron scheduled checks
+func TestNextRunTime(t *testing.T) {
+	t.Parallel()
+
+	now := time.Date(2020, time.January, 1, 12, 30, 0, 0, time.UTC)
+
+	// interval checks that have never run should start right away
+	c := &Checker{RunInterval: time.Minute * 10}
+	if next := c.NextRunTime(time.Time{}, now); !next.Equal(now) {
+		t.Fatal("Expected first run of interval check at", now, "but got", next)
+	}
+
+	// interval checks run one interval after their last start
+	lastRun := now.Add(-time.Minute)
+	if next := c.NextRunTime(lastRun, now); !next.Equal(lastRun.Add(time.Minute * 10)) {
+		t.Fatal("Expected next run of interval check at", lastRun.Add(time.Minute*10), "but got", next)
+	}
+
+	// cron checks wait for their next scheduled time, even on their first run
+	c.RunSchedule = cronexpr.MustParse("0 2 * * *")
+	expected := time.Date(2020, time.January, 2, 2, 0, 0, 0, time.UTC)
+	if next := c.NextRunTime(time.Time{}, now); !next.Equal(expected) {
+		t.Fatal("Expected next run of cron check at", expected, "but got", next)
+	}
+
+	// cron checks whose schedule never matches again have no next run
+	c.RunSchedule = cronexpr.MustParse("0 0 30 2 *")
+	if next := c.NextRunTime(time.Time{}, now); !next.IsZero() {
+		t.Fatal("Expected no next run of a cron check that never matches but got", next)
+	}
+}
+
+// TestJitter tests that jitter delays runs by up to the jitter duration without moving the schedule
+func TestJitter(t *testing.T) {
+	t.Parallel()
+
+	now := time.Date(2020, time.January, 1, 12, 30, 0, 0, time.UTC)
+
+	c := &Checker{RunInterval: time.Minute * 10}
+	if jitter := c.Jitter(); jitter != 0 {
+		t.Fatal("Expected no jitter for a check without a jitter duration but got", jitter)
+	}
+
+	c.RunJitter = time.Minute
+	for i := 0; i < 100; i++ {
+		jitter := c.Jitter()
+		if jitter < 0 || jitter >= c.RunJitter {
+			t.Fatal("Expected jitter between 0 and", c.RunJitter, "but got", jitter)
+		}
+	}
+
+	// the next run is scheduled from the last scheduled run, so jitter never accumulates across runs
+	lastScheduledRun := now.Add(-time.Minute)
+	if next := c.NextRunTime(lastScheduledRun, now); !next.Equal(lastScheduledRun.Add(c.RunInterval)) {
+		t.Fatal("Expected next run of jittered check at", lastScheduledRun.Add(c.RunInterval), "but got", next)
+	}
+}
+
+// TestParseSchedule tests that schedules which never match are rejected
+func TestParseSchedule(t *testing.T) {
+	t.Parallel()
+
+	now := time.Date(2020, time.January, 1, 12, 30, 0, 0, time.UTC)
+
+	for _, schedule := range []string{"0 0 30 2 *", "not a schedule"} {
+		_, err := ParseSchedule(schedule, now)
+		if err == nil {
+			t.Fatal("Expected an error parsing schedule", schedule)
+		}
+	}
+
+	expression, err := ParseSchedule("*/15 * * * *", now)
+	if err != nil {
+		t.Fatal("Error parsing schedule:", err)
+	}
+	if expression == nil {
+		t.Fatal("Expected a parsed schedule")
+	}
+}
+
+// TestSuspendedNextRunTime tests that checks suspended until a time do not run before it
+func TestSuspendedNextRunTime(t *testing.T) {
+	t.Parallel()
+
+	now := time.Date(2020, time.January, 1, 12, 30, 0, 0, time.UTC)
+	suspendUntil := now.Add(time.Hour)
+
+	c := &Checker{RunInterval: time.Minute * 10, SuspendUntil: suspendUntil}
+	if next := c.NextRunTime(now.Add(-time.Minute), now); !next.Equal(suspendUntil) {
+		t.Fatal("Expected suspended interval check to resume at", suspendUntil, "but got", next)
+	}
+
+	c.RunSchedule = cronexpr.MustParse("0 * * * *")
+	expected := time.Date(2020, time.January, 1, 14, 0, 0, 0, time.UTC)
+	if next := c.NextRunTime(time.Time{}, now); !next.Equal(expected) {
+		t.Fatal("Expected suspended cron check to resume at", expected, "but got", next)
+	}
+}
+
+// TestSuspended tests when checks are considered suspended
+func TestSuspended(t *testing.T) {
+	t.Parallel()
+
+	now := time.Date(2020, time.January, 1, 12, 30, 0, 0, time.UTC)
+
+	c := &Checker{}
+	if c.Suspended(now) {
+		t.Fatal("Expected check without suspend settings to not be suspended")
+	}
+
+	c.Suspend = true
+	if !c.Suspended(now) {
+		t.Fatal("Expected check with suspend set to be suspended")
+	}
+
+	c.SuspendUntil = now.Add(time.Minute)
+	if !c.Suspended(now) {
+		t.Fatal("Expected check to be suspended before its suspendUntil time")
+	}
+
+	c.SuspendUntil = now.Add(-time.Minute)
+	if c.Suspended(now) {
+		t.Fatal("Expected check to resume after its suspendUntil time")
+	}
+}
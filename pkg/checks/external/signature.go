@@ -0,0 +1,45 @@
+package external
+
+import (
+	"crypto/hmac"
+	"crypto/rand"
+	"crypto/sha256"
+	"encoding/hex"
+)
+
+// ReportSignatureHeader is the header that carries the signature of an external check report
+const ReportSignatureHeader = "kh-signature"
+
+// runKeyLength is the number of random bytes in a run key
+const runKeyLength = 32
+
+// NewRunKey generates a random key for a single run of an external check.  The key is hex encoded so that it can be
+// passed to the checker pod as an environment variable and used as an HMAC key by clients in any language.
+func NewRunKey() (string, error) {
+	b := make([]byte, runKeyLength)
+	_, err := rand.Read(b)
+	if err != nil {
+		return "", err
+	}
+	return hex.EncodeToString(b), nil
+}
+
+// SignReport returns the hex encoded HMAC-SHA256 of a report body, keyed with the run key as a string
+func SignReport(runKey string, body []byte) string {
+	mac := hmac.New(sha256.New, []byte(runKey))
+	mac.Write(body)
+	return hex.EncodeToString(mac.Sum(nil))
+}
+
+// VerifyReportSignature checks that a signature was made with the run key over the report body
+func VerifyReportSignature(runKey string, body []byte, signature string) bool {
+	expected, err := hex.DecodeString(SignReport(runKey, body))
+	if err != nil {
+		return false
+	}
+	actual, err := hex.DecodeString(signature)
+	if err != nil {
+		return false
+	}
+	return hmac.Equal(expected, actual)
+}
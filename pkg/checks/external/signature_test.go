@@ -0,0 +1,36 @@
+package external
+
+import (
+	"testing"
+)
+
+// TestReportSignature tests that reports signed with a run key can only be verified with the same key and body
+func TestReportSignature(t *testing.T) {
+	key, err := NewRunKey()
+	if err != nil {
+		t.Fatal("Error generating run key:", err)
+	}
+	otherKey, err := NewRunKey()
+	if err != nil {
+		t.Fatal("Error generating run key:", err)
+	}
+	if key == otherKey {
+		t.Fatal("Expected run keys to be unique")
+	}
+
+	body := []byte(`{"OK":true,"Errors":[]}`)
+	signature := SignReport(key, body)
+
+	if !VerifyReportSignature(key, body, signature) {
+		t.Fatal("Expected signature to verify with the key it was signed with")
+	}
+	if VerifyReportSignature(otherKey, body, signature) {
+		t.Fatal("Expected signature not to verify with another run key")
+	}
+	if VerifyReportSignature(key, []byte(`{"OK":false,"Errors":["tampered"]}`), signature) {
+		t.Fatal("Expected signature not to verify with a changed body")
+	}
+	if VerifyReportSignature(key, body, "not-hex") {
+		t.Fatal("Expected a malformed signature not to verify")
+	}
+}
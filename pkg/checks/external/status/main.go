@@ -3,10 +3,31 @@
 // status reporting endpoint.
 package status
 
+// The severities that a check can report.  When the severity is left blank, it is
+// derived from the OK state of the report.
+const (
+	SeverityOK      = "OK"
+	SeverityWarning = "Warning"
+	SeverityFailing = "Failing"
+)
+
 // Report is the format expected by the /externalCheckStatus endpoint
 type Report struct {
-	Errors []string
-	OK     bool
+	Errors   []string
+	OK       bool
+	Warnings []string `json:",omitempty"` // non-fatal problems found during the check run
+	Metrics  []Metric `json:",omitempty"` // numeric measurements taken during the check run
+	Severity string   `json:",omitempty"` // the severity of the check run: OK, Warning or Failing
+}
+
+// Metric is a single numeric measurement taken by a check during its run, such
+// as a request latency or a download size.  Metrics are stored on the check's
+// khstate and exposed by Kuberhealthy as gauges.
+type Metric struct {
+	Name   string            `json:"Name"`       // the name of the metric, such as request_duration
+	Value  float64           `json:"Value"`      // the measured value
+	Unit   string            `json:",omitempty"` // the unit of the value, such as seconds or bytes
+	Labels map[string]string `json:",omitempty"` // extra labels that describe the measurement
 }
 
 // NewReport creates a new error report to be sent to the server.  If
@@ -24,3 +45,31 @@ func NewReport(errorMessages []string) Report {
 		OK:     ok,
 	}
 }
+
+// NewWarningReport creates a new report for a check that found a problem that is
+// not severe enough to fail.  The report is OK, but is shown as degraded by the
+// server with the supplied warning messages.
+func NewWarningReport(warningMessages []string) Report {
+	return Report{
+		Errors:   []string{},
+		OK:       true,
+		Warnings: warningMessages,
+		Severity: SeverityWarning,
+	}
+}
+
+// AddMetric adds a numeric measurement to the report
+func (r *Report) AddMetric(name string, value float64, unit string, labels map[string]string) {
+	r.Metrics = append(r.Metrics, Metric{
+		Name:   name,
+		Value:  value,
+		Unit:   unit,
+		Labels: labels,
+	})
+}
+
+// AddWarning adds non-fatal warning messages to the report.  Warnings do not
+// change the OK state of the report.
+func (r *Report) AddWarning(warningMessages ...string) {
+	r.Warnings = append(r.Warnings, warningMessages...)
+}
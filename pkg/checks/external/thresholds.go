@@ -0,0 +1,67 @@
+package external
+
+import (
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
+)
+
+// ApplyRunThresholds sets the OK state shown for the check on details from the result of its latest run in
+// details.LastRunResult.  A check shown as OK is only shown as failing after FailureThreshold consecutive failed
+// runs, and a failing check is only shown as OK again after SuccessThreshold consecutive successful runs.  Until
+// then, the state shown in previous is kept.  previous holds the state of the check from before the latest run.
+// When previous has no counted runs, such as for a new check or a khstate written before runs were counted, the
+// state it shows is unknown and the result of the latest run is shown right away.
+func (ext *Checker) ApplyRunThresholds(previous khstatev1.WorkloadDetails, details *khstatev1.WorkloadDetails) {
+	result := details.LastRunResult
+	if result == nil {
+		return
+	}
+
+	// count how many runs in a row had the same result as this one
+	details.ConsecutiveFailures = 0
+	details.ConsecutiveSuccesses = 0
+	if result.OK {
+		details.ConsecutiveSuccesses = previous.ConsecutiveSuccesses + 1
+	} else {
+		details.ConsecutiveFailures = previous.ConsecutiveFailures + 1
+	}
+
+	// the shown state of checks without counted runs is unknown, so there is nothing to hold on to
+	previousOK := previous.OK
+	showResult := result.OK == previousOK
+	if previous.ConsecutiveFailures == 0 && previous.ConsecutiveSuccesses == 0 {
+		showResult = true
+	}
+	if result.OK && details.ConsecutiveSuccesses >= thresholdOrDefault(ext.SuccessThreshold) {
+		showResult = true
+	}
+	if !result.OK && details.ConsecutiveFailures >= thresholdOrDefault(ext.FailureThreshold) {
+		showResult = true
+	}
+
+	if showResult {
+		details.OK = result.OK
+		details.Errors = result.Errors
+		details.Severity = result.Severity
+	} else {
+		ext.log("keeping OK state of", previousOK, "after", details.ConsecutiveFailures, "consecutive failures and", details.ConsecutiveSuccesses, "consecutive successes")
+		details.OK = previousOK
+		details.Errors = previous.Errors
+		details.Severity = previous.Severity
+		if previousOK {
+			details.Errors = []string{}
+		}
+	}
+
+	if details.Errors == nil {
+		details.Errors = []string{}
+	}
+}
+
+// thresholdOrDefault returns the supplied threshold of consecutive runs, or a threshold of a single run if it
+// was not set
+func thresholdOrDefault(threshold int) int {
+	if threshold < 1 {
+		return 1
+	}
+	return threshold
+}
@@ -0,0 +1,75 @@
+package external
+
+import (
+	"testing"
+
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
+)
+
+// TestApplyRunThresholds tests that the shown state of a check only changes after enough consecutive runs
+func TestApplyRunThresholds(t *testing.T) {
+	t.Parallel()
+
+	c := &Checker{FailureThreshold: 3, SuccessThreshold: 2}
+	failedRun := &khstatev1.RunResult{OK: false, Errors: []string{"failed"}}
+	successfulRun := &khstatev1.RunResult{OK: true}
+
+	// run applies a run result on top of the previous state and returns the new state
+	run := func(previous khstatev1.WorkloadDetails, result *khstatev1.RunResult) khstatev1.WorkloadDetails {
+		details := khstatev1.WorkloadDetails{LastRunResult: result}
+		c.ApplyRunThresholds(previous, &details)
+		return details
+	}
+
+	// the state of a check without counted runs is unknown, so its first run is shown right away, even when a
+	// khstate written before runs were counted shows it as OK
+	for _, previous := range []khstatev1.WorkloadDetails{{}, {OK: true}} {
+		state := run(previous, failedRun)
+		if state.OK || len(state.Errors) != 1 || state.ConsecutiveFailures != 1 {
+			t.Fatal("Expected check without counted runs to show its first failed run but got", state.OK, state.Errors, state.ConsecutiveFailures)
+		}
+	}
+	state := run(khstatev1.WorkloadDetails{OK: false, Errors: []string{"failed"}}, successfulRun)
+	if !state.OK || len(state.Errors) != 0 {
+		t.Fatal("Expected failing check without counted runs to show its first successful run but got", state.OK, state.Errors)
+	}
+
+	// an OK check stays OK until it fails three times in a row
+	for i := 1; i < 3; i++ {
+		state = run(state, failedRun)
+		if !state.OK || len(state.Errors) != 0 {
+			t.Fatal("Expected check to stay OK after", i, "failed runs but got", state.OK, state.Errors)
+		}
+		if state.ConsecutiveFailures != i {
+			t.Fatal("Expected", i, "consecutive failures but got", state.ConsecutiveFailures)
+		}
+	}
+	state = run(state, failedRun)
+	if state.OK || len(state.Errors) != 1 {
+		t.Fatal("Expected check to be failing after 3 failed runs but got", state.OK, state.Errors)
+	}
+
+	// a failing check stays failing until it succeeds twice in a row
+	state = run(state, successfulRun)
+	if state.OK || state.ConsecutiveSuccesses != 1 || state.ConsecutiveFailures != 0 {
+		t.Fatal("Expected check to stay failing after 1 successful run but got", state.OK, state.ConsecutiveSuccesses, state.ConsecutiveFailures)
+	}
+	state = run(state, successfulRun)
+	if !state.OK || len(state.Errors) != 0 {
+		t.Fatal("Expected check to be OK after 2 successful runs but got", state.OK, state.Errors)
+	}
+
+	// a single failure between successes does not change the shown state
+	state = run(state, failedRun)
+	state = run(state, successfulRun)
+	if !state.OK || state.ConsecutiveSuccesses != 1 {
+		t.Fatal("Expected flapping check to stay OK but got", state.OK, state.ConsecutiveSuccesses)
+	}
+
+	// without thresholds, every run changes the shown state
+	c = &Checker{}
+	state = run(state, failedRun)
+	if state.OK {
+		t.Fatal("Expected check without thresholds to fail after 1 failed run")
+	}
+}
@@ -0,0 +1,242 @@
+package health
+
+import (
+	"bytes"
+	"html/template"
+	"net/http"
+	"sort"
+	"strings"
+	"time"
+
+	log "github.com/sirupsen/logrus"
+
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
+)
+
+// dashboardRefreshSeconds is how often the dashboard reloads itself in the browser
+const dashboardRefreshSeconds = 30
+
+// WantsHTML returns true when the Accept header of a request prefers HTML over JSON, as it does for browsers.  Callers
+// that accept anything, such as curl, are sent JSON.
+func WantsHTML(r *http.Request) bool {
+	for _, accept := range strings.Split(r.Header.Get("Accept"), ",") {
+		switch strings.TrimSpace(strings.Split(accept, ";")[0]) {
+		case "text/html", "application/xhtml+xml":
+			return true
+		case "application/json":
+			return false
+		}
+	}
+	return false
+}
+
+// dashboardWorkload is a single check or job shown on the dashboard
+type dashboardWorkload struct {
+	Kind    string // khcheck or khjob
+	Name    string
+	Status  string // OK, Warning, Failing, Suspended or Blocked
+	Details khstatev1.WorkloadDetails
+}
+
+// dashboardNamespace is the checks and jobs of a namespace shown on the dashboard
+type dashboardNamespace struct {
+	Name      string
+	Workloads []dashboardWorkload
+}
+
+// dashboardData is rendered by the dashboard template
+type dashboardData struct {
+	State          *State
+	Status         string
+	Namespaces     []dashboardNamespace
+	Generated      time.Time
+	RefreshSeconds int
+}
+
+// workloadStatus returns the status shown for a check or job on the dashboard
+func workloadStatus(d khstatev1.WorkloadDetails) string {
+	switch {
+	case d.Suspended:
+		return "Suspended"
+	case d.Blocked:
+		return "Blocked"
+	default:
+		return string(d.GetSeverity())
+	}
+}
+
+// dashboardNamespaces groups the checks and jobs of the state by namespace, sorted by namespace and name
+func (h *State) dashboardNamespaces() []dashboardNamespace {
+	byNamespace := make(map[string][]dashboardWorkload)
+	add := func(kind string, details map[string]khstatev1.WorkloadDetails) {
+		for key, d := range details {
+			byNamespace[d.Namespace] = append(byNamespace[d.Namespace], dashboardWorkload{
+				Kind:    kind,
+				Name:    strings.TrimPrefix(key, d.Namespace+"/"),
+				Status:  workloadStatus(d),
+				Details: d,
+			})
+		}
+	}
+	add("khcheck", h.CheckDetails)
+	add("khjob", h.JobDetails)
+
+	namespaces := make([]dashboardNamespace, 0, len(byNamespace))
+	for name, workloads := range byNamespace {
+		sort.Slice(workloads, func(i, j int) bool {
+			if workloads[i].Kind != workloads[j].Kind {
+				return workloads[i].Kind < workloads[j].Kind
+			}
+			return workloads[i].Name < workloads[j].Name
+		})
+		namespaces = append(namespaces, dashboardNamespace{Name: name, Workloads: workloads})
+	}
+	sort.Slice(namespaces, func(i, j int) bool {
+		return namespaces[i].Name < namespaces[j].Name
+	})
+	return namespaces
+}
+
+// WriteHTMLStatusResponse writes the state to an http response writer as an HTML dashboard.  The dashboard is
+// rendered on the server and uses no scripts or external resources, so it works in air-gapped clusters.
+func (h *State) WriteHTMLStatusResponse(w http.ResponseWriter) error {
+	status := string(h.Severity)
+	if len(status) == 0 {
+		status = string(khstatev1.SeverityOK)
+		if !h.OK {
+			status = string(khstatev1.SeverityFailing)
+		}
+	}
+
+	data := dashboardData{
+		State:          h,
+		Status:         status,
+		Namespaces:     h.dashboardNamespaces(),
+		Generated:      time.Now().UTC(),
+		RefreshSeconds: dashboardRefreshSeconds,
+	}
+
+	// render the whole page before writing so a template error does not leave a partial page
+	var b bytes.Buffer
+	err := dashboardTemplate.Execute(&b, data)
+	if err != nil {
+		log.Warningln("Error rendering status dashboard for caller:", err)
+		return err
+	}
+
+	w.Header().Set("Content-Type", "text/html; charset=utf-8")
+	_, err = w.Write(b.Bytes())
+	if err != nil {
+		log.Errorln("Error writing response to caller:", err)
+		return err
+	}
+	return nil
+}
+
+// dashboardTemplate renders the status dashboard
+var dashboardTemplate = template.Must(template.New("dashboard").Funcs(template.FuncMap{
+	"lower":     strings.ToLower,
+	"timestamp": dashboardTimestamp,
+}).Parse(dashboardHTML))
+
+// dashboardTimestamp formats a time for the dashboard
+func dashboardTimestamp(t time.Time) string {
+	if t.IsZero() {
+		return "never"
+	}
+	return t.UTC().Format("2006-01-02 15:04:05 MST")
+}
+
+// dashboardHTML is the template of the status dashboard
+const dashboardHTML = `<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta http-equiv="refresh" content="{{ .RefreshSeconds }}">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Kuberhealthy - {{ .Status }}</title>
+<style>
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 0; color: #1f2328; background: #f6f8fa; }
+header { padding: 16px 24px; color: #fff; }
+header h1 { margin: 0; font-size: 22px; }
+header p { margin: 4px 0 0; font-size: 13px; }
+main { padding: 8px 24px 24px; }
+h2 { font-size: 17px; margin: 24px 0 8px; }
+table { width: 100%; border-collapse: collapse; background: #fff; font-size: 14px; }
+th, td { text-align: left; padding: 8px; border-bottom: 1px solid #d0d7de; vertical-align: top; }
+th { background: #eaeef2; }
+ul { margin: 0; padding-left: 18px; }
+details summary { cursor: pointer; }
+.badge { display: inline-block; padding: 2px 8px; border-radius: 10px; color: #fff; font-size: 12px; font-weight: 600; }
+.ok { background: #1a7f37; }
+.warning { background: #bf8700; }
+.failing { background: #cf222e; }
+.suspended, .blocked { background: #6e7781; }
+.history span { display: inline-block; width: 10px; height: 16px; margin-right: 2px; border-radius: 2px; }
+.muted { color: #6e7781; }
+.errors { color: #cf222e; }
+.warnings { color: #9a6700; }
+</style>
+</head>
+<body>
+<header class="{{ lower .Status }}">
+<h1>Kuberhealthy: {{ .Status }}</h1>
+<p>Master: {{ if .State.CurrentMaster }}{{ .State.CurrentMaster }}{{ else }}unknown{{ end }} &middot; Generated {{ timestamp .Generated }} &middot; Refreshes every {{ .RefreshSeconds }} seconds</p>
+{{- range $k, $v := .State.Metadata }}
+<p>{{ $k }}: {{ $v }}</p>
+{{- end }}
+</header>
+<main>
+{{- if .State.Errors }}
+<h2>Errors</h2>
+<ul class="errors">
+{{- range .State.Errors }}
+<li>{{ . }}</li>
+{{- end }}
+</ul>
+{{- end }}
+{{- if .State.Warnings }}
+<h2>Warnings</h2>
+<ul class="warnings">
+{{- range .State.Warnings }}
+<li>{{ . }}</li>
+{{- end }}
+</ul>
+{{- end }}
+{{- range .Namespaces }}
+<h2>Namespace {{ .Name }}</h2>
+<table>
+<tr><th>Name</th><th>Kind</th><th>Status</th><th>Last Run</th><th>Duration</th><th>Node</th><th>Errors and Warnings</th><th>History</th></tr>
+{{- range .Workloads }}
+<tr>
+<td>{{ .Name }}</td>
+<td>{{ .Kind }}</td>
+<td><span class="badge {{ lower .Status }}">{{ .Status }}</span>{{ if .Details.BlockedReason }}<div class="muted">{{ .Details.BlockedReason }}</div>{{ end }}</td>
+<td>{{ if .Details.LastRun }}{{ timestamp .Details.LastRun.Time }}{{ else }}<span class="muted">never</span>{{ end }}</td>
+<td>{{ .Details.RunDuration }}</td>
+<td>{{ .Details.Node }}</td>
+<td>
+{{- if .Details.Errors }}<ul class="errors">{{ range .Details.Errors }}<li>{{ . }}</li>{{ end }}</ul>{{ end }}
+{{- if .Details.Warnings }}<ul class="warnings">{{ range .Details.Warnings }}<li>{{ . }}</li>{{ end }}</ul>{{ end }}
+</td>
+<td>
+{{- if .Details.History }}
+<details><summary class="history">{{ range .Details.History }}<span class="{{ if .OK }}ok{{ else }}failing{{ end }}" title="{{ timestamp .Time.Time }} ({{ .RunDuration }})"></span>{{ end }}</summary>
+<ul>
+{{- range .Details.History }}
+<li>{{ timestamp .Time.Time }}: {{ if .OK }}OK{{ else }}Failing{{ end }} in {{ .RunDuration }}{{ if .Node }} on {{ .Node }}{{ end }}{{ range .Errors }}<div class="errors">{{ . }}</div>{{ end }}</li>
+{{- end }}
+</ul>
+</details>
+{{- else }}<span class="muted">none</span>{{ end }}
+</td>
+</tr>
+{{- end }}
+</table>
+{{- else }}
+<p class="muted">No checks or jobs have reported yet.</p>
+{{- end }}
+</main>
+</body>
+</html>
+`
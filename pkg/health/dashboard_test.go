@@ -0,0 +1,70 @@
+package health_test
+
+import (
+	"net/http/httptest"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/assert"
+	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
+
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/health"
+)
+
+func TestWantsHTML(t *testing.T) {
+	var testCases = []struct {
+		accept   string
+		expected bool
+	}{
+		{"text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8", true},
+		{"application/json", false},
+		{"application/json, text/html", false},
+		{"*/*", false},
+		{"", false},
+	}
+	for _, tc := range testCases {
+		r := httptest.NewRequest("GET", "/", nil)
+		r.Header.Set("Accept", tc.accept)
+		assert.Equal(t, tc.expected, health.WantsHTML(r), tc.accept)
+	}
+}
+
+func TestWriteHTMLStatusResponse(t *testing.T) {
+	lastRun := metav1.NewTime(time.Date(2024, 5, 1, 12, 0, 0, 0, time.UTC))
+	s := health.NewState()
+	s.OK = false
+	s.Severity = khstatev1.SeverityFailing
+	s.AddError("<script>alert('dns')</script>")
+	s.CheckDetails["kube-system/dns"] = khstatev1.WorkloadDetails{
+		OK:          false,
+		Namespace:   "kube-system",
+		Errors:      []string{"<script>alert('dns')</script>"},
+		RunDuration: "2s",
+		Node:        "node-a",
+		LastRun:     &lastRun,
+		History:     []khstatev1.RunHistoryEntry{{Time: lastRun, OK: false, RunDuration: "2s", UUID: "a"}},
+	}
+	s.CheckDetails["kuberhealthy/deployment"] = khstatev1.WorkloadDetails{OK: true, Namespace: "kuberhealthy", Suspended: true}
+	s.JobDetails["kuberhealthy/daily"] = khstatev1.WorkloadDetails{OK: true, Namespace: "kuberhealthy", Severity: khstatev1.SeverityWarning, Warnings: []string{"quota at 90%"}}
+
+	w := httptest.NewRecorder()
+	err := s.WriteHTMLStatusResponse(w)
+	assert.NoError(t, err)
+	assert.Equal(t, "text/html; charset=utf-8", w.Header().Get("Content-Type"))
+
+	body := w.Body.String()
+	assert.Contains(t, body, "<title>Kuberhealthy - Failing</title>")
+	assert.Contains(t, body, "2024-05-01 12:00:00 UTC")
+	assert.Contains(t, body, "node-a")
+	assert.Contains(t, body, ">Suspended</span>")
+	assert.Contains(t, body, ">Warning</span>")
+	assert.Contains(t, body, "quota at 90%")
+	assert.NotContains(t, body, "<script>", "errors must be escaped")
+	assert.NotContains(t, body, "http://", "the dashboard must not load external resources")
+	assert.NotContains(t, body, "https://", "the dashboard must not load external resources")
+
+	// namespaces are listed in order
+	assert.Less(t, strings.Index(body, "Namespace kube-system"), strings.Index(body, "Namespace kuberhealthy"))
+}
@@ -10,10 +10,13 @@ import (
 )
 
 // State represents the results of all checks being managed along with a top-level OK and Error state. This is displayed
-// on the kuberhealthy status page as JSON
+// on the kuberhealthy status page as JSON.  The top-level Severity distinguishes a degraded cluster with checks in a
+// Warning state from a failing one.
 type State struct {
 	OK            bool
+	Severity      khstatev1.Severity
 	Errors        []string
+	Warnings      []string
 	CheckDetails  map[string]khstatev1.WorkloadDetails // map of check names to last run timestamp
 	JobDetails    map[string]khstatev1.WorkloadDetails // map of job names to last run timestamp
 	CurrentMaster string
@@ -32,6 +35,43 @@ func (h *State) AddError(s ...string) {
 	}
 }
 
+// AddWarning adds new warnings to State
+func (h *State) AddWarning(s ...string) {
+	for _, str := range s {
+		if len(str) == 0 {
+			log.Warningln("AddWarning was called but the warning was blank so it was skipped.")
+			continue
+		}
+		log.Debugln("Appending warning:", str)
+		h.Warnings = append(h.Warnings, str)
+	}
+}
+
+// AddSeverity raises the top-level Severity of the State to the severity of a check or job if it is worse than the
+// current Severity.  A failing check always leaves the State failing, while a warning only leaves it degraded.
+func (h *State) AddSeverity(s khstatev1.Severity) {
+	switch s {
+	case khstatev1.SeverityFailing:
+		h.Severity = khstatev1.SeverityFailing
+	case khstatev1.SeverityWarning:
+		if h.Severity != khstatev1.SeverityFailing {
+			h.Severity = khstatev1.SeverityWarning
+		}
+	}
+}
+
+// RemoveHistory removes the run history from the details of every check and job in the State
+func (h *State) RemoveHistory() {
+	for name, details := range h.CheckDetails {
+		details.History = nil
+		h.CheckDetails[name] = details
+	}
+	for name, details := range h.JobDetails {
+		details.History = nil
+		h.JobDetails[name] = details
+	}
+}
+
 // WriteHTTPStatusResponse writes a response to an http response writer
 func (h *State) WriteHTTPStatusResponse(w http.ResponseWriter) error {
 
@@ -58,7 +98,9 @@ func (h *State) WriteHTTPStatusResponse(w http.ResponseWriter) error {
 func NewState() State {
 	s := State{}
 	s.OK = true
+	s.Severity = khstatev1.SeverityOK
 	s.Errors = []string{}
+	s.Warnings = []string{}
 	s.CheckDetails = make(map[string]khstatev1.WorkloadDetails)
 	s.JobDetails = make(map[string]khstatev1.WorkloadDetails)
 	s.Metadata = map[string]string{}
@@ -3,6 +3,7 @@ package health_test
 import (
 	"testing"
 
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/health"
 	"github.com/stretchr/testify/assert"
 )
@@ -10,6 +11,34 @@ import (
 func TestNewState(t *testing.T) {
 	s := health.NewState()
 	assert.True(t, s.OK)
+	assert.Equal(t, khstatev1.SeverityOK, s.Severity)
+}
+
+func TestRemoveHistory(t *testing.T) {
+	s := health.NewState()
+	s.CheckDetails["kuberhealthy/check"] = khstatev1.WorkloadDetails{
+		OK:      true,
+		History: []khstatev1.RunHistoryEntry{{OK: true, UUID: "a"}},
+	}
+	s.JobDetails["kuberhealthy/job"] = khstatev1.WorkloadDetails{
+		History: []khstatev1.RunHistoryEntry{{OK: false, UUID: "b"}},
+	}
+	s.RemoveHistory()
+	assert.Nil(t, s.CheckDetails["kuberhealthy/check"].History)
+	assert.True(t, s.CheckDetails["kuberhealthy/check"].OK)
+	assert.Nil(t, s.JobDetails["kuberhealthy/job"].History)
+}
+
+func TestAddSeverity(t *testing.T) {
+	s := health.NewState()
+	s.AddSeverity(khstatev1.SeverityOK)
+	assert.Equal(t, khstatev1.SeverityOK, s.Severity)
+	s.AddSeverity(khstatev1.SeverityWarning)
+	assert.Equal(t, khstatev1.SeverityWarning, s.Severity)
+	s.AddSeverity(khstatev1.SeverityFailing)
+	assert.Equal(t, khstatev1.SeverityFailing, s.Severity)
+	s.AddSeverity(khstatev1.SeverityWarning)
+	assert.Equal(t, khstatev1.SeverityFailing, s.Severity)
 }
 
 func TestAddError(t *testing.T) {
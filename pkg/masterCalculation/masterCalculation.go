@@ -5,11 +5,13 @@ package masterCalculation // import "github.com/kuberhealthy/kuberhealthy/v2/pkg
 import (
 	"context"
 	"errors"
-	"os"
-	"sort"
-	"strings"
+	"sync"
+	"sync/atomic"
+	"time"
 
 	"k8s.io/client-go/kubernetes"
+	"k8s.io/client-go/tools/leaderelection"
+	"k8s.io/client-go/tools/leaderelection/resourcelock"
 
 	// blank insert is for handling reverse proxy authN via oidc protocol
 	_ "k8s.io/client-go/plugin/pkg/client/auth/oidc"
@@ -18,7 +20,18 @@ import (
 	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
 )
 
-var namespace = os.Getenv("POD_NAMESPACE")
+// DefaultLeaseName is the name of the coordination.k8s.io lease used for master election
+const DefaultLeaseName = "kuberhealthy-master"
+
+// DefaultLeaseDuration is how long non-master pods wait before attempting to take over an un-renewed lease
+const DefaultLeaseDuration = time.Second * 15
+
+// DefaultRenewDeadline is how long the master keeps retrying to renew its lease before giving up master
+const DefaultRenewDeadline = time.Second * 10
+
+// DefaultRetryPeriod is how long pods wait between attempts to acquire or renew the lease
+const DefaultRetryPeriod = time.Second * 2
+
 var enableForceMaster bool // indicates we should always report as master for debugging
 
 // DebugAlwaysMasterOn makes all master queries return true without logic
@@ -31,78 +44,166 @@ func EnableDebug() {
 	log.SetLevel(log.DebugLevel)
 }
 
-// getEnvVar attempts to retrieve and then validates an environmental variable
-func getEnvVar(v string) (string, error) {
-	var err error
-	envVar := os.Getenv(v)
-	if len(envVar) < 1 {
-		err = errors.New("Could not retrieve environment variable, or it had no content. " + v)
-		return envVar, err
-	}
-	return envVar, err
+// LeaderElectionConfig holds the configurable settings for lease based master election
+type LeaderElectionConfig struct {
+	LeaseName     string        `yaml:"leaseName,omitempty"`     // the name of the lease resource (default: kuberhealthy-master)
+	LeaseDuration time.Duration `yaml:"leaseDuration,omitempty"` // how long a lease is valid without being renewed (default: 15s)
+	RenewDeadline time.Duration `yaml:"renewDeadline,omitempty"` // how long the master retries renewing before giving up (default: 10s)
+	RetryPeriod   time.Duration `yaml:"retryPeriod,omitempty"`   // how often to retry acquiring or renewing the lease (default: 2s)
 }
 
-// CalculateMaster determines which kuberhealthy pod should assume the master role
-func CalculateMaster(client *kubernetes.Clientset) (string, error) {
-	// TODO: refactor function to receive context on exported function in next breaking change.
-	ctx := context.TODO()
+// withDefaults returns a copy of the config with defaults filled in for any unset values
+func (c LeaderElectionConfig) withDefaults() LeaderElectionConfig {
+	if len(c.LeaseName) == 0 {
+		c.LeaseName = DefaultLeaseName
+	}
+	if c.LeaseDuration == 0 {
+		c.LeaseDuration = DefaultLeaseDuration
+	}
+	if c.RenewDeadline == 0 {
+		c.RenewDeadline = DefaultRenewDeadline
+	}
+	if c.RetryPeriod == 0 {
+		c.RetryPeriod = DefaultRetryPeriod
+	}
+	return c
+}
 
-	log.Debugln("Calculating current master...")
+// Elector runs master election for a kuberhealthy pod using a coordination.k8s.io lease.  Only the current
+// holder of the lease is considered the master.
+type Elector struct {
+	client        kubernetes.Interface
+	namespace     string
+	identity      string
+	config        LeaderElectionConfig
+	mu            sync.RWMutex
+	leaderElector *leaderelection.LeaderElector // the elector for the current election attempt
+	currentMaster string                        // the identity of the lease holder as last observed
+}
 
-	// get a list of all kuberhealthy pods
-	pods, err := client.CoreV1().Pods(namespace).List(ctx, metav1.ListOptions{
-		LabelSelector: "app=kuberhealthy", FieldSelector: "status.phase=Running",
-	})
-	if err != nil {
-		return "", err
+// NewElector creates a new lease based master elector for the pod with the supplied identity.  The lease
+// is created in the supplied namespace.
+func NewElector(client kubernetes.Interface, namespace string, identity string, config LeaderElectionConfig) (*Elector, error) {
+	if len(namespace) == 0 {
+		return nil, errors.New("can not run master election without a namespace")
 	}
-
-	// create a slice of all kuberhealthy pod names for use in sort
-	var podlist []string
-	for _, p := range pods.Items {
-		podlist = append(podlist, p.Name)
+	if len(identity) == 0 {
+		return nil, errors.New("can not run master election without an identity")
 	}
 
-	if len(podlist) < 1 {
-		return "", errors.New("Failed to retrieve list of Kuberhealthy pods")
+	config = config.withDefaults()
+	if config.RenewDeadline >= config.LeaseDuration {
+		return nil, errors.New("master election renewDeadline must be less than leaseDuration")
 	}
 
-	// choose master by grabbing the first in alphabetical order based on
-	// the pod name
-	sort.Strings(podlist)
-	master := podlist[0]
-
-	log.Debugln("Calculated master as", master)
-	return master, err
+	return &Elector{
+		client:    client,
+		namespace: namespace,
+		identity:  identity,
+		config:    config,
+	}, nil
 }
 
-// IAmMaster determines if the executing pod is the cluster master or not
-func IAmMaster(client *kubernetes.Clientset) (bool, error) {
+// Run takes part in master election until the context is canceled.  becameMasterChan is notified each time
+// this pod acquires the lease, and lostMasterChan is notified each time this pod loses it.  The lease is
+// released when the context is canceled so that another pod can take over quickly.
+func (e *Elector) Run(ctx context.Context, becameMasterChan chan struct{}, lostMasterChan chan struct{}) {
 
-	// if we are in debug enable master always, then just return true
+	// if we are in debug enable master always, we are the master until shutdown
 	if enableForceMaster {
-		return true, nil
+		e.setCurrentMaster(e.identity)
+		becameMasterChan <- struct{}{}
+		<-ctx.Done()
+		return
 	}
 
-	master, err := CalculateMaster(client)
-	if err != nil {
-		return false, err
+	for {
+		lock := &resourcelock.LeaseLock{
+			LeaseMeta: metav1.ObjectMeta{
+				Name:      e.config.LeaseName,
+				Namespace: e.namespace,
+			},
+			Client: e.client.CoordinationV1(),
+			LockConfig: resourcelock.ResourceLockConfig{
+				Identity: e.identity,
+			},
+		}
+
+		// the lost master callback also fires when an election attempt ends without ever acquiring the
+		// lease, so we track if this attempt actually became master
+		var leading atomic.Bool
+
+		leaderElector, err := leaderelection.NewLeaderElector(leaderelection.LeaderElectionConfig{
+			Lock:            lock,
+			Name:            e.config.LeaseName,
+			LeaseDuration:   e.config.LeaseDuration,
+			RenewDeadline:   e.config.RenewDeadline,
+			RetryPeriod:     e.config.RetryPeriod,
+			ReleaseOnCancel: true,
+			Callbacks: leaderelection.LeaderCallbacks{
+				OnStartedLeading: func(ctx context.Context) {
+					log.Infoln("masterCalculation: acquired lease", e.config.LeaseName, "as", e.identity)
+					leading.Store(true)
+					becameMasterChan <- struct{}{}
+				},
+				OnStoppedLeading: func() {
+					if !leading.Swap(false) {
+						return
+					}
+					log.Infoln("masterCalculation: no longer holding lease", e.config.LeaseName, "as", e.identity)
+					lostMasterChan <- struct{}{}
+				},
+				OnNewLeader: func(identity string) {
+					log.Infoln("masterCalculation: observed new master", identity)
+					e.setCurrentMaster(identity)
+				},
+			},
+		})
+		if err != nil {
+			log.Errorln("masterCalculation: failed to configure master election:", err)
+			return
+		}
+
+		e.mu.Lock()
+		e.leaderElector = leaderElector
+		e.mu.Unlock()
+
+		// Run blocks until the lease is lost or the context is canceled
+		leaderElector.Run(ctx)
+
+		select {
+		case <-ctx.Done():
+			log.Infoln("masterCalculation: stopping master election due to context cancellation")
+			return
+		case <-time.After(e.config.RetryPeriod):
+		}
 	}
+}
 
-	// get name of the pod running this check from an environment variable we set
-	// in the pod spec
-	myPod, err := getEnvVar("POD_NAME")
-	log.Debugln("My pod hostname is: " + myPod)
-	if err != nil {
-		log.Errorln(err)
+// IsMaster indicates if this pod currently holds the master lease
+func (e *Elector) IsMaster() bool {
+	if enableForceMaster {
+		return true
 	}
 
-	// if our pod name matches the calculated master pod name, we are the master
-	if strings.ToLower(myPod) == strings.ToLower(master) {
-		log.Debugln("I am master")
-		return true, err
+	e.mu.RLock()
+	defer e.mu.RUnlock()
+	if e.leaderElector == nil {
+		return false
 	}
+	return e.leaderElector.IsLeader()
+}
+
+// CurrentMaster returns the identity of the pod that was last seen holding the master lease
+func (e *Elector) CurrentMaster() string {
+	e.mu.RLock()
+	defer e.mu.RUnlock()
+	return e.currentMaster
+}
 
-	log.Debugln("I am NOT master")
-	return false, err
+// setCurrentMaster records the identity of the pod holding the master lease
+func (e *Elector) setCurrentMaster(identity string) {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	e.currentMaster = identity
 }
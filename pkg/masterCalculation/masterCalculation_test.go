@@ -1,25 +1,91 @@
 package masterCalculation
 
 import (
-	"os"
+	"context"
 	"testing"
+	"time"
 
-	"github.com/kuberhealthy/kuberhealthy/v2/pkg/kubeClient"
 	log "github.com/sirupsen/logrus"
+	"k8s.io/client-go/kubernetes/fake"
 )
 
-var kubeConfigFile = os.Getenv("HOME") + "/.kube/config"
+// testLeaderElectionConfig uses short lease timings so that tests run quickly
+var testLeaderElectionConfig = LeaderElectionConfig{
+	LeaseDuration: time.Second * 2,
+	RenewDeadline: time.Second,
+	RetryPeriod:   time.Millisecond * 100,
+}
+
+func TestNewElector(t *testing.T) {
+	client := fake.NewSimpleClientset()
+
+	_, err := NewElector(client, "", "kuberhealthy-a", LeaderElectionConfig{})
+	if err == nil {
+		t.Fatal("Expected an error when creating an elector without a namespace")
+	}
+
+	_, err = NewElector(client, "kuberhealthy", "", LeaderElectionConfig{})
+	if err == nil {
+		t.Fatal("Expected an error when creating an elector without an identity")
+	}
 
-func TestRun(t *testing.T) {
+	_, err = NewElector(client, "kuberhealthy", "kuberhealthy-a", LeaderElectionConfig{LeaseDuration: time.Second, RenewDeadline: time.Second * 2})
+	if err == nil {
+		t.Fatal("Expected an error when the renew deadline is longer than the lease duration")
+	}
+
+	e, err := NewElector(client, "kuberhealthy", "kuberhealthy-a", LeaderElectionConfig{})
+	if err != nil {
+		t.Fatal(err)
+	}
+	if e.config.LeaseName != DefaultLeaseName || e.config.LeaseDuration != DefaultLeaseDuration {
+		t.Fatal("Expected default lease settings to be applied but got", e.config)
+	}
+}
+
+func TestElectorRun(t *testing.T) {
 	log.SetLevel(log.DebugLevel)
-	client, err := kubeClient.Create(kubeConfigFile)
+	client := fake.NewSimpleClientset()
+
+	ctx, ctxCancel := context.WithCancel(context.Background())
+	defer ctxCancel()
+
+	// start the first elector and wait for it to become master
+	first, err := NewElector(client, "kuberhealthy", "kuberhealthy-a", testLeaderElectionConfig)
 	if err != nil {
 		t.Fatal(err)
 	}
+	firstBecameMaster := make(chan struct{}, 10)
+	firstLostMaster := make(chan struct{}, 10)
+	go first.Run(ctx, firstBecameMaster, firstLostMaster)
 
-	master, err := CalculateMaster(client)
+	select {
+	case <-firstBecameMaster:
+	case <-time.After(time.Second * 10):
+		t.Fatal("Timed out waiting for the first elector to become master")
+	}
+	if !first.IsMaster() {
+		t.Fatal("Expected the first elector to be master after acquiring the lease")
+	}
+
+	// start a second elector, which should not become master while the first holds the lease
+	second, err := NewElector(client, "kuberhealthy", "kuberhealthy-b", testLeaderElectionConfig)
 	if err != nil {
 		t.Fatal(err)
 	}
-	t.Log(master)
+	secondBecameMaster := make(chan struct{}, 10)
+	secondLostMaster := make(chan struct{}, 10)
+	go second.Run(ctx, secondBecameMaster, secondLostMaster)
+
+	select {
+	case <-secondBecameMaster:
+		t.Fatal("Second elector became master while the first held the lease")
+	case <-time.After(time.Second * 3):
+	}
+	if second.IsMaster() {
+		t.Fatal("Expected the second elector to not be master")
+	}
+	if second.CurrentMaster() != "kuberhealthy-a" {
+		t.Fatal("Expected the second elector to see kuberhealthy-a as master but saw", second.CurrentMaster())
+	}
 }
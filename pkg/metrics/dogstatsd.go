@@ -0,0 +1,185 @@
+package metrics
+
+import (
+	"errors"
+	"fmt"
+	"math"
+	"net"
+	"os"
+	"sort"
+	"strconv"
+	"strings"
+)
+
+// DefaultDogStatsDPort is the port the datadog agent listens for DogStatsD metrics on
+const DefaultDogStatsDPort = "8125"
+
+// DefaultDogStatsDPrefix is prepended to the name of every metric sent to DogStatsD
+const DefaultDogStatsDPrefix = "kuberhealthy."
+
+// dogStatsDMaxPacketSize keeps packets below the size the datadog agent reads from its UDP socket by default
+const dogStatsDMaxPacketSize = 1432
+
+// DogStatsDConfig configures the forwarding of metrics to a DogStatsD server such as the datadog agent
+type DogStatsDConfig struct {
+	Address string   `yaml:"address,omitempty"` // the host:port of the DogStatsD server (default: $DD_AGENT_HOST:8125)
+	Prefix  string   `yaml:"prefix,omitempty"`  // prepended to every metric name (default: kuberhealthy.)
+	Tags    []string `yaml:"tags,omitempty"`    // extra key:value tags added to every metric
+}
+
+// DogStatsDClient sends metrics as DogStatsD gauges over UDP
+type DogStatsDClient struct {
+	conn   net.Conn
+	prefix string
+	tags   []string
+}
+
+// NewDogStatsDClient creates a DogStatsDClient that can be used to push metrics.  When no address is configured,
+// the datadog agent is found with the DD_AGENT_HOST and DD_DOGSTATSD_PORT environment variables.
+func NewDogStatsDClient(config DogStatsDConfig) (*DogStatsDClient, error) {
+	address := config.Address
+	if len(address) == 0 {
+		host := os.Getenv("DD_AGENT_HOST")
+		if len(host) == 0 {
+			return nil, errors.New("a DogStatsD address is required when DD_AGENT_HOST is not set")
+		}
+		port := os.Getenv("DD_DOGSTATSD_PORT")
+		if len(port) == 0 {
+			port = DefaultDogStatsDPort
+		}
+		address = net.JoinHostPort(host, port)
+	}
+
+	conn, err := net.Dial("udp", address)
+	if err != nil {
+		return nil, errors.New("failed to set up DogStatsD connection to " + address + ": " + err.Error())
+	}
+
+	prefix := config.Prefix
+	if len(prefix) == 0 {
+		prefix = DefaultDogStatsDPrefix
+	}
+
+	return &DogStatsDClient{
+		conn:   conn,
+		prefix: prefix,
+		tags:   config.Tags,
+	}, nil
+}
+
+// Push accepts a list of metrics, with a metric being defined as a map of string (name) to interface (value), and
+// sends each of them as a DogStatsD gauge tagged with the supplied tags
+func (d *DogStatsDClient) Push(points Metric, tags map[string]string) error {
+	tagSuffix := dogStatsDTags(d.tags, tags)
+
+	var lines []string
+	for _, p := range points {
+		for key, val := range p {
+			value, err := dogStatsDValue(val)
+			if err != nil {
+				return fmt.Errorf("failed to convert metric %s: %w", key, err)
+			}
+			lines = append(lines, dogStatsDName(d.prefix+key)+":"+value+"|g"+tagSuffix)
+		}
+	}
+
+	// send the gauges in as few packets as possible
+	var packet string
+	for _, l := range lines {
+		if len(packet) > 0 && len(packet)+1+len(l) > dogStatsDMaxPacketSize {
+			err := d.send(packet)
+			if err != nil {
+				return err
+			}
+			packet = ""
+		}
+		if len(packet) > 0 {
+			packet += "\n"
+		}
+		packet += l
+	}
+	if len(packet) > 0 {
+		return d.send(packet)
+	}
+	return nil
+}
+
+// Close closes the connection to the DogStatsD server
+func (d *DogStatsDClient) Close() error {
+	return d.conn.Close()
+}
+
+// send writes a single packet to the DogStatsD server
+func (d *DogStatsDClient) send(packet string) error {
+	_, err := d.conn.Write([]byte(packet))
+	if err != nil {
+		return errors.New("failed to send metrics to DogStatsD: " + err.Error())
+	}
+	return nil
+}
+
+// dogStatsDName replaces the characters that are not allowed in a DogStatsD metric name with underscores
+func dogStatsDName(name string) string {
+	return strings.Map(func(r rune) rune {
+		if (r >= 'a' && r <= 'z') || (r >= 'A' && r <= 'Z') || (r >= '0' && r <= '9') || r == '_' || r == '.' {
+			return r
+		}
+		return '_'
+	}, name)
+}
+
+// dogStatsDTagReplacer removes the characters that separate the parts of a DogStatsD datagram from tags
+var dogStatsDTagReplacer = strings.NewReplacer("|", "_", ",", "_", "#", "_", "\n", " ")
+
+// dogStatsDTags formats the constant tags and the supplied tags, sorted by key, as the tag section of a datagram.
+// Tags with empty values are sent as bare keys.
+func dogStatsDTags(constantTags []string, tags map[string]string) string {
+	formatted := make([]string, 0, len(constantTags)+len(tags))
+	for _, t := range constantTags {
+		formatted = append(formatted, dogStatsDTagReplacer.Replace(t))
+	}
+
+	keys := make([]string, 0, len(tags))
+	for k := range tags {
+		keys = append(keys, k)
+	}
+	sort.Strings(keys)
+	for _, k := range keys {
+		if len(tags[k]) == 0 {
+			formatted = append(formatted, dogStatsDTagReplacer.Replace(k))
+			continue
+		}
+		formatted = append(formatted, dogStatsDTagReplacer.Replace(k+":"+tags[k]))
+	}
+
+	if len(formatted) == 0 {
+		return ""
+	}
+	return "|#" + strings.Join(formatted, ",")
+}
+
+// dogStatsDValue formats a metric value as a gauge value
+func dogStatsDValue(val interface{}) (string, error) {
+	switch v := val.(type) {
+	case float64:
+		if math.IsNaN(v) || math.IsInf(v, 0) {
+			return "", fmt.Errorf("value %v can not be sent to DogStatsD", v)
+		}
+		return strconv.FormatFloat(v, 'f', -1, 64), nil
+	case float32:
+		return dogStatsDValue(float64(v))
+	case int:
+		return strconv.Itoa(v), nil
+	case int64:
+		return strconv.FormatInt(v, 10), nil
+	case int32:
+		return strconv.FormatInt(int64(v), 10), nil
+	case bool:
+		if v {
+			return "1", nil
+		}
+		return "0", nil
+	default:
+		return "", fmt.Errorf("unsupported value type %T", val)
+	}
+}
@@ -0,0 +1,68 @@
+package metrics
+
+import (
+	"net"
+	"sort"
+	"strings"
+	"testing"
+	"time"
+)
+
+// TestDogStatsDClientPush tests that metrics are sent as tagged DogStatsD gauges
+func TestDogStatsDClientPush(t *testing.T) {
+	listener, err := net.ListenPacket("udp", "127.0.0.1:0")
+	if err != nil {
+		t.Fatal("Error listening for DogStatsD packets:", err)
+	}
+	defer listener.Close()
+
+	client, err := NewDogStatsDClient(DogStatsDConfig{Address: listener.LocalAddr().String(), Tags: []string{"env:test"}})
+	if err != nil {
+		t.Fatal("Error creating DogStatsD client:", err)
+	}
+	defer client.Close()
+
+	tags := map[string]string{
+		"Name":      "dns",
+		"Namespace": "kuberhealthy",
+		"Errors":    "",
+	}
+	err = client.Push(Metric{{"dns.kuberhealthy": 1}, {"RunDuration.dns.kuberhealthy": 1.25}}, tags)
+	if err != nil {
+		t.Fatal("Error pushing metrics:", err)
+	}
+
+	buf := make([]byte, dogStatsDMaxPacketSize)
+	err = listener.SetReadDeadline(time.Now().Add(time.Second * 5))
+	if err != nil {
+		t.Fatal(err)
+	}
+	n, _, err := listener.ReadFrom(buf)
+	if err != nil {
+		t.Fatal("Error reading DogStatsD packet:", err)
+	}
+
+	lines := strings.Split(string(buf[:n]), "\n")
+	sort.Strings(lines)
+	expected := []string{
+		"kuberhealthy.RunDuration.dns.kuberhealthy:1.25|g|#env:test,Errors,Name:dns,Namespace:kuberhealthy",
+		"kuberhealthy.dns.kuberhealthy:1|g|#env:test,Errors,Name:dns,Namespace:kuberhealthy",
+	}
+	if len(lines) != len(expected) || lines[0] != expected[0] || lines[1] != expected[1] {
+		t.Fatalf("expected DogStatsD lines %v, got %v", expected, lines)
+	}
+}
+
+// TestDogStatsDTags tests that tags can not break the DogStatsD datagram format
+func TestDogStatsDTags(t *testing.T) {
+	tags := dogStatsDTags(nil, map[string]string{"Errors": "pod failed|exit 1,#2\nretrying"})
+	if tags != "|#Errors:pod failed_exit 1__2 retrying" {
+		t.Fatal("Unexpected DogStatsD tags:", tags)
+	}
+	if dogStatsDTags(nil, nil) != "" {
+		t.Fatal("Expected no tag section without tags")
+	}
+	if dogStatsDName("kuberhealthy.my check/v1") != "kuberhealthy.my_check_v1" {
+		t.Fatal("Unexpected DogStatsD metric name:", dogStatsDName("kuberhealthy.my check/v1"))
+	}
+}
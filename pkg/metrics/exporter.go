@@ -1,13 +1,19 @@
 package metrics // import "github.com/kuberhealthy/kuberhealthy/v2/pkg/metrics"
 
 import (
-	"fmt"
 	"net/http"
+	"sort"
+	"strconv"
 	"strings"
 	"time"
+	"unicode/utf8"
 
+	"github.com/prometheus/client_golang/prometheus"
+	"github.com/prometheus/client_golang/prometheus/promhttp"
+	"github.com/prometheus/common/expfmt"
 	log "github.com/sirupsen/logrus"
 
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/health"
 )
 
@@ -16,124 +22,249 @@ type PromMetricsConfig struct {
 	ErrorLabelMaxLength int  `yaml:"errorLabelMaxLength,omitempty"` // if not suppress, then bound the error label value length to a number of bytes
 }
 
-// promMetricName: helper fn for GenerateMetrics, does a quick format of the metric line - checkOrJob is literally the string "check" or "job"
-func promMetricName(config PromMetricsConfig, checkOrJob string, checkName string, namespace string, status string, errors []string) string {
-	metricName := fmt.Sprintf("kuberhealthy_%s{check=\"%s\",namespace=\"%s\",status=\"%s\"", checkOrJob, checkName, namespace, status)
-	if !config.SuppressErrorLabel {
-		errorsStr := ""
-		if len(errors) > 0 {
-			for _, error := range errors {
-				errorsStr += fmt.Sprintf("%s|", error)
-			}
-			errorsStr = strings.TrimSuffix(errorsStr, "|")
-			errorsStr = strings.ReplaceAll(errorsStr, "\"", "'")
+// errorLabelValue: helper fn for StateCollector, joins the errors of a check or job into the value of its error label,
+// bounded to the configured maximum length without splitting a character
+func errorLabelValue(config PromMetricsConfig, errors []string) string {
+	errorsStr := strings.Join(errors, "|")
+	if config.ErrorLabelMaxLength > 0 && len(errorsStr) > config.ErrorLabelMaxLength {
+		cut := config.ErrorLabelMaxLength
+		for cut > 0 && !utf8.RuneStart(errorsStr[cut]) {
+			cut--
 		}
-		if config.ErrorLabelMaxLength > 0 && len(errorsStr) > config.ErrorLabelMaxLength {
-			errorsStr = errorsStr[0:config.ErrorLabelMaxLength]
-		}
-		metricName += fmt.Sprintf(",error=\"%s\"}", errorsStr)
-	} else {
-		metricName += "}"
+		errorsStr = errorsStr[0:cut]
 	}
-	return metricName
+	return errorsStr
 }
 
-//GenerateMetrics takes the state and returns it in the Prometheus format
-func GenerateMetrics(state health.State, config PromMetricsConfig) string {
-	metricsOutput := ""
-	healthStatus := "0"
-	if state.OK {
-		healthStatus = "1"
+// promSeverityValue: helper fn for StateCollector, converts a severity to a gauge value that increases with how bad
+// the severity is so alert rules can compare against it - 0 for OK, 1 for Warning and 2 for Failing
+func promSeverityValue(severity khstatev1.Severity) float64 {
+	switch severity {
+	case khstatev1.SeverityFailing:
+		return 2
+	case khstatev1.SeverityWarning:
+		return 1
+	default:
+		return 0
 	}
-	// Kuberhealthy metrics
-	metricsOutput += "# HELP kuberhealthy_running Shows if kuberhealthy is running error free\n"
-	metricsOutput += "# TYPE kuberhealthy_running gauge\n"
-	metricsOutput += fmt.Sprintf("kuberhealthy_running{current_master=\"%s\"} 1\n", state.CurrentMaster)
-	metricsOutput += "# HELP kuberhealthy_cluster_state Shows the status of the cluster\n"
-	metricsOutput += "# TYPE kuberhealthy_cluster_state gauge\n"
-	metricsOutput += fmt.Sprintf("kuberhealthy_cluster_state %s\n", healthStatus)
-
-	metricCheckState := make(map[string]string)
-	metricCheckDuration := make(map[string]string)
-	metricJobState := make(map[string]string)
-	metricJobDuration := make(map[string]string)
-
-	// Parse through all check details and append to metricState
-	for c, d := range state.CheckDetails {
-		checkStatus := "0"
-		if d.OK {
-			checkStatus = "1"
-		}
-		metricName := promMetricName(config, "check", c, d.Namespace, checkStatus, d.Errors)
-		metricDurationName := fmt.Sprintf("kuberhealthy_check_duration_seconds{check=\"%s\",namespace=\"%s\"}", c, d.Namespace)
-		metricCheckState[metricName] = checkStatus
+}
 
-		// if runDuration hasn't been set yet, ie. pod never ran or failed to provision, set runDuration to 0
-		if d.RunDuration == "" {
-			d.RunDuration = time.Duration(0).String()
-		}
-		runDuration, err := time.ParseDuration(d.RunDuration)
-		if err != nil {
-			log.Errorln("Error parsing run duration:", d.RunDuration, "for metric:", metricName, "error:", err)
-		}
-		metricCheckDuration[metricDurationName] = fmt.Sprintf("%f", runDuration.Seconds())
+// promBool: helper fn for collectors, converts a bool to a gauge value
+func promBool(b bool) float64 {
+	if b {
+		return 1
 	}
+	return 0
+}
+
+// reservedReportedMetricLabels are the labels set by kuberhealthy on reported check metrics.  Labels reported by a
+// check with these names are dropped so they can not override the kuberhealthy labels.
+var reservedReportedMetricLabels = map[string]bool{
+	"check":     true,
+	"namespace": true,
+	"metric":    true,
+	"unit":      true,
+}
 
-	// Parse through all job details and append to metricState
-	for c, d := range state.JobDetails {
-		jobStatus := "0"
-		if d.OK {
-			jobStatus = "1"
+// promLabelName: helper fn for reportedMetricLabels, replaces any characters that are not valid in a prometheus
+// label name with underscores
+func promLabelName(name string) string {
+	labelName := []byte(name)
+	for i, c := range labelName {
+		if (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') || c == '_' || (i > 0 && c >= '0' && c <= '9') {
+			continue
 		}
-		metricName := promMetricName(config, "job", c, d.Namespace, jobStatus, d.Errors)
-		metricDurationName := fmt.Sprintf("kuberhealthy_job_duration_seconds{check=\"%s\",namespace=\"%s\"}", c, d.Namespace)
-		metricJobState[metricName] = jobStatus
+		labelName[i] = '_'
+	}
+	return string(labelName)
+}
+
+// reportedMetricLabels: helper fn for StateCollector, returns the labels of a metric reported by a check or job run.
+// Reported labels that are reserved by prometheus or kuberhealthy are dropped, and when two reported labels have the
+// same prometheus label name the first one in sorted order is kept.
+func reportedMetricLabels(name string, namespace string, metric khstatev1.Metric) prometheus.Labels {
+	labels := prometheus.Labels{
+		"check":     name,
+		"namespace": namespace,
+		"metric":    metric.Name,
+		"unit":      metric.Unit,
+	}
 
-		// if runDuration hasn't been set yet, ie. pod never ran or failed to provision, set runDuration to 0
-		if d.RunDuration == "" {
-			d.RunDuration = time.Duration(0).String()
+	labelNames := make([]string, 0, len(metric.Labels))
+	for l := range metric.Labels {
+		labelNames = append(labelNames, l)
+	}
+	sort.Strings(labelNames)
+	for _, l := range labelNames {
+		labelName := promLabelName(l)
+		if len(labelName) == 0 || strings.HasPrefix(labelName, "__") || reservedReportedMetricLabels[labelName] {
+			continue
 		}
-		runDuration, err := time.ParseDuration(d.RunDuration)
-		if err != nil {
-			log.Errorln("Error parsing run duration:", d.RunDuration, "for metric:", metricName, "error:", err)
+		if _, exists := labels[labelName]; exists {
+			continue
 		}
-		metricJobDuration[metricDurationName] = fmt.Sprintf("%f", runDuration.Seconds())
+		labels[labelName] = metric.Labels[l]
 	}
+	return labels
+}
 
-	// Add each metric format individually. This addresses issue https://github.com/kuberhealthy/kuberhealthy/issues/813.
-	// Unless metric help and type are followed by the metric, datadog cannot process Kuberhealthy metrics.
-	// Kuberhealthy check metrics
-	metricsOutput += "# HELP kuberhealthy_check Shows the status of a Kuberhealthy check\n"
-	metricsOutput += "# TYPE kuberhealthy_check gauge\n"
-	for m, v := range metricCheckState {
-		metricsOutput += fmt.Sprintf("%s %s\n", m, v)
+// runDurationSeconds: helper fn for StateCollector, parses the run duration of a check or job.  Checks and jobs
+// that never ran or failed to provision have no run duration yet and are shown with a duration of 0.
+func runDurationSeconds(name string, d khstatev1.WorkloadDetails) float64 {
+	if d.RunDuration == "" {
+		return 0
+	}
+	runDuration, err := time.ParseDuration(d.RunDuration)
+	if err != nil {
+		log.Errorln("Error parsing run duration:", d.RunDuration, "for:", name, "error:", err)
+	}
+	return runDuration.Seconds()
+}
+
+// constMetric: helper fn for StateCollector, builds a metric with the supplied labels.  A description is built for
+// each metric because the labels of the metrics reported by checks differ from check to check.
+func constMetric(name string, help string, valueType prometheus.ValueType, value float64, labels prometheus.Labels) prometheus.Metric {
+	desc := prometheus.NewDesc(name, help, nil, labels)
+	metric, err := prometheus.NewConstMetric(desc, valueType, value)
+	if err != nil {
+		return prometheus.NewInvalidMetric(desc, err)
+	}
+	return metric
+}
+
+// workloadMetrics: helper fn for StateCollector, builds the metrics of a kind of workload - checkOrJob is literally
+// the string "check" or "job"
+type workloadMetrics struct {
+	checkOrJob     string
+	runDescription string
+	config         PromMetricsConfig
+}
+
+// collect sends the metrics of a single check or job.  The status, severity, duration and reported metrics are left
+// out when includeStatus is false.
+func (m workloadMetrics) collect(ch chan<- prometheus.Metric, name string, d khstatev1.WorkloadDetails, includeStatus bool) {
+	prefix := "kuberhealthy_" + m.checkOrJob
+	labels := prometheus.Labels{"check": name, "namespace": d.Namespace}
+
+	if d.LastRun != nil {
+		ch <- constMetric(prefix+"_last_run_timestamp_seconds", "Shows the unix time of the last run of a Kuberhealthy "+m.checkOrJob, prometheus.GaugeValue, float64(d.LastRun.Unix()), labels)
+	}
+	ch <- constMetric(prefix+"_runs_total", "Counts the runs of a Kuberhealthy "+m.checkOrJob, prometheus.CounterValue, float64(d.TotalRuns), labels)
+	ch <- constMetric(prefix+"_failures_total", "Counts the failed runs of a Kuberhealthy "+m.checkOrJob, prometheus.CounterValue, float64(d.TotalFailures), labels)
+	if !includeStatus {
+		return
 	}
-	metricsOutput += "# HELP kuberhealthy_check_duration_seconds Shows the check run duration of a Kuberhealthy check\n"
-	metricsOutput += "# TYPE kuberhealthy_check_duration_seconds gauge\n"
-	for m, v := range metricCheckDuration {
-		metricsOutput += fmt.Sprintf("%s %s\n", m, v)
+
+	status := promBool(d.OK)
+	statusLabels := prometheus.Labels{"check": name, "namespace": d.Namespace, "status": strconv.FormatFloat(status, 'g', -1, 64)}
+	if !m.config.SuppressErrorLabel {
+		statusLabels["error"] = errorLabelValue(m.config, d.Errors)
+	}
+	ch <- constMetric(prefix, "Shows the status of a Kuberhealthy "+m.checkOrJob, prometheus.GaugeValue, status, statusLabels)
+
+	// the severity is only a value so that a change in severity does not leave a stale series behind
+	ch <- constMetric(prefix+"_severity", "Shows the severity of a Kuberhealthy "+m.checkOrJob+": 0 for OK, 1 for Warning and 2 for Failing", prometheus.GaugeValue, promSeverityValue(d.GetSeverity()), labels)
+	ch <- constMetric(prefix+"_duration_seconds", "Shows the "+m.checkOrJob+" run duration of a Kuberhealthy "+m.checkOrJob, prometheus.GaugeValue, runDurationSeconds(name, d), labels)
+
+	// send any metrics reported by the last run
+	for _, reported := range d.Metrics {
+		ch <- constMetric(prefix+"_metric", "Shows the metrics reported by the "+m.runDescription+" of a Kuberhealthy "+m.checkOrJob, prometheus.GaugeValue, reported.Value, reportedMetricLabels(name, d.Namespace, reported))
+	}
+}
+
+// StateCollector is a prometheus collector that exports a snapshot of the state of kuberhealthy and its checks and jobs
+type StateCollector struct {
+	state  health.State
+	config PromMetricsConfig
+}
+
+// NewStateCollector creates a collector that exports the supplied state
+func NewStateCollector(state health.State, config PromMetricsConfig) *StateCollector {
+	return &StateCollector{
+		state:  state,
+		config: config,
+	}
+}
+
+// Describe sends no descriptions, which registers the collector as unchecked.  The labels of the metrics reported by
+// checks are only known once the state is collected.
+func (c *StateCollector) Describe(chan<- *prometheus.Desc) {}
+
+// Collect sends the metrics of kuberhealthy and of every check and job in the state
+func (c *StateCollector) Collect(ch chan<- prometheus.Metric) {
+	// Kuberhealthy metrics
+	ch <- constMetric("kuberhealthy_running", "Shows if kuberhealthy is running error free", prometheus.GaugeValue, 1, prometheus.Labels{"current_master": c.state.CurrentMaster})
+	ch <- constMetric("kuberhealthy_cluster_state", "Shows the status of the cluster", prometheus.GaugeValue, promBool(c.state.OK), nil)
+
+	// Kuberhealthy check metrics
+	checkMetrics := workloadMetrics{checkOrJob: "check", runDescription: "last run", config: c.config}
+	for name, d := range c.state.CheckDetails {
+		// suspended checks and checks blocked by a failing dependency are shown as such rather than OK or failing
+		labels := prometheus.Labels{"check": name, "namespace": d.Namespace}
+		ch <- constMetric("kuberhealthy_check_suspended", "Shows if a Kuberhealthy check is suspended", prometheus.GaugeValue, promBool(d.Suspended), labels)
+		ch <- constMetric("kuberhealthy_check_blocked", "Shows if a Kuberhealthy check is blocked from running by a failing dependency", prometheus.GaugeValue, promBool(d.Blocked), labels)
+		checkMetrics.collect(ch, name, d, !d.Suspended && !d.Blocked)
 	}
+
 	// Kuberhealthy job metrics
-	metricsOutput += "# HELP kuberhealthy_job Shows the status of a Kuberhealthy job\n"
-	metricsOutput += "# TYPE kuberhealthy_job gauge\n"
-	for m, v := range metricJobState {
-		metricsOutput += fmt.Sprintf("%s %s\n", m, v)
+	jobMetrics := workloadMetrics{checkOrJob: "job", runDescription: "run", config: c.config}
+	for name, d := range c.state.JobDetails {
+		jobMetrics.collect(ch, name, d, true)
+	}
+}
+
+// Handler serves the metrics of a gatherer in the exposition format negotiated with the caller, which is OpenMetrics
+// when the caller accepts it.  Metrics that can not be gathered are logged and left out so the rest are still served.
+func Handler(gatherer prometheus.Gatherer) http.Handler {
+	return promhttp.HandlerFor(gatherer, promhttp.HandlerOpts{
+		EnableOpenMetrics: true,
+		ErrorLog:          log.StandardLogger(),
+		ErrorHandling:     promhttp.ContinueOnError,
+	})
+}
+
+// GatherText gathers the metrics of the supplied collectors from a new registry and returns them in the prometheus
+// text format.  The metrics that could be gathered are returned along with any error.
+func GatherText(collectors ...prometheus.Collector) (string, error) {
+	registry := prometheus.NewRegistry()
+	for _, c := range collectors {
+		err := registry.Register(c)
+		if err != nil {
+			return "", err
+		}
 	}
-	metricsOutput += "# HELP kuberhealthy_job_duration_seconds Shows the job run duration of a Kuberhealthy job\n"
-	metricsOutput += "# TYPE kuberhealthy_job_duration_seconds gauge\n"
-	for m, v := range metricJobDuration {
-		metricsOutput += fmt.Sprintf("%s %s\n", m, v)
+
+	families, err := registry.Gather()
+	var sb strings.Builder
+	for _, f := range families {
+		_, writeErr := expfmt.MetricFamilyToText(&sb, f)
+		if writeErr != nil {
+			return sb.String(), writeErr
+		}
 	}
+	return sb.String(), err
+}
 
+// GenerateMetrics takes the state and returns it in the Prometheus text format
+func GenerateMetrics(state health.State, config PromMetricsConfig) string {
+	metricsOutput, err := GatherText(NewStateCollector(state, config))
+	if err != nil {
+		log.Errorln("Error gathering metrics:", err)
+	}
 	return metricsOutput
 }
 
 //ErrorStateMetrics is a Prometheus metric meant to show Kuberhealthy has error
 func ErrorStateMetrics(state health.State) string {
-	errorOutput := ""
-	errorOutput += "# HELP kuberhealthy_running Shows if kuberhealthy is running error free\n"
-	errorOutput += "# TYPE kuberhealthy_running gauge\n"
-	errorOutput += fmt.Sprintf(`kuberhealthy_running{currentMaster="%s"} 0`, state.CurrentMaster)
+	running := prometheus.NewGauge(prometheus.GaugeOpts{
+		Name:        "kuberhealthy_running",
+		Help:        "Shows if kuberhealthy is running error free",
+		ConstLabels: prometheus.Labels{"currentMaster": state.CurrentMaster},
+	})
+	errorOutput, err := GatherText(running)
+	if err != nil {
+		log.Errorln("Error gathering error state metrics:", err)
+	}
 	return errorOutput
 }
 
@@ -2,8 +2,13 @@ package metrics
 
 import (
 	"net/http/httptest"
+	"strconv"
 	"strings"
 	"testing"
+	"time"
+
+	"github.com/prometheus/client_golang/prometheus"
+	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
 
 	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
 	"github.com/kuberhealthy/kuberhealthy/v2/pkg/health"
@@ -90,13 +95,13 @@ func TestGenerateMetrics(t *testing.T) {
 	if metrics["kuberhealthy_cluster_state"] == "1" {
 		t.Fatal("Kuberhealthy shows cluster as healthy when it isn't")
 	}
-	if metrics[`kuberhealthy_check{check="good",namespace="",status="1",error=""}`] != "1" {
+	if metrics[`kuberhealthy_check{check="good",error="",namespace="",status="1"}`] != "1" {
 		t.Fatal("Kuberhealthy good check shows as bad")
 	}
-	if metrics[`kuberhealthy_check{check="bad",namespace="",status="0",error=""}`] != "0" {
+	if metrics[`kuberhealthy_check{check="bad",error="",namespace="",status="0"}`] != "0" {
 		t.Fatal("Kuberhealthy good check shows as bad")
 	}
-	if metrics[`kuberhealthy_check{check="",namespace="",status="1",error=""}`] != "1" {
+	if metrics[`kuberhealthy_check{check="",error="",namespace="",status="1"}`] != "1" {
 		t.Fatal("Kuberhealthy good check shows as bad")
 	}
 	state = health.State{
@@ -108,7 +113,7 @@ func TestGenerateMetrics(t *testing.T) {
 	}
 	result = GenerateMetrics(state, PromMetricsConfig{})
 	metrics = parseMetrics(result)
-	if metrics[`kuberhealthy_check{check="bad",namespace="",status="0",error="12345678910"}`] != "0" {
+	if metrics[`kuberhealthy_check{check="bad",error="12345678910",namespace="",status="0"}`] != "0" {
 		t.Fatal("Kuberhealthy bad error label check does not match - test 1", metrics)
 	}
 	result = GenerateMetrics(state, PromMetricsConfig{SuppressErrorLabel: true})
@@ -118,7 +123,7 @@ func TestGenerateMetrics(t *testing.T) {
 	}
 	result = GenerateMetrics(state, PromMetricsConfig{SuppressErrorLabel: false, ErrorLabelMaxLength: 4})
 	metrics = parseMetrics(result)
-	if metrics[`kuberhealthy_check{check="bad",namespace="",status="0",error="1234"}`] != "0" {
+	if metrics[`kuberhealthy_check{check="bad",error="1234",namespace="",status="0"}`] != "0" {
 		t.Fatal("Kuberhealthy bad error label check does not match - test 3", metrics)
 	}
 	state = health.State{
@@ -130,9 +135,109 @@ func TestGenerateMetrics(t *testing.T) {
 	}
 	result = GenerateMetrics(state, PromMetricsConfig{SuppressErrorLabel: false, ErrorLabelMaxLength: 10})
 	metrics = parseMetrics(result)
-	if metrics[`kuberhealthy_check{check="bad",namespace="",status="0",error="123"}`] != "0" {
+	if metrics[`kuberhealthy_check{check="bad",error="123",namespace="",status="0"}`] != "0" {
 		t.Fatal("Kuberhealthy bad error label check does not match - test 4", metrics)
 	}
+	// Test check severities
+	state = health.State{
+		CheckDetails: map[string]khstatev1.WorkloadDetails{
+			"ok": {
+				OK: true,
+			},
+			"degraded": {
+				OK:       true,
+				Severity: khstatev1.SeverityWarning,
+			},
+			"failing": {
+				OK:       false,
+				Severity: khstatev1.SeverityWarning,
+			},
+		},
+	}
+	result = GenerateMetrics(state, PromMetricsConfig{})
+	metrics = parseMetrics(result)
+	if metrics[`kuberhealthy_check_severity{check="ok",namespace=""}`] != "0" {
+		t.Fatal("Kuberhealthy OK check severity does not match", metrics)
+	}
+	if metrics[`kuberhealthy_check_severity{check="degraded",namespace=""}`] != "1" {
+		t.Fatal("Kuberhealthy warning check severity does not match", metrics)
+	}
+	if metrics[`kuberhealthy_check_severity{check="failing",namespace=""}`] != "2" {
+		t.Fatal("Kuberhealthy failing check severity does not match", metrics)
+	}
+	// Test with metrics reported by a check and a job
+	state = health.State{
+		CheckDetails: map[string]khstatev1.WorkloadDetails{
+			"reporter": {
+				OK:        true,
+				Namespace: "kuberhealthy",
+				Metrics: []khstatev1.Metric{
+					{Name: "request_duration", Value: 0.25, Unit: "seconds", Labels: map[string]string{"url": "example.com", "check": "override", "status-code": "200"}},
+					{Name: "requests_failed", Value: 0},
+				},
+			},
+		},
+		JobDetails: map[string]khstatev1.WorkloadDetails{
+			"job": {
+				OK:        true,
+				Namespace: "kuberhealthy",
+				Metrics: []khstatev1.Metric{
+					{Name: "image_size", Value: 1024, Unit: "bytes"},
+				},
+			},
+		},
+	}
+	result = GenerateMetrics(state, PromMetricsConfig{})
+	metrics = parseMetrics(result)
+	if metrics[`kuberhealthy_check_metric{check="reporter",metric="request_duration",namespace="kuberhealthy",status_code="200",unit="seconds",url="example.com"}`] != "0.25" {
+		t.Fatal("Kuberhealthy reported check metric with labels does not match", metrics)
+	}
+	if metrics[`kuberhealthy_check_metric{check="reporter",metric="requests_failed",namespace="kuberhealthy",unit=""}`] != "0" {
+		t.Fatal("Kuberhealthy reported check metric without labels does not match", metrics)
+	}
+	if metrics[`kuberhealthy_job_metric{check="job",metric="image_size",namespace="kuberhealthy",unit="bytes"}`] != "1024" {
+		t.Fatal("Kuberhealthy reported job metric does not match", metrics)
+	}
+	// Test suspended checks
+	state = health.State{
+		CheckDetails: map[string]khstatev1.WorkloadDetails{
+			"running": {
+				OK:        true,
+				Namespace: "kuberhealthy",
+			},
+			"paused": {
+				OK:        false,
+				Namespace: "kuberhealthy",
+				Suspended: true,
+			},
+			"downstream": {
+				OK:            false,
+				Namespace:     "kuberhealthy",
+				Blocked:       true,
+				BlockedReason: "dependency kuberhealthy/dns is failing",
+			},
+		},
+	}
+	result = GenerateMetrics(state, PromMetricsConfig{SuppressErrorLabel: true})
+	metrics = parseMetrics(result)
+	if metrics[`kuberhealthy_check_suspended{check="running",namespace="kuberhealthy"}`] != "0" {
+		t.Fatal("Kuberhealthy running check shows as suspended", metrics)
+	}
+	if metrics[`kuberhealthy_check_suspended{check="paused",namespace="kuberhealthy"}`] != "1" {
+		t.Fatal("Kuberhealthy suspended check does not show as suspended", metrics)
+	}
+	if _, exists := metrics[`kuberhealthy_check{check="paused",namespace="kuberhealthy",status="0"}`]; exists {
+		t.Fatal("Kuberhealthy suspended check shows as failing", metrics)
+	}
+	if metrics[`kuberhealthy_check_blocked{check="downstream",namespace="kuberhealthy"}`] != "1" {
+		t.Fatal("Kuberhealthy blocked check does not show as blocked", metrics)
+	}
+	if metrics[`kuberhealthy_check_blocked{check="running",namespace="kuberhealthy"}`] != "0" {
+		t.Fatal("Kuberhealthy running check shows as blocked", metrics)
+	}
+	if _, exists := metrics[`kuberhealthy_check{check="downstream",namespace="kuberhealthy",status="0"}`]; exists {
+		t.Fatal("Kuberhealthy blocked check shows as failing", metrics)
+	}
 }
 
 func TestErrorStateMetrics(t *testing.T) {
@@ -181,3 +286,135 @@ func TestWriteMetricError(t *testing.T) {
 		t.Fatal("Error Metric does not match actual error metric function")
 	}
 }
+
+// TestGenerateMetricsFormat tests that label values are escaped and that metric output is stable between calls
+func TestGenerateMetricsFormat(t *testing.T) {
+	state := health.State{
+		CheckDetails: map[string]khstatev1.WorkloadDetails{
+			"b": {Namespace: "kuberhealthy", Errors: []string{`pod "web" crashed`, "path C:\\tmp\nnext line"}},
+			"a": {Namespace: "kuberhealthy", OK: true},
+			"c": {Namespace: "default", OK: true},
+		},
+	}
+	result := GenerateMetrics(state, PromMetricsConfig{})
+	if !strings.Contains(result, `kuberhealthy_check{check="b",error="pod \"web\" crashed|path C:\\tmp\nnext line",namespace="kuberhealthy",status="0"} 0`+"\n") {
+		t.Fatal("Kuberhealthy error label is not escaped", result)
+	}
+	for i := 0; i < 10; i++ {
+		if GenerateMetrics(state, PromMetricsConfig{}) != result {
+			t.Fatal("Kuberhealthy metrics output is not stable between calls")
+		}
+	}
+	first := strings.Index(result, `kuberhealthy_check{check="a"`)
+	second := strings.Index(result, `kuberhealthy_check{check="b"`)
+	if first == -1 || second == -1 || first > second {
+		t.Fatal("Kuberhealthy check metrics are not sorted", result)
+	}
+
+	// the error label is truncated without splitting a character
+	state = health.State{
+		CheckDetails: map[string]khstatev1.WorkloadDetails{
+			"bad": {Errors: []string{"ab€"}},
+		},
+	}
+	metrics := parseMetrics(GenerateMetrics(state, PromMetricsConfig{ErrorLabelMaxLength: 4}))
+	if metrics[`kuberhealthy_check{check="bad",error="ab",namespace="",status="0"}`] != "0" {
+		t.Fatal("Kuberhealthy error label is not truncated on a character boundary", metrics)
+	}
+}
+
+// TestGenerateMetricsRunSeries tests the last run time and run counters of checks and jobs
+func TestGenerateMetricsRunSeries(t *testing.T) {
+	lastRun := metav1.NewTime(time.Unix(1700000123, 0))
+	state := health.State{
+		CheckDetails: map[string]khstatev1.WorkloadDetails{
+			"dns":    {OK: true, Namespace: "kuberhealthy", LastRun: &lastRun, TotalRuns: 12, TotalFailures: 3},
+			"new":    {OK: true, Namespace: "kuberhealthy"},
+			"paused": {Namespace: "kuberhealthy", Suspended: true, TotalRuns: 4, TotalFailures: 4},
+		},
+		JobDetails: map[string]khstatev1.WorkloadDetails{
+			"job": {OK: true, Namespace: "kuberhealthy", LastRun: &lastRun, TotalRuns: 1},
+		},
+	}
+	result := GenerateMetrics(state, PromMetricsConfig{})
+	metrics := parseMetrics(result)
+	lastRunTime, err := strconv.ParseFloat(metrics[`kuberhealthy_check_last_run_timestamp_seconds{check="dns",namespace="kuberhealthy"}`], 64)
+	if err != nil || lastRunTime != 1700000123 {
+		t.Fatal("Kuberhealthy check last run time does not match", metrics)
+	}
+	if _, exists := metrics[`kuberhealthy_check_last_run_timestamp_seconds{check="new",namespace="kuberhealthy"}`]; exists {
+		t.Fatal("Kuberhealthy check that never ran shows a last run time", metrics)
+	}
+	if metrics[`kuberhealthy_check_runs_total{check="dns",namespace="kuberhealthy"}`] != "12" {
+		t.Fatal("Kuberhealthy check run count does not match", metrics)
+	}
+	if metrics[`kuberhealthy_check_failures_total{check="dns",namespace="kuberhealthy"}`] != "3" {
+		t.Fatal("Kuberhealthy check failure count does not match", metrics)
+	}
+	if metrics[`kuberhealthy_check_runs_total{check="paused",namespace="kuberhealthy"}`] != "4" {
+		t.Fatal("Kuberhealthy suspended check run count does not match", metrics)
+	}
+	if metrics[`kuberhealthy_job_runs_total{check="job",namespace="kuberhealthy"}`] != "1" {
+		t.Fatal("Kuberhealthy job run count does not match", metrics)
+	}
+	if !strings.Contains(result, "# TYPE kuberhealthy_check_runs_total counter\n") {
+		t.Fatal("Kuberhealthy check run count is not a counter", result)
+	}
+}
+
+// TestHandlerOpenMetrics tests that the OpenMetrics format is served to callers that accept it
+func TestHandlerOpenMetrics(t *testing.T) {
+	state := health.State{
+		OK: true,
+		CheckDetails: map[string]khstatev1.WorkloadDetails{
+			"dns": {OK: true, Namespace: "kuberhealthy", TotalRuns: 2},
+		},
+	}
+	registry := prometheus.NewRegistry()
+	registry.MustRegister(NewStateCollector(state, PromMetricsConfig{}))
+
+	request := httptest.NewRequest("GET", "/metrics", nil)
+	recorder := httptest.NewRecorder()
+	Handler(registry).ServeHTTP(recorder, request)
+	if !strings.HasPrefix(recorder.Header().Get("Content-Type"), "text/plain") {
+		t.Fatal("Expected the prometheus text format by default but got", recorder.Header().Get("Content-Type"))
+	}
+	if strings.Contains(recorder.Body.String(), "# EOF") {
+		t.Fatal("Prometheus text format should not end with an EOF marker")
+	}
+
+	request = httptest.NewRequest("GET", "/metrics", nil)
+	request.Header.Set("Accept", "application/openmetrics-text;version=1.0.0,text/plain;version=0.0.4;q=0.5")
+	recorder = httptest.NewRecorder()
+	Handler(registry).ServeHTTP(recorder, request)
+	if !strings.HasPrefix(recorder.Header().Get("Content-Type"), "application/openmetrics-text") {
+		t.Fatal("Expected the OpenMetrics format when accepted but got", recorder.Header().Get("Content-Type"))
+	}
+	body := recorder.Body.String()
+	if !strings.HasSuffix(body, "# EOF\n") {
+		t.Fatal("OpenMetrics output does not end with an EOF marker", body)
+	}
+	if !strings.Contains(body, "# TYPE kuberhealthy_check_runs counter\n") || !strings.Contains(body, `kuberhealthy_check_runs_total{check="dns",namespace="kuberhealthy"} 2`) {
+		t.Fatal("OpenMetrics counter is not named correctly", body)
+	}
+}
+
+// TestGenerateMetricsReservedLabels tests that labels reported by a check that prometheus reserves are dropped rather
+// than making the metric invalid
+func TestGenerateMetricsReservedLabels(t *testing.T) {
+	state := health.State{
+		CheckDetails: map[string]khstatev1.WorkloadDetails{
+			"reporter": {
+				OK:        true,
+				Namespace: "kuberhealthy",
+				Metrics: []khstatev1.Metric{
+					{Name: "latency", Value: 1, Labels: map[string]string{"__name__": "override", "zone": "a"}},
+				},
+			},
+		},
+	}
+	metrics := parseMetrics(GenerateMetrics(state, PromMetricsConfig{}))
+	if metrics[`kuberhealthy_check_metric{check="reporter",metric="latency",namespace="kuberhealthy",unit="",zone="a"}`] != "1" {
+		t.Fatal("Kuberhealthy reported check metric with a reserved label does not match", metrics)
+	}
+}
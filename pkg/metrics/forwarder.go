@@ -0,0 +1,289 @@
+package metrics
+
+import (
+	"errors"
+	"io"
+	"net/url"
+	"sync"
+	"time"
+
+	"github.com/prometheus/client_golang/prometheus"
+	log "github.com/sirupsen/logrus"
+)
+
+// The types of metric forwarders that can be configured
+const (
+	InfluxForwarder    = "influx"
+	DogStatsDForwarder = "dogstatsd"
+	OTLPForwarder      = "otlp"
+)
+
+// DefaultForwarderQueueSize is how many pushes are buffered for a metric forwarder before new pushes are dropped
+const DefaultForwarderQueueSize = 100
+
+// InfluxForwarderConfig configures the forwarding of metrics to InfluxDB
+type InfluxForwarderConfig struct {
+	URL      string            `yaml:"url"`                // the URL of the InfluxDB server
+	Database string            `yaml:"database,omitempty"` // the InfluxDB 1.x database to write to
+	Username string            `yaml:"username,omitempty"`
+	Password string            `yaml:"password,omitempty"`
+	Org      string            `yaml:"org,omitempty"`    // the InfluxDB 2.x organization to write to
+	Bucket   string            `yaml:"bucket,omitempty"` // the InfluxDB 2.x bucket to write to.  Setting a bucket selects the 2.x API.
+	Token    string            `yaml:"token,omitempty"`  // the InfluxDB 2.x API token
+	TLS      InfluxTLSConfig   `yaml:"tls,omitempty"`
+	Batch    InfluxBatchConfig `yaml:"batch,omitempty"`
+}
+
+// LineConfig parses the InfluxDB URL and returns the configuration of an InfluxLineClient
+func (c InfluxForwarderConfig) LineConfig() (InfluxLineConfig, error) {
+	influxURL, err := url.Parse(c.URL)
+	if err != nil {
+		return InfluxLineConfig{}, errors.New("Unable to parse influx URL: " + err.Error())
+	}
+	return InfluxLineConfig{
+		URL:      *influxURL,
+		Database: c.Database,
+		Username: c.Username,
+		Password: c.Password,
+		Org:      c.Org,
+		Bucket:   c.Bucket,
+		Token:    c.Token,
+		TLS:      c.TLS,
+		Batch:    c.Batch,
+	}, nil
+}
+
+// ForwarderConfig configures a single metric forwarder
+type ForwarderConfig struct {
+	Name       string                `yaml:"name,omitempty"`       // identifies the forwarder in logs and metrics (default: the type)
+	Type       string                `yaml:"type"`                 // influx, dogstatsd or otlp
+	Enabled    bool                  `yaml:"enabled"`              // only enabled forwarders are sent metrics
+	TagMapping map[string]string     `yaml:"tagMapping,omitempty"` // renames tags before they are sent.  Mapping a tag to "" drops it.
+	QueueSize  int                   `yaml:"queueSize,omitempty"`  // how many pushes are buffered while the forwarder is busy (default: 100)
+	Influx     InfluxForwarderConfig `yaml:"influx,omitempty"`
+	DogStatsD  DogStatsDConfig       `yaml:"dogStatsD,omitempty"`
+	OTLP       OTLPConfig            `yaml:"otlp,omitempty"`
+}
+
+// NewForwarderClient creates the metric client for the type of a forwarder configuration
+func NewForwarderClient(config ForwarderConfig) (Client, error) {
+	switch config.Type {
+	case InfluxForwarder:
+		lineConfig, err := config.Influx.LineConfig()
+		if err != nil {
+			return nil, err
+		}
+		return NewInfluxLineClient(lineConfig)
+	case DogStatsDForwarder:
+		return NewDogStatsDClient(config.DogStatsD)
+	case OTLPForwarder:
+		return NewOTLPClient(config.OTLP)
+	default:
+		return nil, errors.New("unknown metric forwarder type: " + config.Type)
+	}
+}
+
+// ForwarderStats describes the health of a metric forwarder
+type ForwarderStats struct {
+	Name        string
+	Type        string
+	Pushes      int64     // the number of pushes attempted
+	Failures    int64     // the number of pushes that failed
+	Dropped     int64     // the number of pushes dropped because the queue was full
+	Queued      int       // the number of pushes waiting to be sent
+	LastSuccess time.Time // the last time a push succeeded
+	LastError   string    // the error of the last push, empty when it succeeded
+}
+
+// forwarderPush is a single push waiting to be sent by a forwarder
+type forwarderPush struct {
+	points Metric
+	tags   map[string]string
+}
+
+// Forwarder sends metrics to a single metric client from its own queue, so a slow or failing client does not hold up
+// the other forwarders or the check that pushed the metrics
+type Forwarder struct {
+	name          string
+	forwarderType string
+	client        Client
+	tagMapping    map[string]string
+	queue         chan forwarderPush
+	done          chan struct{}
+	mu            sync.Mutex
+	closed        bool // set once the queue is closed so that later pushes are dropped
+	stats         ForwarderStats
+}
+
+// NewForwarder creates a Forwarder for a metric client and starts sending its queued pushes in the background
+func NewForwarder(name string, forwarderType string, client Client, tagMapping map[string]string, queueSize int) *Forwarder {
+	if queueSize <= 0 {
+		queueSize = DefaultForwarderQueueSize
+	}
+	f := &Forwarder{
+		name:          name,
+		forwarderType: forwarderType,
+		client:        client,
+		tagMapping:    tagMapping,
+		queue:         make(chan forwarderPush, queueSize),
+		done:          make(chan struct{}),
+		stats:         ForwarderStats{Name: name, Type: forwarderType},
+	}
+	go f.run()
+	return f
+}
+
+// enqueue queues a push without blocking.  The push is dropped when the queue is full or the forwarder is closed.
+func (f *Forwarder) enqueue(points Metric, tags map[string]string) {
+	f.mu.Lock()
+	defer f.mu.Unlock()
+	if f.closed {
+		return
+	}
+
+	select {
+	case f.queue <- forwarderPush{points: points, tags: mapTags(f.tagMapping, tags)}:
+	default:
+		f.stats.Dropped++
+		log.Warningln("Metric forwarder", f.name, "is falling behind. Dropping metrics.")
+	}
+}
+
+// run sends queued pushes to the metric client until the queue is closed
+func (f *Forwarder) run() {
+	defer close(f.done)
+	for p := range f.queue {
+		err := f.client.Push(p.points, p.tags)
+
+		f.mu.Lock()
+		f.stats.Pushes++
+		if err != nil {
+			f.stats.Failures++
+			f.stats.LastError = err.Error()
+		} else {
+			f.stats.LastSuccess = time.Now()
+			f.stats.LastError = ""
+		}
+		f.mu.Unlock()
+
+		if err != nil {
+			log.Errorln("Error forwarding metrics to", f.name+":", err)
+		}
+	}
+}
+
+// Stats returns the current health of the forwarder
+func (f *Forwarder) Stats() ForwarderStats {
+	f.mu.Lock()
+	defer f.mu.Unlock()
+	stats := f.stats
+	stats.Queued = len(f.queue)
+	return stats
+}
+
+// close stops the forwarder once its queued pushes are sent and closes its client if it can be closed
+func (f *Forwarder) close() error {
+	f.mu.Lock()
+	if !f.closed {
+		f.closed = true
+		close(f.queue)
+	}
+	f.mu.Unlock()
+	<-f.done
+	if closer, ok := f.client.(io.Closer); ok {
+		return closer.Close()
+	}
+	return nil
+}
+
+// mapTags returns a copy of the tags with the tag mapping applied.  Tags mapped to an empty name are dropped.
+func mapTags(mapping map[string]string, tags map[string]string) map[string]string {
+	mapped := make(map[string]string, len(tags))
+	for k, v := range tags {
+		if newName, ok := mapping[k]; ok {
+			if len(newName) == 0 {
+				continue
+			}
+			k = newName
+		}
+		mapped[k] = v
+	}
+	return mapped
+}
+
+// FanOut is a metric client that sends every push to a set of forwarders
+type FanOut struct {
+	forwarders []*Forwarder
+}
+
+// NewFanOut creates a FanOut that pushes to the supplied forwarders
+func NewFanOut(forwarders ...*Forwarder) *FanOut {
+	return &FanOut{forwarders: forwarders}
+}
+
+// Push queues the metrics on every forwarder.  Forwarders send and report errors on their own, so Push does not wait
+// for them and always returns nil.
+func (f *FanOut) Push(points Metric, tags map[string]string) error {
+	for _, forwarder := range f.forwarders {
+		forwarder.enqueue(points, tags)
+	}
+	return nil
+}
+
+// Stats returns the health of every forwarder
+func (f *FanOut) Stats() []ForwarderStats {
+	stats := make([]ForwarderStats, 0, len(f.forwarders))
+	for _, forwarder := range f.forwarders {
+		stats = append(stats, forwarder.Stats())
+	}
+	return stats
+}
+
+// The descriptions of the metrics that show the health of the metric forwarders
+var (
+	forwarderLabels          = []string{"forwarder", "type"}
+	forwarderUpDesc          = prometheus.NewDesc("kuberhealthy_metric_forwarder_up", "Shows if the last push of a Kuberhealthy metric forwarder succeeded", forwarderLabels, nil)
+	forwarderPushesDesc      = prometheus.NewDesc("kuberhealthy_metric_forwarder_pushes_total", "Counts the pushes attempted by a Kuberhealthy metric forwarder", forwarderLabels, nil)
+	forwarderFailuresDesc    = prometheus.NewDesc("kuberhealthy_metric_forwarder_failures_total", "Counts the failed pushes of a Kuberhealthy metric forwarder", forwarderLabels, nil)
+	forwarderDroppedDesc     = prometheus.NewDesc("kuberhealthy_metric_forwarder_dropped_total", "Counts the pushes dropped by a Kuberhealthy metric forwarder because its queue was full", forwarderLabels, nil)
+	forwarderQueuedDesc      = prometheus.NewDesc("kuberhealthy_metric_forwarder_queue_length", "Shows the pushes waiting to be sent by a Kuberhealthy metric forwarder", forwarderLabels, nil)
+	forwarderLastSuccessDesc = prometheus.NewDesc("kuberhealthy_metric_forwarder_last_success_timestamp_seconds", "Shows the unix time of the last successful push of a Kuberhealthy metric forwarder", forwarderLabels, nil)
+)
+
+// Describe sends the descriptions of the forwarder health metrics so the fan out can be registered as a prometheus
+// collector
+func (f *FanOut) Describe(ch chan<- *prometheus.Desc) {
+	ch <- forwarderUpDesc
+	ch <- forwarderPushesDesc
+	ch <- forwarderFailuresDesc
+	ch <- forwarderDroppedDesc
+	ch <- forwarderQueuedDesc
+	ch <- forwarderLastSuccessDesc
+}
+
+// Collect sends the health of every forwarder
+func (f *FanOut) Collect(ch chan<- prometheus.Metric) {
+	for _, s := range f.Stats() {
+		ch <- prometheus.MustNewConstMetric(forwarderUpDesc, prometheus.GaugeValue, promBool(len(s.LastError) == 0), s.Name, s.Type)
+		ch <- prometheus.MustNewConstMetric(forwarderPushesDesc, prometheus.CounterValue, float64(s.Pushes), s.Name, s.Type)
+		ch <- prometheus.MustNewConstMetric(forwarderFailuresDesc, prometheus.CounterValue, float64(s.Failures), s.Name, s.Type)
+		ch <- prometheus.MustNewConstMetric(forwarderDroppedDesc, prometheus.CounterValue, float64(s.Dropped), s.Name, s.Type)
+		ch <- prometheus.MustNewConstMetric(forwarderQueuedDesc, prometheus.GaugeValue, float64(s.Queued), s.Name, s.Type)
+		if !s.LastSuccess.IsZero() {
+			ch <- prometheus.MustNewConstMetric(forwarderLastSuccessDesc, prometheus.GaugeValue, float64(s.LastSuccess.Unix()), s.Name, s.Type)
+		}
+	}
+}
+
+// Close sends the queued pushes of every forwarder and closes their clients
+func (f *FanOut) Close() error {
+	var closeErr error
+	for _, forwarder := range f.forwarders {
+		err := forwarder.close()
+		if err != nil {
+			log.Errorln("Error closing metric forwarder", forwarder.name+":", err)
+			closeErr = err
+		}
+	}
+	return closeErr
+}
@@ -0,0 +1,103 @@
+package metrics
+
+import (
+	"errors"
+	"strings"
+	"sync"
+	"testing"
+	"time"
+)
+
+// recordingClient records the tags of every push and fails when told to
+type recordingClient struct {
+	mu     sync.Mutex
+	tags   []map[string]string
+	fail   bool
+	block  chan struct{}
+	pushed chan struct{}
+}
+
+func (c *recordingClient) Push(points Metric, tags map[string]string) error {
+	if c.block != nil {
+		<-c.block
+	}
+	c.mu.Lock()
+	c.tags = append(c.tags, tags)
+	c.mu.Unlock()
+	c.pushed <- struct{}{}
+	if c.fail {
+		return errors.New("push failed")
+	}
+	return nil
+}
+
+// TestFanOutIsolatesForwarders tests that a blocked forwarder does not hold up the others, that tags are mapped per
+// forwarder and that the health of each forwarder is tracked
+func TestFanOutIsolatesForwarders(t *testing.T) {
+	slow := &recordingClient{block: make(chan struct{}), pushed: make(chan struct{}, 10)}
+	failing := &recordingClient{fail: true, pushed: make(chan struct{}, 10)}
+	fanOut := NewFanOut(
+		NewForwarder("slow", InfluxForwarder, slow, nil, 1),
+		NewForwarder("failing", OTLPForwarder, failing, map[string]string{"Name": "check", "Errors": ""}, 0),
+	)
+
+	tags := map[string]string{"Name": "dns", "Namespace": "kuberhealthy", "Errors": "timeout"}
+	for i := 0; i < 3; i++ {
+		err := fanOut.Push(Metric{{"dns.kuberhealthy": 1}}, tags)
+		if err != nil {
+			t.Fatal("Error pushing metrics:", err)
+		}
+	}
+
+	// the failing forwarder receives every push while the slow one is still blocked on its first
+	for i := 0; i < 3; i++ {
+		select {
+		case <-failing.pushed:
+		case <-time.After(time.Second * 5):
+			t.Fatal("Timed out waiting for the failing forwarder to be pushed to")
+		}
+	}
+	failing.mu.Lock()
+	mapped := failing.tags[0]
+	failing.mu.Unlock()
+	if mapped["check"] != "dns" || len(mapped["Name"]) != 0 || len(mapped["Errors"]) != 0 || mapped["Namespace"] != "kuberhealthy" {
+		t.Fatal("Unexpected mapped tags:", mapped)
+	}
+	if tags["Name"] != "dns" {
+		t.Fatal("Tag mapping changed the pushed tags")
+	}
+
+	close(slow.block)
+	err := fanOut.Close()
+	if err != nil {
+		t.Fatal("Error closing fan out:", err)
+	}
+
+	// pushes after closing, such as reports coming in during shutdown, are dropped
+	err = fanOut.Push(Metric{{"dns.kuberhealthy": 1}}, tags)
+	if err != nil {
+		t.Fatal("Error pushing metrics after closing:", err)
+	}
+
+	stats := fanOut.Stats()
+	if stats[0].Dropped == 0 || stats[0].Failures != 0 || len(stats[0].LastError) != 0 {
+		t.Fatalf("Unexpected stats for the slow forwarder: %+v", stats[0])
+	}
+	if stats[1].Pushes != 3 || stats[1].Failures != 3 || stats[1].LastError != "push failed" {
+		t.Fatalf("Unexpected stats for the failing forwarder: %+v", stats[1])
+	}
+
+	output, err := GatherText(fanOut)
+	if err != nil {
+		t.Fatal("Error gathering forwarder health metrics:", err)
+	}
+	for _, expected := range []string{
+		`kuberhealthy_metric_forwarder_up{forwarder="failing",type="otlp"} 0`,
+		`kuberhealthy_metric_forwarder_up{forwarder="slow",type="influx"} 1`,
+		`kuberhealthy_metric_forwarder_failures_total{forwarder="failing",type="otlp"} 3`,
+	} {
+		if !strings.Contains(output, expected) {
+			t.Fatal("Expected forwarder health metric", expected, "in", output)
+		}
+	}
+}
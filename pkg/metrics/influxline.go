@@ -0,0 +1,377 @@
+package metrics
+
+import (
+	"bytes"
+	"crypto/tls"
+	"crypto/x509"
+	"errors"
+	"fmt"
+	"io"
+	"math"
+	"net/http"
+	"net/url"
+	"os"
+	"sort"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+
+	log "github.com/sirupsen/logrus"
+)
+
+// Defaults for batching writes to InfluxDB
+const (
+	DefaultInfluxBatchSize     = 100
+	DefaultInfluxFlushInterval = time.Second * 10
+	DefaultInfluxMaxRetries    = 3
+	DefaultInfluxRetryInterval = time.Second
+	DefaultInfluxTimeout       = time.Second * 10
+)
+
+// InfluxTLSConfig holds the TLS options used to connect to InfluxDB
+type InfluxTLSConfig struct {
+	CAFile             string `yaml:"caFile,omitempty"`             // a PEM file of CAs trusted to sign the InfluxDB server certificate
+	CertFile           string `yaml:"certFile,omitempty"`           // a PEM client certificate presented to InfluxDB
+	KeyFile            string `yaml:"keyFile,omitempty"`            // the PEM key of the client certificate
+	InsecureSkipVerify bool   `yaml:"insecureSkipVerify,omitempty"` // skip verifying the InfluxDB server certificate
+}
+
+// InfluxBatchConfig holds the options for batching and retrying writes to InfluxDB
+type InfluxBatchConfig struct {
+	BatchSize     int           `yaml:"batchSize,omitempty"`     // the number of points written at once (default: 100)
+	FlushInterval time.Duration `yaml:"flushInterval,omitempty"` // how often buffered points are written (default: 10s)
+	MaxRetries    int           `yaml:"maxRetries,omitempty"`    // how many times a failed write is retried (default: 3, -1 disables retries)
+	RetryInterval time.Duration `yaml:"retryInterval,omitempty"` // the delay before the first retry, doubled for each retry (default: 1s)
+	Timeout       time.Duration `yaml:"timeout,omitempty"`       // how long a single write may take (default: 10s)
+}
+
+// withDefaults returns a copy of the config with defaults filled in for any unset values
+func (c InfluxBatchConfig) withDefaults() InfluxBatchConfig {
+	if c.BatchSize <= 0 {
+		c.BatchSize = DefaultInfluxBatchSize
+	}
+	if c.FlushInterval == 0 {
+		c.FlushInterval = DefaultInfluxFlushInterval
+	}
+	if c.MaxRetries == 0 {
+		c.MaxRetries = DefaultInfluxMaxRetries
+	}
+	if c.RetryInterval == 0 {
+		c.RetryInterval = DefaultInfluxRetryInterval
+	}
+	if c.Timeout == 0 {
+		c.Timeout = DefaultInfluxTimeout
+	}
+	return c
+}
+
+// InfluxLineConfig configures an InfluxLineClient.  Setting a bucket writes to the InfluxDB 2.x API with the org,
+// bucket and token, otherwise the InfluxDB 1.x API is used with the database, username and password.
+type InfluxLineConfig struct {
+	URL      url.URL
+	Database string // the InfluxDB 1.x database
+	Username string // the InfluxDB 1.x username
+	Password string // the InfluxDB 1.x password
+	Org      string // the InfluxDB 2.x organization
+	Bucket   string // the InfluxDB 2.x bucket
+	Token    string // the InfluxDB 2.x API token
+	TLS      InfluxTLSConfig
+	Batch    InfluxBatchConfig
+}
+
+// InfluxLineClient buffers metrics as InfluxDB line protocol and writes them in batches over HTTP to InfluxDB 1.x
+// or 2.x.  Failed writes are retried with exponential backoff.
+type InfluxLineClient struct {
+	config    InfluxLineConfig
+	writeURL  string
+	client    *http.Client
+	mu        sync.Mutex
+	lines     []string      // the buffered points waiting to be written
+	flushChan chan struct{} // notified when a full batch is buffered
+	flushMu   sync.Mutex    // makes sure only one batch is written at a time
+	stopChan  chan struct{}
+	stopOnce  sync.Once
+}
+
+// NewInfluxLineClient creates an InfluxLineClient and starts writing buffered points in the background
+func NewInfluxLineClient(config InfluxLineConfig) (*InfluxLineClient, error) {
+	config.Batch = config.Batch.withDefaults()
+
+	writeURL, err := influxWriteURL(config)
+	if err != nil {
+		return nil, err
+	}
+
+	tlsConfig, err := influxTLSConfig(config.TLS)
+	if err != nil {
+		return nil, err
+	}
+
+	c := &InfluxLineClient{
+		config:   config,
+		writeURL: writeURL,
+		client: &http.Client{
+			Timeout:   config.Batch.Timeout,
+			Transport: &http.Transport{Proxy: http.ProxyFromEnvironment, TLSClientConfig: tlsConfig},
+		},
+		flushChan: make(chan struct{}, 1),
+		stopChan:  make(chan struct{}),
+	}
+	go c.flushLoop()
+	return c, nil
+}
+
+// influxWriteURL builds the write endpoint for the configured InfluxDB version
+func influxWriteURL(config InfluxLineConfig) (string, error) {
+	if len(config.URL.Host) == 0 {
+		return "", errors.New("an InfluxDB URL is required")
+	}
+
+	u := config.URL
+	query := url.Values{}
+	query.Set("precision", "ns")
+	if len(config.Bucket) > 0 {
+		if len(config.Org) == 0 {
+			return "", errors.New("an InfluxDB org is required when writing to a bucket")
+		}
+		u.Path = strings.TrimSuffix(u.Path, "/") + "/api/v2/write"
+		query.Set("org", config.Org)
+		query.Set("bucket", config.Bucket)
+	} else {
+		if len(config.Database) == 0 {
+			return "", errors.New("an InfluxDB database or bucket is required")
+		}
+		u.Path = strings.TrimSuffix(u.Path, "/") + "/write"
+		query.Set("db", config.Database)
+	}
+	u.RawQuery = query.Encode()
+	return u.String(), nil
+}
+
+// influxTLSConfig builds the TLS config used to connect to InfluxDB
+func influxTLSConfig(config InfluxTLSConfig) (*tls.Config, error) {
+	tlsConfig := &tls.Config{
+		InsecureSkipVerify: config.InsecureSkipVerify,
+	}
+
+	if len(config.CAFile) > 0 {
+		caPEM, err := os.ReadFile(config.CAFile)
+		if err != nil {
+			return nil, errors.New("failed to read InfluxDB CA file: " + err.Error())
+		}
+		pool := x509.NewCertPool()
+		if !pool.AppendCertsFromPEM(caPEM) {
+			return nil, errors.New("no certificates found in InfluxDB CA file " + config.CAFile)
+		}
+		tlsConfig.RootCAs = pool
+	}
+
+	if len(config.CertFile) > 0 || len(config.KeyFile) > 0 {
+		cert, err := tls.LoadX509KeyPair(config.CertFile, config.KeyFile)
+		if err != nil {
+			return nil, errors.New("failed to load InfluxDB client certificate: " + err.Error())
+		}
+		tlsConfig.Certificates = []tls.Certificate{cert}
+	}
+
+	return tlsConfig, nil
+}
+
+// Push accepts a list of metrics, with a metric being defined as a map of string (name) to interface (value), and
+// buffers them as points to be written in the next batch
+func (c *InfluxLineClient) Push(points Metric, tags map[string]string) error {
+	now := time.Now()
+
+	var lines []string
+	for _, p := range points {
+		for key, val := range p {
+			line, err := influxLine(key, tags, val, now)
+			if err != nil {
+				return err
+			}
+			lines = append(lines, line)
+		}
+	}
+
+	c.mu.Lock()
+	c.lines = append(c.lines, lines...)
+
+	// never buffer more than ten batches when InfluxDB is unavailable
+	maxBuffered := c.config.Batch.BatchSize * 10
+	if len(c.lines) > maxBuffered {
+		dropped := len(c.lines) - maxBuffered
+		c.lines = c.lines[dropped:]
+		log.Warningln("Dropped", dropped, "buffered InfluxDB points because the buffer is full")
+	}
+	full := len(c.lines) >= c.config.Batch.BatchSize
+	c.mu.Unlock()
+
+	if full {
+		select {
+		case c.flushChan <- struct{}{}:
+		default:
+		}
+	}
+	return nil
+}
+
+// flushLoop writes buffered points whenever a batch is full or the flush interval passes, until the client is closed
+func (c *InfluxLineClient) flushLoop() {
+	ticker := time.NewTicker(c.config.Batch.FlushInterval)
+	defer ticker.Stop()
+
+	for {
+		select {
+		case <-c.stopChan:
+			return
+		case <-ticker.C:
+		case <-c.flushChan:
+		}
+
+		err := c.Flush()
+		if err != nil {
+			log.Errorln("Error writing metrics to InfluxDB:", err)
+		}
+	}
+}
+
+// Flush writes all buffered points in batches.  Batches that still fail after all retries are dropped.
+func (c *InfluxLineClient) Flush() error {
+	c.flushMu.Lock()
+	defer c.flushMu.Unlock()
+
+	for {
+		c.mu.Lock()
+		if len(c.lines) == 0 {
+			c.mu.Unlock()
+			return nil
+		}
+		size := c.config.Batch.BatchSize
+		if size > len(c.lines) {
+			size = len(c.lines)
+		}
+		batch := c.lines[:size]
+		c.lines = c.lines[size:]
+		c.mu.Unlock()
+
+		err := c.writeWithRetry(batch)
+		if err != nil {
+			return fmt.Errorf("dropped %d points: %w", len(batch), err)
+		}
+	}
+}
+
+// Close writes any buffered points and stops the background writer
+func (c *InfluxLineClient) Close() error {
+	c.stopOnce.Do(func() {
+		close(c.stopChan)
+	})
+	return c.Flush()
+}
+
+// writeWithRetry writes a batch of points, retrying with exponential backoff when InfluxDB is unavailable or rate
+// limiting.  Points that InfluxDB rejects as invalid are not retried.
+func (c *InfluxLineClient) writeWithRetry(batch []string) error {
+	delay := c.config.Batch.RetryInterval
+	var err error
+	maxRetries := max(c.config.Batch.MaxRetries, 0)
+	for try := 0; try <= maxRetries; try++ {
+		if try > 0 {
+			log.Infoln("Retrying InfluxDB write in", delay.String()+". Try", try, "of", maxRetries)
+			time.Sleep(delay)
+			delay = delay + delay
+		}
+
+		var retryable bool
+		retryable, err = c.write(batch)
+		if err == nil || !retryable {
+			return err
+		}
+	}
+	return err
+}
+
+// write sends a batch of points to InfluxDB and reports if a failed write can be retried
+func (c *InfluxLineClient) write(batch []string) (bool, error) {
+	body := strings.Join(batch, "\n") + "\n"
+	req, err := http.NewRequest(http.MethodPost, c.writeURL, bytes.NewBufferString(body))
+	if err != nil {
+		return false, err
+	}
+	req.Header.Set("Content-Type", "text/plain; charset=utf-8")
+	if len(c.config.Token) > 0 {
+		req.Header.Set("Authorization", "Token "+c.config.Token)
+	} else if len(c.config.Username) > 0 {
+		req.SetBasicAuth(c.config.Username, c.config.Password)
+	}
+
+	resp, err := c.client.Do(req)
+	if err != nil {
+		return true, err
+	}
+	defer resp.Body.Close()
+
+	if resp.StatusCode >= 200 && resp.StatusCode <= 299 {
+		return false, nil
+	}
+	b, _ := io.ReadAll(io.LimitReader(resp.Body, 1024))
+	err = errors.New("unexpected response from InfluxDB " + resp.Status + ": " + string(b))
+	retryable := resp.StatusCode == http.StatusTooManyRequests || resp.StatusCode >= 500
+	return retryable, err
+}
+
+// influxMeasurementEscaper escapes measurement names for the line protocol
+var influxMeasurementEscaper = strings.NewReplacer(",", `\,`, " ", `\ `)
+
+// influxTagEscaper escapes tag keys, tag values and field keys for the line protocol
+var influxTagEscaper = strings.NewReplacer(",", `\,`, "=", `\=`, " ", `\ `)
+
+// influxLine formats a single point in the InfluxDB line protocol.  The value is written to the "value" field, and
+// tags are sorted by key.  Tags with empty values are left out because
+// the line protocol does not allow them.
+func influxLine(measurement string, tags map[string]string, val interface{}, t time.Time) (string, error) {
+	var field string
+	switch v := val.(type) {
+	case float64:
+		if math.IsNaN(v) || math.IsInf(v, 0) {
+			return "", fmt.Errorf("metric %s has a value of %v which InfluxDB can not store", measurement, v)
+		}
+		field = strconv.FormatFloat(v, 'f', -1, 64)
+	case float32:
+		if math.IsNaN(float64(v)) || math.IsInf(float64(v), 0) {
+			return "", fmt.Errorf("metric %s has a value of %v which InfluxDB can not store", measurement, v)
+		}
+		field = strconv.FormatFloat(float64(v), 'f', -1, 32)
+	case int:
+		field = strconv.Itoa(v) + "i"
+	case int64:
+		field = strconv.FormatInt(v, 10) + "i"
+	case int32:
+		field = strconv.FormatInt(int64(v), 10) + "i"
+	case bool:
+		field = strconv.FormatBool(v)
+	case string:
+		field = `"` + strings.NewReplacer(`\`, `\\`, `"`, `\"`).Replace(v) + `"`
+	default:
+		return "", fmt.Errorf("unsupported value type %T for metric %s", val, measurement)
+	}
+
+	var sb strings.Builder
+	sb.WriteString(influxMeasurementEscaper.Replace(strings.Replace(measurement, " ", "_", -1)))
+
+	keys := make([]string, 0, len(tags))
+	for k := range tags {
+		keys = append(keys, k)
+	}
+	sort.Strings(keys)
+	for _, k := range keys {
+		if len(k) == 0 || len(tags[k]) == 0 {
+			continue
+		}
+		sb.WriteString("," + influxTagEscaper.Replace(k) + "=" + influxTagEscaper.Replace(strings.ReplaceAll(tags[k], "\n", " ")))
+	}
+
+	sb.WriteString(" value=" + field + " " + strconv.FormatInt(t.UnixNano(), 10))
+	return sb.String(), nil
+}
@@ -0,0 +1,174 @@
+package metrics
+
+import (
+	"io"
+	"net/http"
+	"net/http/httptest"
+	"net/url"
+	"strings"
+	"sync"
+	"testing"
+	"time"
+)
+
+// influxStub is a local InfluxDB write endpoint that records the requests it receives
+type influxStub struct {
+	mu       sync.Mutex
+	requests []*http.Request
+	bodies   []string
+	statuses []int // the status codes returned for each request in order, then 204 for the rest
+}
+
+// ServeHTTP implements http.Handler
+func (s *influxStub) ServeHTTP(w http.ResponseWriter, r *http.Request) {
+	body, _ := io.ReadAll(r.Body)
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	s.requests = append(s.requests, r)
+	s.bodies = append(s.bodies, string(body))
+	status := http.StatusNoContent
+	if len(s.statuses) > 0 {
+		status = s.statuses[0]
+		s.statuses = s.statuses[1:]
+	}
+	w.WriteHeader(status)
+}
+
+// newInfluxStub starts an influxStub and returns it with its URL
+func newInfluxStub(t *testing.T, statuses ...int) (*influxStub, url.URL) {
+	stub := &influxStub{statuses: statuses}
+	server := httptest.NewServer(stub)
+	t.Cleanup(server.Close)
+	u, err := url.Parse(server.URL)
+	if err != nil {
+		t.Fatal(err)
+	}
+	return stub, *u
+}
+
+// TestInfluxLine tests the line protocol formatting and escaping of points
+func TestInfluxLine(t *testing.T) {
+	ts := time.Unix(0, 1700000000000000000)
+	line, err := influxLine("RunDuration.dns check.kuberhealthy", map[string]string{"Name": "dns, check", "Errors": "", "Namespace": "kuberhealthy"}, 1.5, ts)
+	if err != nil {
+		t.Fatal("Error formatting line:", err)
+	}
+	expected := `RunDuration.dns_check.kuberhealthy,Name=dns\,\ check,Namespace=kuberhealthy value=1.5 1700000000000000000`
+	if line != expected {
+		t.Fatalf("expected line %s, got %s", expected, line)
+	}
+
+	line, err = influxLine("dns.kuberhealthy", nil, 1, ts)
+	if err != nil || line != "dns.kuberhealthy value=1i 1700000000000000000" {
+		t.Fatal("Unexpected integer line:", line, err)
+	}
+
+	_, err = influxLine("dns.kuberhealthy", nil, []string{}, ts)
+	if err == nil {
+		t.Fatal("Expected an error formatting an unsupported value")
+	}
+}
+
+// TestInfluxLineClientV2 tests that batches are written to the InfluxDB 2.x API with a token
+func TestInfluxLineClientV2(t *testing.T) {
+	stub, u := newInfluxStub(t)
+	client, err := NewInfluxLineClient(InfluxLineConfig{
+		URL:    u,
+		Org:    "kuberhealthy",
+		Bucket: "checks",
+		Token:  "secret",
+		Batch:  InfluxBatchConfig{BatchSize: 2, FlushInterval: time.Hour},
+	})
+	if err != nil {
+		t.Fatal("Error creating InfluxDB client:", err)
+	}
+	defer client.Close()
+
+	err = client.Push(Metric{{"dns.kuberhealthy": 1}}, map[string]string{"Name": "dns"})
+	if err != nil {
+		t.Fatal("Error pushing metrics:", err)
+	}
+	err = client.Push(Metric{{"RunDuration.dns.kuberhealthy": 0.5}}, map[string]string{"Name": "dns"})
+	if err != nil {
+		t.Fatal("Error pushing metrics:", err)
+	}
+
+	// a full batch is written without waiting for the flush interval
+	deadline := time.Now().Add(time.Second * 5)
+	for {
+		stub.mu.Lock()
+		written := len(stub.requests)
+		stub.mu.Unlock()
+		if written > 0 {
+			break
+		}
+		if time.Now().After(deadline) {
+			t.Fatal("Timed out waiting for a full batch to be written")
+		}
+		time.Sleep(time.Millisecond * 10)
+	}
+
+	stub.mu.Lock()
+	defer stub.mu.Unlock()
+	r := stub.requests[0]
+	if r.URL.Path != "/api/v2/write" || r.URL.Query().Get("org") != "kuberhealthy" || r.URL.Query().Get("bucket") != "checks" {
+		t.Fatal("Unexpected InfluxDB 2.x write URL:", r.URL.String())
+	}
+	if r.Header.Get("Authorization") != "Token secret" {
+		t.Fatal("Unexpected InfluxDB 2.x authorization:", r.Header.Get("Authorization"))
+	}
+	if lines := strings.Split(strings.TrimSpace(stub.bodies[0]), "\n"); len(lines) != 2 {
+		t.Fatal("Expected a batch of 2 points but got", stub.bodies[0])
+	}
+}
+
+// TestInfluxLineClientRetry tests that unavailable InfluxDB servers are retried and invalid writes are not
+func TestInfluxLineClientRetry(t *testing.T) {
+	stub, u := newInfluxStub(t, http.StatusServiceUnavailable, http.StatusTooManyRequests)
+	client, err := NewInfluxLineClient(InfluxLineConfig{
+		URL:      u,
+		Database: "kuberhealthy",
+		Username: "user",
+		Password: "pass",
+		Batch:    InfluxBatchConfig{FlushInterval: time.Hour, RetryInterval: time.Millisecond},
+	})
+	if err != nil {
+		t.Fatal("Error creating InfluxDB client:", err)
+	}
+	defer client.Close()
+
+	err = client.Push(Metric{{"dns.kuberhealthy": 1}}, nil)
+	if err != nil {
+		t.Fatal("Error pushing metrics:", err)
+	}
+	err = client.Flush()
+	if err != nil {
+		t.Fatal("Expected write to succeed after retries but got", err)
+	}
+
+	stub.mu.Lock()
+	if len(stub.requests) != 3 {
+		t.Fatal("Expected 3 write attempts but got", len(stub.requests))
+	}
+	r := stub.requests[0]
+	username, password, ok := r.BasicAuth()
+	if r.URL.Path != "/write" || r.URL.Query().Get("db") != "kuberhealthy" || !ok || username != "user" || password != "pass" {
+		t.Fatal("Unexpected InfluxDB 1.x write request:", r.URL.String(), username, password)
+	}
+	stub.statuses = []int{http.StatusBadRequest}
+	stub.mu.Unlock()
+
+	err = client.Push(Metric{{"dns.kuberhealthy": 1}}, nil)
+	if err != nil {
+		t.Fatal("Error pushing metrics:", err)
+	}
+	err = client.Flush()
+	if err == nil {
+		t.Fatal("Expected a rejected write to fail")
+	}
+	stub.mu.Lock()
+	defer stub.mu.Unlock()
+	if len(stub.requests) != 4 {
+		t.Fatal("Expected a rejected write to not be retried but got", len(stub.requests), "attempts")
+	}
+}
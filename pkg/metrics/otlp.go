@@ -0,0 +1,294 @@
+package metrics
+
+import (
+	"bytes"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"sort"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/tracing"
+)
+
+// DefaultOTLPServiceName is the service name reported to the OTLP endpoint when none is configured
+const DefaultOTLPServiceName = "kuberhealthy"
+
+// DefaultOTLPTimeout is how long an export to the OTLP endpoint may take
+const DefaultOTLPTimeout = time.Second * 10
+
+// OTLPConfig configures the export of metrics and traces to an OpenTelemetry collector over OTLP/HTTP
+type OTLPConfig struct {
+	Endpoint      string            `yaml:"endpoint,omitempty"`      // the base URL of the OTLP/HTTP receiver, such as http://otel-collector:4318
+	Headers       map[string]string `yaml:"headers,omitempty"`       // extra headers sent with each export, such as authentication
+	ServiceName   string            `yaml:"serviceName,omitempty"`   // the service.name resource attribute (default: kuberhealthy)
+	Timeout       time.Duration     `yaml:"timeout,omitempty"`       // how long an export may take (default: 10s)
+	EnableMetrics bool              `yaml:"enableMetrics,omitempty"` // forward check metrics to the endpoint
+	EnableTraces  bool              `yaml:"enableTraces,omitempty"`  // send a trace for each khcheck run to the endpoint
+}
+
+// OTLPClient pushes metrics and exports spans to an OpenTelemetry collector using the OTLP/HTTP JSON encoding
+type OTLPClient struct {
+	endpoint    string
+	headers     map[string]string
+	serviceName string
+	client      *http.Client
+}
+
+// NewOTLPClient creates an OTLPClient that can be used to push metrics and export spans
+func NewOTLPClient(config OTLPConfig) (*OTLPClient, error) {
+	if len(config.Endpoint) == 0 {
+		return nil, errors.New("an OTLP endpoint is required")
+	}
+	if !strings.HasPrefix(config.Endpoint, "http://") && !strings.HasPrefix(config.Endpoint, "https://") {
+		return nil, errors.New("OTLP endpoint must be an http or https URL: " + config.Endpoint)
+	}
+
+	serviceName := config.ServiceName
+	if len(serviceName) == 0 {
+		serviceName = DefaultOTLPServiceName
+	}
+	timeout := config.Timeout
+	if timeout == 0 {
+		timeout = DefaultOTLPTimeout
+	}
+
+	return &OTLPClient{
+		endpoint:    strings.TrimSuffix(config.Endpoint, "/"),
+		headers:     config.Headers,
+		serviceName: serviceName,
+		client:      &http.Client{Timeout: timeout},
+	}, nil
+}
+
+// otlpKeyValue is an OTLP attribute
+type otlpKeyValue struct {
+	Key   string       `json:"key"`
+	Value otlpAnyValue `json:"value"`
+}
+
+// otlpAnyValue is the value of an OTLP attribute.  Kuberhealthy only sends string attributes.
+type otlpAnyValue struct {
+	StringValue string `json:"stringValue"`
+}
+
+// otlpResource describes the entity producing telemetry
+type otlpResource struct {
+	Attributes []otlpKeyValue `json:"attributes"`
+}
+
+// otlpScope describes the library producing telemetry
+type otlpScope struct {
+	Name string `json:"name"`
+}
+
+// otlpMetricsRequest is an OTLP ExportMetricsServiceRequest
+type otlpMetricsRequest struct {
+	ResourceMetrics []otlpResourceMetrics `json:"resourceMetrics"`
+}
+
+type otlpResourceMetrics struct {
+	Resource     otlpResource       `json:"resource"`
+	ScopeMetrics []otlpScopeMetrics `json:"scopeMetrics"`
+}
+
+type otlpScopeMetrics struct {
+	Scope   otlpScope    `json:"scope"`
+	Metrics []otlpMetric `json:"metrics"`
+}
+
+type otlpMetric struct {
+	Name  string    `json:"name"`
+	Gauge otlpGauge `json:"gauge"`
+}
+
+type otlpGauge struct {
+	DataPoints []otlpDataPoint `json:"dataPoints"`
+}
+
+type otlpDataPoint struct {
+	Attributes   []otlpKeyValue `json:"attributes"`
+	TimeUnixNano string         `json:"timeUnixNano"`
+	AsDouble     float64        `json:"asDouble"`
+}
+
+// otlpTracesRequest is an OTLP ExportTraceServiceRequest
+type otlpTracesRequest struct {
+	ResourceSpans []otlpResourceSpans `json:"resourceSpans"`
+}
+
+type otlpResourceSpans struct {
+	Resource   otlpResource     `json:"resource"`
+	ScopeSpans []otlpScopeSpans `json:"scopeSpans"`
+}
+
+type otlpScopeSpans struct {
+	Scope otlpScope  `json:"scope"`
+	Spans []otlpSpan `json:"spans"`
+}
+
+type otlpSpan struct {
+	TraceID           string         `json:"traceId"`
+	SpanID            string         `json:"spanId"`
+	ParentSpanID      string         `json:"parentSpanId,omitempty"`
+	Name              string         `json:"name"`
+	Kind              int            `json:"kind"`
+	StartTimeUnixNano string         `json:"startTimeUnixNano"`
+	EndTimeUnixNano   string         `json:"endTimeUnixNano"`
+	Attributes        []otlpKeyValue `json:"attributes"`
+	Status            otlpStatus     `json:"status"`
+}
+
+type otlpStatus struct {
+	Code    int    `json:"code"`
+	Message string `json:"message,omitempty"`
+}
+
+// OTLP span kinds and status codes used by kuberhealthy
+const (
+	otlpSpanKindInternal = 1
+	otlpStatusCodeUnset  = 0
+	otlpStatusCodeError  = 2
+)
+
+// otlpAttributes converts a map to OTLP attributes sorted by key
+func otlpAttributes(m map[string]string) []otlpKeyValue {
+	keys := make([]string, 0, len(m))
+	for k := range m {
+		keys = append(keys, k)
+	}
+	sort.Strings(keys)
+
+	attributes := make([]otlpKeyValue, 0, len(keys))
+	for _, k := range keys {
+		attributes = append(attributes, otlpKeyValue{Key: k, Value: otlpAnyValue{StringValue: m[k]}})
+	}
+	return attributes
+}
+
+// otlpTime formats a time as the unix nanosecond string used by the OTLP JSON encoding
+func otlpTime(t time.Time) string {
+	return strconv.FormatInt(t.UnixNano(), 10)
+}
+
+// otlpValue converts a metric value to a float
+func otlpValue(val interface{}) (float64, error) {
+	switch v := val.(type) {
+	case float64:
+		return v, nil
+	case float32:
+		return float64(v), nil
+	case int:
+		return float64(v), nil
+	case int64:
+		return float64(v), nil
+	case int32:
+		return float64(v), nil
+	case bool:
+		if v {
+			return 1, nil
+		}
+		return 0, nil
+	default:
+		return 0, fmt.Errorf("unsupported metric value type %T", val)
+	}
+}
+
+// resource returns the OTLP resource describing this kuberhealthy instance
+func (o *OTLPClient) resource() otlpResource {
+	return otlpResource{Attributes: otlpAttributes(map[string]string{"service.name": o.serviceName})}
+}
+
+// Push accepts a list of metrics, with a metric being defined as a map of string (name) to interface (value), and
+// sends them to the OTLP endpoint as gauges with the tags as attributes
+func (o *OTLPClient) Push(points Metric, tags map[string]string) error {
+	now := otlpTime(time.Now())
+	attributes := otlpAttributes(tags)
+
+	otlpMetrics := []otlpMetric{}
+	for _, p := range points {
+		for key, val := range p {
+			value, err := otlpValue(val)
+			if err != nil {
+				return errors.New("failed to convert metric " + key + ": " + err.Error())
+			}
+			otlpMetrics = append(otlpMetrics, otlpMetric{
+				Name: strings.Replace(key, " ", "_", -1),
+				Gauge: otlpGauge{
+					DataPoints: []otlpDataPoint{{Attributes: attributes, TimeUnixNano: now, AsDouble: value}},
+				},
+			})
+		}
+	}
+
+	request := otlpMetricsRequest{
+		ResourceMetrics: []otlpResourceMetrics{{
+			Resource:     o.resource(),
+			ScopeMetrics: []otlpScopeMetrics{{Scope: otlpScope{Name: DefaultOTLPServiceName}, Metrics: otlpMetrics}},
+		}},
+	}
+	return o.post("/v1/metrics", request)
+}
+
+// ExportSpans sends finished spans to the OTLP endpoint
+func (o *OTLPClient) ExportSpans(spans []tracing.SpanData) error {
+	otlpSpans := make([]otlpSpan, 0, len(spans))
+	for _, s := range spans {
+		status := otlpStatus{Code: otlpStatusCodeUnset}
+		if len(s.Error) > 0 {
+			status = otlpStatus{Code: otlpStatusCodeError, Message: s.Error}
+		}
+		otlpSpans = append(otlpSpans, otlpSpan{
+			TraceID:           s.TraceID,
+			SpanID:            s.SpanID,
+			ParentSpanID:      s.ParentSpanID,
+			Name:              s.Name,
+			Kind:              otlpSpanKindInternal,
+			StartTimeUnixNano: otlpTime(s.Start),
+			EndTimeUnixNano:   otlpTime(s.End),
+			Attributes:        otlpAttributes(s.Attributes),
+			Status:            status,
+		})
+	}
+
+	request := otlpTracesRequest{
+		ResourceSpans: []otlpResourceSpans{{
+			Resource:   o.resource(),
+			ScopeSpans: []otlpScopeSpans{{Scope: otlpScope{Name: DefaultOTLPServiceName}, Spans: otlpSpans}},
+		}},
+	}
+	return o.post("/v1/traces", request)
+}
+
+// post sends an export request as JSON to a path of the OTLP endpoint
+func (o *OTLPClient) post(path string, request interface{}) error {
+	body, err := json.Marshal(request)
+	if err != nil {
+		return err
+	}
+
+	req, err := http.NewRequest(http.MethodPost, o.endpoint+path, bytes.NewReader(body))
+	if err != nil {
+		return err
+	}
+	req.Header.Set("Content-Type", "application/json")
+	for k, v := range o.headers {
+		req.Header.Set(k, v)
+	}
+
+	resp, err := o.client.Do(req)
+	if err != nil {
+		return err
+	}
+	defer resp.Body.Close()
+
+	if resp.StatusCode < 200 || resp.StatusCode > 299 {
+		b, _ := io.ReadAll(io.LimitReader(resp.Body, 1024))
+		return errors.New("unexpected response from OTLP endpoint " + resp.Status + ": " + string(b))
+	}
+	return nil
+}
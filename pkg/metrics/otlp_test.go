@@ -0,0 +1,128 @@
+package metrics
+
+import (
+	"encoding/json"
+	"io"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+	"time"
+
+	"github.com/kuberhealthy/kuberhealthy/v2/pkg/tracing"
+)
+
+// newOTLPStub starts a local OTLP receiver that sends the path and body of each request it receives to a channel
+func newOTLPStub(t *testing.T) (*httptest.Server, chan map[string]interface{}) {
+	requests := make(chan map[string]interface{}, 1)
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		body, err := io.ReadAll(r.Body)
+		if err != nil {
+			t.Errorf("failed to read request body: %s", err)
+		}
+		request := map[string]interface{}{}
+		err = json.Unmarshal(body, &request)
+		if err != nil {
+			t.Errorf("failed to decode request body: %s", err)
+		}
+		request["path"] = r.URL.Path
+		request["authorization"] = r.Header.Get("Authorization")
+		requests <- request
+	}))
+	t.Cleanup(server.Close)
+	return server, requests
+}
+
+// TestOTLPClientPush tests that metrics are sent to the OTLP metrics endpoint as gauges
+func TestOTLPClientPush(t *testing.T) {
+	server, requests := newOTLPStub(t)
+	client, err := NewOTLPClient(OTLPConfig{Endpoint: server.URL + "/", Headers: map[string]string{"Authorization": "Bearer token"}})
+	if err != nil {
+		t.Fatal("Error creating OTLP client:", err)
+	}
+
+	err = client.Push(Metric{{"dns.kuberhealthy": 1}}, map[string]string{"Name": "dns"})
+	if err != nil {
+		t.Fatal("Error pushing metrics:", err)
+	}
+	request := <-requests
+	if request["path"] != "/v1/metrics" || request["authorization"] != "Bearer token" {
+		t.Fatal("Unexpected OTLP metrics request:", request)
+	}
+
+	b, _ := json.Marshal(request["resourceMetrics"])
+	var resourceMetrics []otlpResourceMetrics
+	err = json.Unmarshal(b, &resourceMetrics)
+	if err != nil {
+		t.Fatal("Error decoding resource metrics:", err)
+	}
+	if resourceMetrics[0].Resource.Attributes[0].Value.StringValue != DefaultOTLPServiceName {
+		t.Fatal("Unexpected OTLP resource:", resourceMetrics[0].Resource)
+	}
+	metric := resourceMetrics[0].ScopeMetrics[0].Metrics[0]
+	point := metric.Gauge.DataPoints[0]
+	if metric.Name != "dns.kuberhealthy" || point.AsDouble != 1 || point.Attributes[0].Key != "Name" || point.Attributes[0].Value.StringValue != "dns" {
+		t.Fatal("Unexpected OTLP metric:", metric)
+	}
+
+	err = client.Push(Metric{{"dns.kuberhealthy": "up"}}, nil)
+	if err == nil {
+		t.Fatal("Expected an error pushing a metric that is not a number")
+	}
+}
+
+// TestOTLPClientExportSpans tests that spans are sent to the OTLP traces endpoint
+func TestOTLPClientExportSpans(t *testing.T) {
+	server, requests := newOTLPStub(t)
+	client, err := NewOTLPClient(OTLPConfig{Endpoint: server.URL, ServiceName: "kuberhealthy-test"})
+	if err != nil {
+		t.Fatal("Error creating OTLP client:", err)
+	}
+
+	start := time.Unix(1700000000, 0)
+	err = client.ExportSpans([]tracing.SpanData{{
+		TraceID:      "0123456789abcdef0123456789abcdef",
+		SpanID:       "0123456789abcdef",
+		ParentSpanID: "fedcba9876543210",
+		Name:         "create pod",
+		Start:        start,
+		End:          start.Add(time.Second),
+		Attributes:   map[string]string{"kuberhealthy.run_uuid": "1234"},
+		Error:        "failed to create pod",
+	}})
+	if err != nil {
+		t.Fatal("Error exporting spans:", err)
+	}
+	request := <-requests
+	if request["path"] != "/v1/traces" {
+		t.Fatal("Unexpected OTLP traces request path:", request["path"])
+	}
+
+	b, _ := json.Marshal(request["resourceSpans"])
+	var resourceSpans []otlpResourceSpans
+	err = json.Unmarshal(b, &resourceSpans)
+	if err != nil {
+		t.Fatal("Error decoding resource spans:", err)
+	}
+	if resourceSpans[0].Resource.Attributes[0].Value.StringValue != "kuberhealthy-test" {
+		t.Fatal("Unexpected OTLP resource:", resourceSpans[0].Resource)
+	}
+	span := resourceSpans[0].ScopeSpans[0].Spans[0]
+	if span.ParentSpanID != "fedcba9876543210" || span.StartTimeUnixNano != "1700000000000000000" || span.EndTimeUnixNano != "1700000001000000000" {
+		t.Fatal("Unexpected OTLP span:", span)
+	}
+	if span.Status.Code != otlpStatusCodeError || span.Status.Message != "failed to create pod" || span.Attributes[0].Value.StringValue != "1234" {
+		t.Fatal("Unexpected OTLP span status or attributes:", span)
+	}
+}
+
+// TestNewOTLPClient tests that OTLP endpoints are validated
+func TestNewOTLPClient(t *testing.T) {
+	_, err := NewOTLPClient(OTLPConfig{})
+	if err == nil {
+		t.Fatal("Expected an error creating an OTLP client without an endpoint")
+	}
+	_, err = NewOTLPClient(OTLPConfig{Endpoint: "otel-collector:4318"})
+	if err == nil {
+		t.Fatal("Expected an error creating an OTLP client without an http endpoint")
+	}
+}
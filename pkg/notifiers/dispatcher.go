@@ -0,0 +1,102 @@
+package notifiers
+
+import (
+	"context"
+	"fmt"
+	"sync"
+
+	log "github.com/sirupsen/logrus"
+)
+
+// DefaultQueueSize is how many transitions a dispatcher holds while its worker is busy sending notifications
+const DefaultQueueSize = 100
+
+// Dispatcher sends transitions to a set of notifiers from a single background worker.  The notifiers are created
+// once when the dispatcher is created.  Transitions beyond the queue size are dropped so that a slow external service
+// never holds up the writing of check states.
+type Dispatcher struct {
+	notifiers []configuredNotifier
+	queue     chan Transition
+	done      chan struct{} // closed when the worker has sent every queued transition
+	ctx       context.Context
+	cancel    context.CancelFunc // aborts the notification being sent and drops the rest of the queue
+	mu        sync.Mutex
+	closed    bool // set once the queue is closed so that later transitions are dropped
+}
+
+// NewDispatcher creates the notifiers described by the supplied configs and starts sending transitions to them.  An
+// error is returned if any of the configs is invalid.  queueSize defaults to DefaultQueueSize when it is below one.
+func NewDispatcher(configs []Config, queueSize int) (*Dispatcher, error) {
+	if queueSize < 1 {
+		queueSize = DefaultQueueSize
+	}
+
+	d := &Dispatcher{
+		queue: make(chan Transition, queueSize),
+		done:  make(chan struct{}),
+	}
+	d.ctx, d.cancel = context.WithCancel(context.Background())
+	for _, config := range configs {
+		n, err := New(config)
+		if err != nil {
+			return nil, fmt.Errorf("notifier %s: %w", config.Name, err)
+		}
+		d.notifiers = append(d.notifiers, configuredNotifier{config: config, notifier: n})
+	}
+
+	go d.run()
+	return d, nil
+}
+
+// Send queues the transition to be sent to every notifier.  Returns false if the transition was dropped because the
+// queue is full or the dispatcher is closed.
+func (d *Dispatcher) Send(t Transition) bool {
+	d.mu.Lock()
+	defer d.mu.Unlock()
+	if d.closed {
+		return false
+	}
+
+	select {
+	case d.queue <- t:
+		return true
+	default:
+		log.Warningln("notifiers: queue is full. Dropping notification that", t.Summary())
+		return false
+	}
+}
+
+// Close stops accepting transitions and waits for the queued transitions to be sent.  If ctx is done first, the
+// notification being sent is aborted and the transitions still queued are dropped.
+func (d *Dispatcher) Close(ctx context.Context) {
+	d.mu.Lock()
+	if !d.closed {
+		d.closed = true
+		close(d.queue)
+	}
+	d.mu.Unlock()
+
+	select {
+	case <-d.done:
+	case <-ctx.Done():
+		log.Warningln("notifiers: dropping", len(d.queue), "queued notifications:", ctx.Err())
+		d.cancel()
+	}
+}
+
+// run sends queued transitions to every notifier until the queue is closed or the dispatcher is cancelled
+func (d *Dispatcher) run() {
+	defer close(d.done)
+	defer d.cancel()
+	for t := range d.queue {
+		for _, n := range d.notifiers {
+			if d.ctx.Err() != nil {
+				return
+			}
+			err := notify(d.ctx, n, t)
+			if err != nil {
+				log.Errorln("notifiers: error notifying that", t.Summary()+":", err)
+			}
+		}
+	}
+}
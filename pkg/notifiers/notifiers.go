@@ -0,0 +1,121 @@
+// Package notifiers pushes notifications to external services when the state of a check or job changes
+package notifiers // import "github.com/kuberhealthy/kuberhealthy/v2/pkg/notifiers"
+
+import (
+	"bytes"
+	"context"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"time"
+
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
+)
+
+// The types of notifiers that can be configured
+const (
+	TypeWebhook   = "webhook"
+	TypeSlack     = "slack"
+	TypePagerDuty = "pagerduty"
+)
+
+// DefaultTimeout is how long a notifier waits for the external service to accept a notification
+const DefaultTimeout = time.Second * 10
+
+// Config configures a single notifier
+type Config struct {
+	Name       string            `yaml:"name"`                 // a name for the notifier used in logs
+	Type       string            `yaml:"type"`                 // the type of notifier: webhook, slack or pagerduty
+	URL        string            `yaml:"url,omitempty"`        // the URL notifications are sent to (default for pagerduty: the events v2 API)
+	Headers    map[string]string `yaml:"headers,omitempty"`    // extra headers sent with each notification
+	Template   string            `yaml:"template,omitempty"`   // a go template for the JSON body of webhook notifications
+	RoutingKey string            `yaml:"routingKey,omitempty"` // the integration key of the pagerduty service
+	Timeout    time.Duration     `yaml:"timeout,omitempty"`    // how long to wait for the service to accept a notification (default: 10s)
+}
+
+// Transition describes a check or job changing from one severity to another
+type Transition struct {
+	Name             string             `json:"name"`
+	Namespace        string             `json:"namespace"`
+	Workload         string             `json:"workload"` // KHCheck or KHJob
+	PreviousSeverity khstatev1.Severity `json:"previousSeverity"`
+	Severity         khstatev1.Severity `json:"severity"`
+	OK               bool               `json:"ok"`
+	Errors           []string           `json:"errors"`
+	Warnings         []string           `json:"warnings"`
+	Node             string             `json:"node"`
+	RunUUID          string             `json:"uuid"`
+	Time             time.Time          `json:"time"`
+	Metadata         map[string]string  `json:"metadata,omitempty"` // the stateMetadata of the kuberhealthy configuration
+}
+
+// Summary returns a one line description of the transition
+func (t Transition) Summary() string {
+	return fmt.Sprintf("%s %s/%s is now %s (was %s)", t.Workload, t.Namespace, t.Name, t.Severity, t.PreviousSeverity)
+}
+
+// Notifier sends notifications about transitions to an external service
+type Notifier interface {
+	Notify(ctx context.Context, t Transition) error
+}
+
+// New creates the notifier described by the supplied config
+func New(config Config) (Notifier, error) {
+	switch config.Type {
+	case TypeWebhook:
+		return newWebhookNotifier(config)
+	case TypeSlack:
+		return newSlackNotifier(config)
+	case TypePagerDuty:
+		return newPagerDutyNotifier(config)
+	default:
+		return nil, fmt.Errorf("unknown notifier type %q", config.Type)
+	}
+}
+
+// configuredNotifier is a notifier along with the config it was created from
+type configuredNotifier struct {
+	config   Config
+	notifier Notifier
+}
+
+// notify sends the transition to a single notifier within the timeout of its config
+func notify(ctx context.Context, n configuredNotifier, t Transition) error {
+	timeout := n.config.Timeout
+	if timeout == 0 {
+		timeout = DefaultTimeout
+	}
+	ctx, cancel := context.WithTimeout(ctx, timeout)
+	defer cancel()
+
+	err := n.notifier.Notify(ctx, t)
+	if err != nil {
+		return fmt.Errorf("notifier %s: %w", n.config.Name, err)
+	}
+	return nil
+}
+
+// postJSON sends a JSON body to the supplied URL and returns an error if the service does not accept it
+func postJSON(ctx context.Context, url string, headers map[string]string, body []byte) error {
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, url, bytes.NewReader(body))
+	if err != nil {
+		return err
+	}
+	req.Header.Set("Content-Type", "application/json")
+	for k, v := range headers {
+		req.Header.Set(k, v)
+	}
+
+	resp, err := http.DefaultClient.Do(req)
+	if err != nil {
+		return err
+	}
+	defer resp.Body.Close()
+
+	if resp.StatusCode < 200 || resp.StatusCode > 299 {
+		b, _ := io.ReadAll(io.LimitReader(resp.Body, 1024))
+		return errors.New("unexpected response " + resp.Status + ": " + string(b))
+	}
+	return nil
+}
@@ -0,0 +1,215 @@
+package notifiers
+
+import (
+	"context"
+	"encoding/json"
+	"io"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+	"time"
+
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
+)
+
+// testTransition is a check that started failing
+var testTransition = Transition{
+	Name:             "dns-status-internal",
+	Namespace:        "kuberhealthy",
+	Workload:         "KHCheck",
+	PreviousSeverity: khstatev1.SeverityOK,
+	Severity:         khstatev1.SeverityFailing,
+	Errors:           []string{"DNS Status check determined that kubernetes.default is DOWN"},
+	Node:             "node-1",
+	RunUUID:          "a7f3e3a4-1b8e-4b8c-9a1c-1e0d2d5c8f11",
+	Time:             time.Date(2024, 1, 2, 3, 4, 5, 0, time.UTC),
+}
+
+// newStubServer starts a local http server that records the last request body and headers it received
+func newStubServer(t *testing.T, statusCode int) (*httptest.Server, *[]byte, *http.Header) {
+	var body []byte
+	var headers http.Header
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		var err error
+		body, err = io.ReadAll(r.Body)
+		if err != nil {
+			t.Errorf("failed to read request body: %s", err)
+		}
+		headers = r.Header
+		w.WriteHeader(statusCode)
+	}))
+	t.Cleanup(server.Close)
+	return server, &body, &headers
+}
+
+// sendThroughDispatcher sends a transition through a dispatcher of the supplied notifiers and waits for it to be sent
+func sendThroughDispatcher(t *testing.T, configs []Config, transition Transition) {
+	t.Helper()
+	dispatcher, err := NewDispatcher(configs, 1)
+	if err != nil {
+		t.Fatalf("failed to create dispatcher: %s", err)
+	}
+	if !dispatcher.Send(transition) {
+		t.Fatal("expected the transition to be queued")
+	}
+	dispatcher.Close(context.Background())
+}
+
+// TestWebhookNotifier tests that the default and custom templates are rendered and posted to the webhook
+func TestWebhookNotifier(t *testing.T) {
+	server, body, headers := newStubServer(t, http.StatusOK)
+
+	sendThroughDispatcher(t, []Config{{Name: "hook", Type: TypeWebhook, URL: server.URL, Headers: map[string]string{"Authorization": "Bearer secret"}}}, testTransition)
+	if headers.Get("Authorization") != "Bearer secret" {
+		t.Fatalf("expected configured headers to be sent, got %v", *headers)
+	}
+
+	var payload map[string]interface{}
+	err := json.Unmarshal(*body, &payload)
+	if err != nil {
+		t.Fatalf("expected default template to render valid JSON, got %s: %s", err, *body)
+	}
+	if payload["name"] != "dns-status-internal" || payload["severity"] != "Failing" || payload["previousSeverity"] != "OK" {
+		t.Fatalf("unexpected default webhook payload: %v", payload)
+	}
+
+	template := `{"text": {{ json .Summary }}}`
+	sendThroughDispatcher(t, []Config{{Name: "hook", Type: TypeWebhook, URL: server.URL, Template: template}}, testTransition)
+	expected := `{"text": "KHCheck kuberhealthy/dns-status-internal is now Failing (was OK)"}`
+	if string(*body) != expected {
+		t.Fatalf("expected templated body %s, got %s", expected, *body)
+	}
+}
+
+// TestSlackNotifier tests that slack messages include the summary and errors of the transition
+func TestSlackNotifier(t *testing.T) {
+	server, body, _ := newStubServer(t, http.StatusOK)
+
+	sendThroughDispatcher(t, []Config{{Name: "slack", Type: TypeSlack, URL: server.URL}}, testTransition)
+
+	var message slackMessage
+	err := json.Unmarshal(*body, &message)
+	if err != nil {
+		t.Fatalf("failed to decode slack message: %s", err)
+	}
+	if message.Text != testTransition.Summary() {
+		t.Fatalf("expected slack text %q, got %q", testTransition.Summary(), message.Text)
+	}
+	if len(message.Attachments) != 1 || message.Attachments[0].Color != "danger" || message.Attachments[0].Text != testTransition.Errors[0] {
+		t.Fatalf("unexpected slack attachments: %+v", message.Attachments)
+	}
+}
+
+// TestPagerDutyNotifier tests that failures trigger pagerduty alerts and recoveries resolve them
+func TestPagerDutyNotifier(t *testing.T) {
+	server, body, _ := newStubServer(t, http.StatusAccepted)
+	config := Config{Name: "pagerduty", Type: TypePagerDuty, URL: server.URL, RoutingKey: "routing-key"}
+
+	sendThroughDispatcher(t, []Config{config}, testTransition)
+
+	var event pagerDutyEvent
+	err := json.Unmarshal(*body, &event)
+	if err != nil {
+		t.Fatalf("failed to decode pagerduty event: %s", err)
+	}
+	if event.RoutingKey != "routing-key" || event.EventAction != "trigger" || event.DedupKey != "kuberhealthy/KHCheck/kuberhealthy/dns-status-internal" {
+		t.Fatalf("unexpected pagerduty event: %+v", event)
+	}
+	if event.Payload == nil || event.Payload.Severity != "critical" || event.Payload.Timestamp != "2024-01-02T03:04:05Z" {
+		t.Fatalf("unexpected pagerduty payload: %+v", event.Payload)
+	}
+
+	recovered := testTransition
+	recovered.PreviousSeverity = khstatev1.SeverityFailing
+	recovered.Severity = khstatev1.SeverityOK
+	recovered.OK = true
+	recovered.Errors = nil
+	sendThroughDispatcher(t, []Config{config}, recovered)
+
+	event = pagerDutyEvent{}
+	err = json.Unmarshal(*body, &event)
+	if err != nil {
+		t.Fatalf("failed to decode pagerduty event: %s", err)
+	}
+	if event.EventAction != "resolve" || event.DedupKey != "kuberhealthy/KHCheck/kuberhealthy/dns-status-internal" || event.Payload != nil {
+		t.Fatalf("expected recovery to resolve the pagerduty alert, got %+v", event)
+	}
+}
+
+// TestDispatcher tests that a dispatcher rejects invalid configs, sends queued transitions past notifiers that fail
+// and drops transitions once it is closed
+func TestDispatcher(t *testing.T) {
+	server, body, _ := newStubServer(t, http.StatusOK)
+	rejecting, _, _ := newStubServer(t, http.StatusInternalServerError)
+
+	for _, config := range []Config{
+		{Name: "unknown", Type: "email"},
+		{Name: "no-url", Type: TypeSlack},
+		{Name: "no-key", Type: TypePagerDuty},
+		{Name: "bad-template", Type: TypeWebhook, URL: server.URL, Template: "{{ .Name"},
+	} {
+		_, err := NewDispatcher([]Config{{Name: "slack", Type: TypeSlack, URL: server.URL}, config}, 0)
+		if err == nil {
+			t.Fatal("expected an error creating a dispatcher with invalid notifier", config.Name)
+		}
+	}
+
+	dispatcher, err := NewDispatcher([]Config{{Name: "rejected", Type: TypeWebhook, URL: rejecting.URL}, {Name: "slack", Type: TypeSlack, URL: server.URL}}, 1)
+	if err != nil {
+		t.Fatalf("failed to create dispatcher: %s", err)
+	}
+	if !dispatcher.Send(testTransition) {
+		t.Fatal("expected a transition to be queued")
+	}
+	dispatcher.Close(context.Background())
+
+	var message slackMessage
+	err = json.Unmarshal(*body, &message)
+	if err != nil {
+		t.Fatalf("expected the queued transition to be sent before closing, got %s: %s", err, *body)
+	}
+	if message.Text != testTransition.Summary() {
+		t.Fatalf("expected slack text %q, got %q", testTransition.Summary(), message.Text)
+	}
+
+	if dispatcher.Send(testTransition) {
+		t.Fatal("expected a transition sent after closing to be dropped")
+	}
+}
+
+// TestDispatcherCloseDeadline tests that closing a dispatcher stops waiting on a notifier that does not respond once
+// the close context is done
+func TestDispatcherCloseDeadline(t *testing.T) {
+	unblock := make(chan struct{})
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		select {
+		case <-unblock:
+		case <-r.Context().Done():
+		}
+	}))
+	t.Cleanup(server.Close)
+	t.Cleanup(func() { close(unblock) })
+
+	dispatcher, err := NewDispatcher([]Config{{Name: "hook", Type: TypeWebhook, URL: server.URL, Timeout: time.Minute}}, 10)
+	if err != nil {
+		t.Fatalf("failed to create dispatcher: %s", err)
+	}
+	for i := 0; i < 3; i++ {
+		if !dispatcher.Send(testTransition) {
+			t.Fatal("expected a transition to be queued")
+		}
+	}
+
+	ctx, cancel := context.WithTimeout(context.Background(), time.Millisecond*200)
+	defer cancel()
+	closed := make(chan struct{})
+	go func() {
+		dispatcher.Close(ctx)
+		close(closed)
+	}()
+	select {
+	case <-closed:
+	case <-time.After(time.Second * 5):
+		t.Fatal("expected closing the dispatcher to stop waiting once its context was done")
+	}
+}
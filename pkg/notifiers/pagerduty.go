@@ -0,0 +1,97 @@
+package notifiers
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"time"
+
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
+)
+
+// DefaultPagerDutyURL is the pagerduty events v2 API
+const DefaultPagerDutyURL = "https://events.pagerduty.com/v2/enqueue"
+
+// pagerDutyEvent is an event for the pagerduty events v2 API
+type pagerDutyEvent struct {
+	RoutingKey  string            `json:"routing_key"`
+	EventAction string            `json:"event_action"` // trigger or resolve
+	DedupKey    string            `json:"dedup_key"`
+	Payload     *pagerDutyPayload `json:"payload,omitempty"`
+}
+
+// pagerDutyPayload describes the alert of a triggered pagerduty event
+type pagerDutyPayload struct {
+	Summary       string                 `json:"summary"`
+	Source        string                 `json:"source"`
+	Severity      string                 `json:"severity"` // critical, error, warning or info
+	Timestamp     string                 `json:"timestamp,omitempty"`
+	Component     string                 `json:"component,omitempty"`
+	Group         string                 `json:"group,omitempty"`
+	Class         string                 `json:"class,omitempty"`
+	CustomDetails map[string]interface{} `json:"custom_details,omitempty"`
+}
+
+// pagerDutyNotifier triggers and resolves pagerduty alerts
+type pagerDutyNotifier struct {
+	url        string
+	headers    map[string]string
+	routingKey string
+}
+
+// newPagerDutyNotifier creates a pagerduty notifier from its config
+func newPagerDutyNotifier(config Config) (*pagerDutyNotifier, error) {
+	if len(config.RoutingKey) == 0 {
+		return nil, errors.New("pagerduty notifiers require a routingKey")
+	}
+
+	url := config.URL
+	if len(url) == 0 {
+		url = DefaultPagerDutyURL
+	}
+	return &pagerDutyNotifier{
+		url:        url,
+		headers:    config.Headers,
+		routingKey: config.RoutingKey,
+	}, nil
+}
+
+// Notify triggers an alert for a check or job that is failing or degraded, and resolves it once the check or job
+// is OK again.  Alerts are de-duplicated per check or job.
+func (n *pagerDutyNotifier) Notify(ctx context.Context, t Transition) error {
+	event := pagerDutyEvent{
+		RoutingKey:  n.routingKey,
+		EventAction: "resolve",
+		DedupKey:    "kuberhealthy/" + t.Workload + "/" + t.Namespace + "/" + t.Name,
+	}
+
+	if t.Severity != khstatev1.SeverityOK {
+		severity := "critical"
+		if t.Severity == khstatev1.SeverityWarning {
+			severity = "warning"
+		}
+		event.EventAction = "trigger"
+		event.Payload = &pagerDutyPayload{
+			Summary:   t.Summary(),
+			Source:    "kuberhealthy",
+			Severity:  severity,
+			Timestamp: t.Time.Format(time.RFC3339),
+			Component: t.Name,
+			Group:     t.Namespace,
+			Class:     t.Workload,
+			CustomDetails: map[string]interface{}{
+				"errors":   t.Errors,
+				"warnings": t.Warnings,
+				"node":     t.Node,
+				"uuid":     t.RunUUID,
+				"metadata": t.Metadata,
+			},
+		}
+	}
+
+	body, err := json.Marshal(event)
+	if err != nil {
+		return err
+	}
+	return postJSON(ctx, n.url, n.headers, body)
+}
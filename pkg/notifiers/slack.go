@@ -0,0 +1,74 @@
+package notifiers
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"strings"
+
+	khstatev1 "github.com/kuberhealthy/kuberhealthy/v2/pkg/apis/khstate/v1"
+)
+
+// slackMessage is the payload of a slack incoming webhook
+type slackMessage struct {
+	Text        string            `json:"text"`
+	Attachments []slackAttachment `json:"attachments,omitempty"`
+}
+
+// slackAttachment adds the errors or warnings of a transition to a slack message
+type slackAttachment struct {
+	Color string `json:"color"`
+	Text  string `json:"text"`
+}
+
+// slackNotifier posts messages to a slack incoming webhook
+type slackNotifier struct {
+	url     string
+	headers map[string]string
+}
+
+// newSlackNotifier creates a slack notifier from its config
+func newSlackNotifier(config Config) (*slackNotifier, error) {
+	if len(config.URL) == 0 {
+		return nil, errors.New("slack notifiers require an incoming webhook url")
+	}
+	return &slackNotifier{
+		url:     config.URL,
+		headers: config.Headers,
+	}, nil
+}
+
+// slackColor returns the attachment color for a severity
+func slackColor(severity khstatev1.Severity) string {
+	switch severity {
+	case khstatev1.SeverityFailing:
+		return "danger"
+	case khstatev1.SeverityWarning:
+		return "warning"
+	default:
+		return "good"
+	}
+}
+
+// Notify posts a message describing the transition to slack
+func (n *slackNotifier) Notify(ctx context.Context, t Transition) error {
+	details := append(append([]string{}, t.Errors...), t.Warnings...)
+	if len(details) == 0 {
+		details = []string{"No errors or warnings were reported."}
+	}
+
+	message := slackMessage{
+		Text: t.Summary(),
+		Attachments: []slackAttachment{
+			{
+				Color: slackColor(t.Severity),
+				Text:  strings.Join(details, "\n"),
+			},
+		},
+	}
+	body, err := json.Marshal(message)
+	if err != nil {
+		return err
+	}
+	return postJSON(ctx, n.url, n.headers, body)
+}
@@ -0,0 +1,73 @@
+package notifiers
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"errors"
+	"text/template"
+)
+
+// DefaultWebhookTemplate is the JSON body sent by webhook notifiers without a template
+const DefaultWebhookTemplate = `{
+  "name": {{ json .Name }},
+  "namespace": {{ json .Namespace }},
+  "workload": {{ json .Workload }},
+  "ok": {{ json .OK }},
+  "severity": {{ json .Severity }},
+  "previousSeverity": {{ json .PreviousSeverity }},
+  "errors": {{ json .Errors }},
+  "warnings": {{ json .Warnings }},
+  "node": {{ json .Node }},
+  "uuid": {{ json .RunUUID }},
+  "time": {{ json .Time }},
+  "metadata": {{ json .Metadata }}
+}`
+
+// webhookTemplateFuncs are the functions available to webhook templates.  json renders any value as JSON so that
+// strings are quoted and escaped correctly.
+var webhookTemplateFuncs = template.FuncMap{
+	"json": func(v interface{}) (string, error) {
+		b, err := json.Marshal(v)
+		return string(b), err
+	},
+}
+
+// webhookNotifier posts a templated JSON body to a URL
+type webhookNotifier struct {
+	url      string
+	headers  map[string]string
+	template *template.Template
+}
+
+// newWebhookNotifier creates a webhook notifier from its config
+func newWebhookNotifier(config Config) (*webhookNotifier, error) {
+	if len(config.URL) == 0 {
+		return nil, errors.New("webhook notifiers require a url")
+	}
+
+	body := config.Template
+	if len(body) == 0 {
+		body = DefaultWebhookTemplate
+	}
+	tmpl, err := template.New(config.Name).Funcs(webhookTemplateFuncs).Parse(body)
+	if err != nil {
+		return nil, errors.New("failed to parse webhook template: " + err.Error())
+	}
+
+	return &webhookNotifier{
+		url:      config.URL,
+		headers:  config.Headers,
+		template: tmpl,
+	}, nil
+}
+
+// Notify renders the template for the transition and posts it to the webhook
+func (n *webhookNotifier) Notify(ctx context.Context, t Transition) error {
+	var body bytes.Buffer
+	err := n.template.Execute(&body, t)
+	if err != nil {
+		return errors.New("failed to render webhook template: " + err.Error())
+	}
+	return postJSON(ctx, n.url, n.headers, body.Bytes())
+}
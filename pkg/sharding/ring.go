@@ -0,0 +1,73 @@
+package sharding
+
+import (
+	"hash/fnv"
+	"sort"
+	"strconv"
+)
+
+// virtualNodes is the number of points each member has on the ring.  More points spread keys more evenly across
+// members.
+const virtualNodes = 128
+
+// Ring is a consistent hash ring that assigns keys to members.  When a member joins or leaves, only the keys owned by
+// that member move, so most keys keep their owner.
+type Ring struct {
+	members []string          // the members of the ring, sorted
+	points  []uint64          // the hashes of the virtual nodes of all members, sorted
+	owners  map[uint64]string // the member of each virtual node
+}
+
+// NewRing creates a hash ring with the supplied members
+func NewRing(members []string) *Ring {
+	r := &Ring{
+		owners: make(map[uint64]string),
+	}
+
+	// members are added in sorted order so that every replica builds the same ring, even when hashes collide
+	r.members = append(r.members, members...)
+	sort.Strings(r.members)
+	for _, member := range r.members {
+		for i := 0; i < virtualNodes; i++ {
+			point := hash(member + "#" + strconv.Itoa(i))
+			if _, exists := r.owners[point]; exists {
+				continue
+			}
+			r.owners[point] = member
+			r.points = append(r.points, point)
+		}
+	}
+	sort.Slice(r.points, func(i, j int) bool {
+		return r.points[i] < r.points[j]
+	})
+	return r
+}
+
+// Owner returns the member that owns the supplied key, or a blank string if the ring has no members
+func (r *Ring) Owner(key string) string {
+	if len(r.points) == 0 {
+		return ""
+	}
+
+	// the owner is the member of the first virtual node at or after the hash of the key, wrapping around the ring
+	h := hash(key)
+	i := sort.Search(len(r.points), func(i int) bool {
+		return r.points[i] >= h
+	})
+	if i == len(r.points) {
+		i = 0
+	}
+	return r.owners[r.points[i]]
+}
+
+// Members returns the sorted members of the ring
+func (r *Ring) Members() []string {
+	return append([]string{}, r.members...)
+}
+
+// hash returns the 64 bit FNV-1a hash of a string
+func hash(s string) uint64 {
+	h := fnv.New64a()
+	_, _ = h.Write([]byte(s))
+	return h.Sum64()
+}
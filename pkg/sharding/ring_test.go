@@ -0,0 +1,42 @@
+package sharding
+
+import (
+	"strconv"
+	"testing"
+)
+
+// TestRingOwner tests that keys are spread across members and that only the keys of a removed member move
+func TestRingOwner(t *testing.T) {
+	if owner := NewRing(nil).Owner("kuberhealthy/dns"); owner != "" {
+		t.Fatalf("expected a ring without members to have no owner, got %s", owner)
+	}
+
+	keys := make([]string, 0, 3000)
+	for i := 0; i < cap(keys); i++ {
+		keys = append(keys, "namespace-"+strconv.Itoa(i%30)+"/check-"+strconv.Itoa(i))
+	}
+
+	three := NewRing([]string{"kuberhealthy-c", "kuberhealthy-a", "kuberhealthy-b"})
+	counts := make(map[string]int)
+	for _, key := range keys {
+		counts[three.Owner(key)]++
+	}
+	for _, member := range three.Members() {
+		if counts[member] < len(keys)/6 {
+			t.Fatalf("expected keys to be spread across members, got %v", counts)
+		}
+	}
+
+	// the same members in any order build the same ring
+	reordered := NewRing([]string{"kuberhealthy-b", "kuberhealthy-c", "kuberhealthy-a"})
+	two := NewRing([]string{"kuberhealthy-a", "kuberhealthy-c"})
+	for _, key := range keys {
+		owner := three.Owner(key)
+		if reordered.Owner(key) != owner {
+			t.Fatalf("expected key %s to have the same owner regardless of member order", key)
+		}
+		if owner != "kuberhealthy-b" && two.Owner(key) != owner {
+			t.Fatalf("expected key %s to stay with %s when another member leaves, but it moved to %s", key, owner, two.Owner(key))
+		}
+	}
+}
@@ -0,0 +1,305 @@
+// Package sharding distributes khchecks across kuberhealthy replicas.  Every replica heartbeats its own
+// coordination.k8s.io lease, and the replicas with an unexpired lease share the checks by consistent hashing.
+package sharding // import "github.com/kuberhealthy/kuberhealthy/v2/pkg/sharding"
+
+import (
+	"context"
+	"errors"
+	"reflect"
+	"sync"
+	"time"
+
+	log "github.com/sirupsen/logrus"
+	coordinationv1 "k8s.io/api/coordination/v1"
+	apierrors "k8s.io/apimachinery/pkg/api/errors"
+	metav1 "k8s.io/apimachinery/pkg/apis/meta/v1"
+	"k8s.io/client-go/kubernetes"
+)
+
+// DefaultLeasePrefix prefixes the name of the lease of each replica and identifies the replicas sharing checks
+const DefaultLeasePrefix = "kuberhealthy-shard"
+
+// DefaultLeaseDuration is how long a replica keeps its checks without renewing its lease
+const DefaultLeaseDuration = time.Second * 15
+
+// DefaultRenewPeriod is how often replicas renew their lease and look for other replicas
+const DefaultRenewPeriod = time.Second * 5
+
+// GroupLabel is set on the lease of every replica, with the lease prefix as its value
+const GroupLabel = "kuberhealthy.github.io/shard-group"
+
+// Config holds the settings for sharding checks across replicas
+type Config struct {
+	Enabled       bool          `yaml:"enabled,omitempty"`       // run checks on every replica instead of only on the master
+	LeasePrefix   string        `yaml:"leasePrefix,omitempty"`   // the prefix of the lease name of each replica (default: kuberhealthy-shard)
+	LeaseDuration time.Duration `yaml:"leaseDuration,omitempty"` // how long a replica keeps its checks without renewing its lease (default: 15s)
+	RenewPeriod   time.Duration `yaml:"renewPeriod,omitempty"`   // how often replicas renew their lease (default: 5s)
+}
+
+// withDefaults returns a copy of the config with defaults filled in for any unset values
+func (c Config) withDefaults() Config {
+	if len(c.LeasePrefix) == 0 {
+		c.LeasePrefix = DefaultLeasePrefix
+	}
+	if c.LeaseDuration == 0 {
+		c.LeaseDuration = DefaultLeaseDuration
+	}
+	if c.RenewPeriod == 0 {
+		c.RenewPeriod = DefaultRenewPeriod
+	}
+	return c
+}
+
+// Membership tracks the replicas sharing checks and which checks this replica owns
+type Membership struct {
+	client      kubernetes.Interface
+	namespace   string
+	identity    string
+	config      Config
+	mu          sync.RWMutex
+	ring        *Ring        // the ring of the replicas as last observed
+	lastRefresh time.Time    // when the members were last observed successfully
+	handoffs    []ringChange // the rings replaced within the last renew period
+}
+
+// ringChange is a ring that was replaced when the members changed
+type ringChange struct {
+	previous *Ring     // the ring before the change
+	changed  time.Time // when the change was observed
+}
+
+// NewMembership creates a membership for the replica with the supplied identity.  The leases of replicas are created
+// in the supplied namespace.
+func NewMembership(client kubernetes.Interface, namespace string, identity string, config Config) (*Membership, error) {
+	if len(namespace) == 0 {
+		return nil, errors.New("can not shard checks without a namespace")
+	}
+	if len(identity) == 0 {
+		return nil, errors.New("can not shard checks without an identity")
+	}
+
+	config = config.withDefaults()
+	if config.RenewPeriod >= config.LeaseDuration {
+		return nil, errors.New("sharding renewPeriod must be less than leaseDuration")
+	}
+
+	return &Membership{
+		client:    client,
+		namespace: namespace,
+		identity:  identity,
+		config:    config,
+		ring:      NewRing(nil),
+	}, nil
+}
+
+// Run renews the lease of this replica and refreshes the members until the context is canceled.  changedChan is
+// notified each time the members change.  The lease is removed when the context is canceled so that the other
+// replicas take over the checks of this replica right away.
+func (m *Membership) Run(ctx context.Context, changedChan chan struct{}) {
+	ticker := time.NewTicker(m.config.RenewPeriod)
+	defer ticker.Stop()
+
+	for {
+		if m.refresh(ctx) {
+			select {
+			case changedChan <- struct{}{}:
+			default:
+			}
+		}
+
+		select {
+		case <-ctx.Done():
+			m.leave()
+			return
+		case <-ticker.C:
+		}
+	}
+}
+
+// Owns indicates if this replica owns the supplied key
+func (m *Membership) Owns(key string) bool {
+	m.mu.RLock()
+	defer m.mu.RUnlock()
+	return m.ring.Owner(key) == m.identity
+}
+
+// HandoffDelay returns how long this replica should wait before it starts running a key it has taken over.  The
+// previous owner of the key notices the change in members within a renew period of it being observed here, so a key
+// taken over from a replica that still has a lease waits until then.  Keys of replicas that no longer have a lease
+// have already been given up and do not wait.
+func (m *Membership) HandoffDelay(key string) time.Duration {
+	m.mu.RLock()
+	defer m.mu.RUnlock()
+
+	now := time.Now()
+	members := m.ring.Members()
+	var delay time.Duration
+	for _, h := range m.handoffs {
+		remaining := h.changed.Add(m.config.RenewPeriod).Sub(now)
+		if remaining <= delay {
+			continue
+		}
+		owner := h.previous.Owner(key)
+		if len(owner) == 0 || owner == m.identity || !containsString(members, owner) {
+			continue
+		}
+		delay = remaining
+	}
+	return delay
+}
+
+// Members returns the sorted identities of the replicas sharing checks
+func (m *Membership) Members() []string {
+	m.mu.RLock()
+	defer m.mu.RUnlock()
+	return m.ring.Members()
+}
+
+// refresh renews the lease of this replica and rebuilds the ring from the unexpired leases.  Returns true if the
+// members changed.  If the members can not be observed for longer than the lease duration, this replica gives up all
+// of its keys, as the other replicas have taken them over by then.
+func (m *Membership) refresh(ctx context.Context) bool {
+	now := time.Now()
+	members, err := m.observeMembers(ctx, now)
+	if err != nil {
+		log.Errorln("sharding: error refreshing members:", err)
+		m.mu.RLock()
+		expired := now.Sub(m.lastRefresh) > m.config.LeaseDuration
+		m.mu.RUnlock()
+		if !expired {
+			return false
+		}
+		members = nil
+	}
+
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	if err == nil {
+		m.lastRefresh = now
+	}
+	ring := NewRing(members)
+	if reflect.DeepEqual(m.ring.Members(), ring.Members()) {
+		return false
+	}
+
+	// remember the previous owners of keys so that they are not taken over before the previous owners notice the
+	// change.  when this replica joins, the other replicas owned all keys before it.
+	previous := m.ring
+	if len(previous.Members()) == 0 {
+		previous = NewRing(removeString(members, m.identity))
+	}
+	handoffs := []ringChange{{previous: previous, changed: now}}
+	for _, h := range m.handoffs {
+		if now.Sub(h.changed) < m.config.RenewPeriod {
+			handoffs = append(handoffs, h)
+		}
+	}
+	m.handoffs = handoffs
+	m.ring = ring
+	log.Infoln("sharding: checks are now shared by", len(members), "replicas:", m.ring.Members())
+	return true
+}
+
+// observeMembers renews the lease of this replica and returns the identities of the replicas with unexpired leases
+func (m *Membership) observeMembers(ctx context.Context, now time.Time) ([]string, error) {
+	err := m.renew(ctx, now)
+	if err != nil {
+		return nil, err
+	}
+
+	leases, err := m.client.CoordinationV1().Leases(m.namespace).List(ctx, metav1.ListOptions{
+		LabelSelector: GroupLabel + "=" + m.config.LeasePrefix,
+	})
+	if err != nil {
+		return nil, err
+	}
+
+	var members []string
+	for _, lease := range leases.Items {
+		if lease.Spec.HolderIdentity == nil || lease.Spec.RenewTime == nil || lease.Spec.LeaseDurationSeconds == nil {
+			continue
+		}
+		expires := lease.Spec.RenewTime.Add(time.Duration(*lease.Spec.LeaseDurationSeconds) * time.Second)
+		if now.After(expires) {
+			continue
+		}
+		members = append(members, *lease.Spec.HolderIdentity)
+	}
+	return members, nil
+}
+
+// renew creates or renews the lease of this replica
+func (m *Membership) renew(ctx context.Context, now time.Time) error {
+	leases := m.client.CoordinationV1().Leases(m.namespace)
+	renewTime := metav1.NewMicroTime(now)
+	durationSeconds := int32(m.config.LeaseDuration.Seconds())
+
+	lease, err := leases.Get(ctx, m.leaseName(), metav1.GetOptions{})
+	if apierrors.IsNotFound(err) {
+		_, err = leases.Create(ctx, &coordinationv1.Lease{
+			ObjectMeta: metav1.ObjectMeta{
+				Name:      m.leaseName(),
+				Namespace: m.namespace,
+				Labels:    map[string]string{GroupLabel: m.config.LeasePrefix},
+			},
+			Spec: coordinationv1.LeaseSpec{
+				HolderIdentity:       &m.identity,
+				LeaseDurationSeconds: &durationSeconds,
+				AcquireTime:          &renewTime,
+				RenewTime:            &renewTime,
+			},
+		}, metav1.CreateOptions{})
+		return err
+	}
+	if err != nil {
+		return err
+	}
+
+	lease.Spec.HolderIdentity = &m.identity
+	lease.Spec.LeaseDurationSeconds = &durationSeconds
+	lease.Spec.RenewTime = &renewTime
+	_, err = leases.Update(ctx, lease, metav1.UpdateOptions{})
+	return err
+}
+
+// leave removes the lease of this replica and gives up all of its keys
+func (m *Membership) leave() {
+	m.mu.Lock()
+	m.ring = NewRing(nil)
+	m.mu.Unlock()
+
+	ctx, cancel := context.WithTimeout(context.Background(), m.config.RenewPeriod)
+	defer cancel()
+	err := m.client.CoordinationV1().Leases(m.namespace).Delete(ctx, m.leaseName(), metav1.DeleteOptions{})
+	if err != nil && !apierrors.IsNotFound(err) {
+		log.Errorln("sharding: error removing lease", m.leaseName(), err)
+		return
+	}
+	log.Infoln("sharding: removed lease", m.leaseName())
+}
+
+// containsString indicates if a string is in a slice of strings
+func containsString(s []string, value string) bool {
+	for _, v := range s {
+		if v == value {
+			return true
+		}
+	}
+	return false
+}
+
+// removeString returns a copy of a slice of strings without the supplied value
+func removeString(s []string, value string) []string {
+	var out []string
+	for _, v := range s {
+		if v != value {
+			out = append(out, v)
+		}
+	}
+	return out
+}
+
+// leaseName returns the name of the lease of this replica
+func (m *Membership) leaseName() string {
+	return m.config.LeasePrefix + "-" + m.identity
+}
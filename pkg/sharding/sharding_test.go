@@ -0,0 +1,153 @@
+package sharding
+
+import (
+	"context"
+	"strconv"
+	"testing"
+	"time"
+
+	"k8s.io/client-go/kubernetes/fake"
+)
+
+func TestNewMembership(t *testing.T) {
+	client := fake.NewSimpleClientset()
+
+	_, err := NewMembership(client, "", "kuberhealthy-a", Config{})
+	if err == nil {
+		t.Fatal("Expected an error when creating a membership without a namespace")
+	}
+
+	_, err = NewMembership(client, "kuberhealthy", "", Config{})
+	if err == nil {
+		t.Fatal("Expected an error when creating a membership without an identity")
+	}
+
+	_, err = NewMembership(client, "kuberhealthy", "kuberhealthy-a", Config{LeaseDuration: time.Second, RenewPeriod: time.Second * 2})
+	if err == nil {
+		t.Fatal("Expected an error when the renew period is longer than the lease duration")
+	}
+
+	m, err := NewMembership(client, "kuberhealthy", "kuberhealthy-a", Config{})
+	if err != nil {
+		t.Fatal(err)
+	}
+	if m.config.LeasePrefix != DefaultLeasePrefix || m.config.LeaseDuration != DefaultLeaseDuration {
+		t.Fatal("Expected default sharding settings to be applied but got", m.config)
+	}
+	if m.Owns("kuberhealthy/dns") {
+		t.Fatal("Expected a membership to own no keys before it has observed the members")
+	}
+}
+
+func TestMembershipRefresh(t *testing.T) {
+	ctx := context.Background()
+	client := fake.NewSimpleClientset()
+
+	first, err := NewMembership(client, "kuberhealthy", "kuberhealthy-a", Config{})
+	if err != nil {
+		t.Fatal(err)
+	}
+	second, err := NewMembership(client, "kuberhealthy", "kuberhealthy-b", Config{})
+	if err != nil {
+		t.Fatal(err)
+	}
+
+	keys := make([]string, 0, 100)
+	for i := 0; i < cap(keys); i++ {
+		keys = append(keys, "kuberhealthy/check-"+strconv.Itoa(i))
+	}
+
+	// a single replica owns every key
+	if !first.refresh(ctx) {
+		t.Fatal("Expected the first replica to see the members change when it joins")
+	}
+	for _, key := range keys {
+		if !first.Owns(key) {
+			t.Fatal("Expected the only replica to own key", key)
+		}
+	}
+
+	// once both replicas have seen each other, every key is owned by exactly one of them
+	if !second.refresh(ctx) || !first.refresh(ctx) {
+		t.Fatal("Expected both replicas to see the members change when the second joins")
+	}
+	if first.refresh(ctx) {
+		t.Fatal("Expected no change when the members are the same")
+	}
+	var firstOwned int
+	for _, key := range keys {
+		if first.Owns(key) == second.Owns(key) {
+			t.Fatal("Expected key", key, "to be owned by exactly one replica")
+		}
+		if first.Owns(key) {
+			firstOwned++
+		}
+	}
+	if firstOwned == 0 || firstOwned == len(keys) {
+		t.Fatal("Expected keys to be shared by both replicas but the first replica owns", firstOwned)
+	}
+
+	// a replica that leaves gives up its keys and the remaining replica takes them over
+	second.leave()
+	if second.Owns(keys[0]) || len(second.Members()) != 0 {
+		t.Fatal("Expected a replica that left to own no keys")
+	}
+	if !first.refresh(ctx) {
+		t.Fatal("Expected the first replica to see the members change when the second leaves")
+	}
+	for _, key := range keys {
+		if !first.Owns(key) {
+			t.Fatal("Expected the remaining replica to own key", key)
+		}
+	}
+}
+
+func TestMembershipHandoffDelay(t *testing.T) {
+	ctx := context.Background()
+	client := fake.NewSimpleClientset()
+	config := Config{LeaseDuration: time.Minute * 2, RenewPeriod: time.Minute}
+
+	first, err := NewMembership(client, "kuberhealthy", "kuberhealthy-a", config)
+	if err != nil {
+		t.Fatal(err)
+	}
+	second, err := NewMembership(client, "kuberhealthy", "kuberhealthy-b", config)
+	if err != nil {
+		t.Fatal(err)
+	}
+
+	keys := make([]string, 0, 100)
+	for i := 0; i < cap(keys); i++ {
+		keys = append(keys, "kuberhealthy/check-"+strconv.Itoa(i))
+	}
+
+	// the first replica has no one to take keys over from
+	first.refresh(ctx)
+	for _, key := range keys {
+		if first.HandoffDelay(key) != 0 {
+			t.Fatal("Expected the only replica to start key", key, "right away")
+		}
+	}
+
+	// a joining replica waits for the first replica to notice it before starting the keys it takes over
+	second.refresh(ctx)
+	first.refresh(ctx)
+	for _, key := range keys {
+		delay := second.HandoffDelay(key)
+		if second.Owns(key) && (delay <= 0 || delay > config.RenewPeriod) {
+			t.Fatal("Expected key", key, "taken over by the joining replica to wait up to a renew period but got", delay)
+		}
+		if first.Owns(key) && first.HandoffDelay(key) != 0 {
+			t.Fatal("Expected key", key, "kept by the first replica to not wait")
+		}
+	}
+
+	// keys of a replica that left are started right away, as that replica has already given them up
+	second.leave()
+	first.refresh(ctx)
+	for _, key := range keys {
+		if first.HandoffDelay(key) != 0 {
+			t.Fatal("Expected key", key, "taken over from a replica that left to start right away")
+		}
+	}
+}
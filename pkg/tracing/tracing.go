@@ -0,0 +1,173 @@
+// Package tracing records spans for check runs and hands them to an exporter once each run is done
+package tracing // import "github.com/kuberhealthy/kuberhealthy/v2/pkg/tracing"
+
+import (
+	"context"
+	"crypto/rand"
+	"encoding/hex"
+	"sync"
+	"time"
+
+	log "github.com/sirupsen/logrus"
+)
+
+// SpanData is the recorded data of a finished span
+type SpanData struct {
+	TraceID      string            // the hex encoded 16 byte id of the trace the span is part of
+	SpanID       string            // the hex encoded 8 byte id of the span
+	ParentSpanID string            // the id of the parent span, empty for the root span of a trace
+	Name         string            // the name of the operation the span covers
+	Start        time.Time         // when the operation started
+	End          time.Time         // when the operation ended
+	Attributes   map[string]string // attributes describing the operation
+	Error        string            // the error the operation ended with, if any
+}
+
+// SpanExporter sends finished spans to a tracing backend
+type SpanExporter interface {
+	ExportSpans(spans []SpanData) error
+}
+
+// Tracer creates spans and exports them when the root span of their trace ends.  A nil Tracer is valid and records
+// nothing, so callers do not need to check if tracing is enabled.
+type Tracer struct {
+	exporter SpanExporter
+}
+
+// NewTracer creates a tracer that exports spans with the supplied exporter
+func NewTracer(exporter SpanExporter) *Tracer {
+	return &Tracer{
+		exporter: exporter,
+	}
+}
+
+// trace collects the finished spans of a trace until its root span ends
+type trace struct {
+	mu    sync.Mutex
+	spans []SpanData
+	done  bool // set once the root span has ended and the trace was exported
+}
+
+// Span is a single timed operation within a trace.  All methods of a nil Span do nothing.
+type Span struct {
+	tracer *Tracer
+	trace  *trace
+	root   bool
+	mu     sync.Mutex
+	data   SpanData
+	ended  bool
+}
+
+// spanContextKey is the context key the current span is stored under
+type spanContextKey struct{}
+
+// SpanFromContext returns the span stored in the context, if any
+func SpanFromContext(ctx context.Context) *Span {
+	span, _ := ctx.Value(spanContextKey{}).(*Span)
+	return span
+}
+
+// Start starts a span.  If the context holds a span, the new span is its child, otherwise it is the root span of a
+// new trace.  The returned context holds the new span.
+func (t *Tracer) Start(ctx context.Context, name string) (context.Context, *Span) {
+	if t == nil {
+		return ctx, nil
+	}
+
+	span := &Span{
+		tracer: t,
+		data: SpanData{
+			SpanID:     randomHex(8),
+			Name:       name,
+			Start:      time.Now(),
+			Attributes: map[string]string{},
+		},
+	}
+
+	parent := SpanFromContext(ctx)
+	if parent != nil && parent.tracer == t {
+		span.trace = parent.trace
+		span.data.TraceID = parent.data.TraceID
+		span.data.ParentSpanID = parent.data.SpanID
+	} else {
+		span.trace = &trace{}
+		span.root = true
+		span.data.TraceID = randomHex(16)
+	}
+
+	return context.WithValue(ctx, spanContextKey{}, span), span
+}
+
+// SetAttribute sets an attribute on the span
+func (s *Span) SetAttribute(key string, value string) {
+	if s == nil {
+		return
+	}
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	s.data.Attributes[key] = value
+}
+
+// RecordError marks the span as failed with the supplied error.  A nil error is ignored.
+func (s *Span) RecordError(err error) {
+	if s == nil || err == nil {
+		return
+	}
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	s.data.Error = err.Error()
+}
+
+// End ends the span.  Ending the root span exports every span of its trace.  Ending a span more than once does nothing.
+func (s *Span) End() {
+	if s == nil {
+		return
+	}
+
+	s.mu.Lock()
+	if s.ended {
+		s.mu.Unlock()
+		return
+	}
+	s.ended = true
+	s.data.End = time.Now()
+	data := s.data
+	s.mu.Unlock()
+
+	s.trace.mu.Lock()
+	// spans that end after their trace was exported are exported on their own
+	if s.trace.done {
+		s.trace.mu.Unlock()
+		go s.tracer.export([]SpanData{data})
+		return
+	}
+	s.trace.spans = append(s.trace.spans, data)
+	if !s.root {
+		s.trace.mu.Unlock()
+		return
+	}
+	s.trace.done = true
+	spans := s.trace.spans
+	s.trace.spans = nil
+	s.trace.mu.Unlock()
+
+	go s.tracer.export(spans)
+}
+
+// export sends spans to the exporter of the tracer
+func (t *Tracer) export(spans []SpanData) {
+	err := t.exporter.ExportSpans(spans)
+	if err != nil {
+		log.Errorln("tracing: error exporting", len(spans), "spans:", err)
+	}
+}
+
+// randomHex returns n random bytes as a hex string
+func randomHex(n int) string {
+	b := make([]byte, n)
+	_, err := rand.Read(b)
+	if err != nil {
+		log.Errorln("tracing: failed to generate a random id:", err)
+	}
+	return hex.EncodeToString(b)
+}
@@ -0,0 +1,69 @@
+package tracing
+
+import (
+	"context"
+	"errors"
+	"testing"
+	"time"
+)
+
+// testExporter sends exported spans to a channel
+type testExporter struct {
+	exported chan []SpanData
+}
+
+// ExportSpans implements SpanExporter
+func (e *testExporter) ExportSpans(spans []SpanData) error {
+	e.exported <- spans
+	return nil
+}
+
+// TestTracer tests that child spans are exported with their root span once it ends
+func TestTracer(t *testing.T) {
+	exporter := &testExporter{exported: make(chan []SpanData, 1)}
+	tracer := NewTracer(exporter)
+
+	ctx, root := tracer.Start(context.Background(), "run")
+	root.SetAttribute("uuid", "1234")
+	_, child := tracer.Start(ctx, "create pod")
+	child.RecordError(errors.New("failed to create pod"))
+	child.End()
+	child.End()
+	root.End()
+
+	var spans []SpanData
+	select {
+	case spans = <-exporter.exported:
+	case <-time.After(time.Second * 5):
+		t.Fatal("Timed out waiting for spans to be exported")
+	}
+
+	if len(spans) != 2 {
+		t.Fatal("Expected 2 spans to be exported but got", len(spans))
+	}
+	childData, rootData := spans[0], spans[1]
+	if rootData.Name != "run" || rootData.ParentSpanID != "" || rootData.Attributes["uuid"] != "1234" {
+		t.Fatal("Unexpected root span:", rootData)
+	}
+	if childData.TraceID != rootData.TraceID || childData.ParentSpanID != rootData.SpanID {
+		t.Fatal("Expected child span to be part of the root span's trace but got", childData)
+	}
+	if childData.Error != "failed to create pod" {
+		t.Fatal("Expected child span to record its error but got", childData.Error)
+	}
+	if len(rootData.TraceID) != 32 || len(rootData.SpanID) != 16 {
+		t.Fatal("Unexpected trace or span id length:", rootData.TraceID, rootData.SpanID)
+	}
+}
+
+// TestNilTracer tests that a nil tracer records nothing without failing
+func TestNilTracer(t *testing.T) {
+	var tracer *Tracer
+	ctx, span := tracer.Start(context.Background(), "run")
+	if span != nil || SpanFromContext(ctx) != nil {
+		t.Fatal("Expected a nil tracer to not create spans")
+	}
+	span.SetAttribute("uuid", "1234")
+	span.RecordError(errors.New("failed"))
+	span.End()
+}